@@ -0,0 +1,80 @@
+
+use std::sync::Arc;
+
+use crate::command::{CommandOutput, CommandResult};
+use crate::diff::Change;
+use crate::files::Entry;
+use crate::hold::Hold;
+use crate::iostat::Monitor;
+use crate::job::Progress;
+use crate::pool::{BlockDevice, Control, CreateOptions, ImportOptions};
+use crate::property::Property;
+use crate::replication::SendOptions;
+use crate::status::PoolStatus;
+
+pub type ListResult = Result<Vec<CommandResult>, CommandOutput>;
+
+pub type PropertyResult = Result<Vec<Property>, CommandOutput>;
+
+pub type StatusResult = Result<PoolStatus, CommandOutput>;
+
+pub type SharedBackend = Arc<dyn Backend>;
+
+pub trait Backend: Send + Sync {
+
+    fn zfs_pools(&self) -> ListResult;
+    fn zfs_dataset(&self) -> ListResult;
+    fn zfs_volumes(&self) -> ListResult;
+    fn zfs_snapshots(&self) -> ListResult;
+    fn zfs_bookmarks(&self) -> ListResult;
+    fn zfs_list_properties(&self, types: &str, properties: &[String]) -> ListResult;
+
+    fn zfs_get_all(&self, dataset_name: &str) -> PropertyResult;
+    fn zfs_diff(&self, snapshot_name: &str, target: Option<&str>) -> Result<Vec<Change>, CommandOutput>;
+    fn zfs_holds(&self, snapshot_names: &[String]) -> Result<Vec<Hold>, CommandOutput>;
+    fn list_directory(&self, path: &str) -> Result<Vec<Entry>, CommandOutput>;
+    fn zpool_get_all(&self, pool_name: &str) -> PropertyResult;
+    fn zpool_status(&self, pool_name: &str) -> StatusResult;
+    fn zpool_status_all(&self) -> Result<Vec<PoolStatus>, CommandOutput>;
+    fn zpool_devices(&self) -> Result<Vec<BlockDevice>, CommandOutput>;
+    fn zpool_importable(&self, directories: &[String], destroyed: bool) -> Result<Vec<PoolStatus>, CommandOutput>;
+    fn zpool_iostat(&self, pool_names: &[String], monitor: &Monitor) -> CommandOutput;
+
+    fn zfs_create(&self, dataset_name: &str) -> CommandOutput;
+    fn zfs_rename(&self, old_dataset_name: &str, new_dataset_name: &str) -> CommandOutput;
+    fn zfs_clone(&self, snapshot_name: &str, new_dataset_name: &str) -> CommandOutput;
+    fn zfs_snapshot(&self, snapshot_name: &str) -> CommandOutput;
+    fn zfs_snapshot_all(&self, snapshot_names: &[String]) -> CommandOutput;
+    fn zfs_bookmark(&self, snapshot_name: &str, bookmark_name: &str) -> CommandOutput;
+    fn zfs_destroy(&self, dataset_name: &str) -> CommandOutput;
+    fn zfs_destroy_estimate(&self, snapshot_names: &[String]) -> Result<u64, CommandOutput>;
+    fn zfs_rollback(&self, snapshot_name: &str) -> CommandOutput;
+    fn zfs_promote(&self, dataset_name: &str) -> CommandOutput;
+    fn zfs_hold(&self, tag: &str, snapshot_name: &str) -> CommandOutput;
+    fn zfs_release(&self, tag: &str, snapshot_name: &str) -> CommandOutput;
+    fn zfs_set(&self, dataset_name: &str, property: &str, value: &str) -> CommandOutput;
+    fn zfs_inherit(&self, dataset_name: &str, property: &str) -> CommandOutput;
+    fn zfs_send_estimate(&self, send: &SendOptions) -> Result<u64, CommandOutput>;
+    fn zfs_send(&self, send: &SendOptions, progress: &Progress) -> CommandOutput;
+    fn zfs_receive_abort(&self, dataset_name: &str) -> CommandOutput;
+    fn restore_files(&self, sources: &[String], directory: &str) -> CommandOutput;
+
+    fn zpool_create(&self, create: &CreateOptions, dry_run: bool) -> CommandOutput;
+    fn zpool_file_vdev(&self, path: &str, size: u64) -> CommandOutput;
+    fn zpool_destroy(&self, pool_name: &str) -> CommandOutput;
+    fn zpool_import(&self, import: &ImportOptions) -> CommandOutput;
+    fn zpool_export(&self, pool_name: &str) -> CommandOutput;
+    fn zpool_scrub(&self, pool_name: &str, control: Control) -> CommandOutput;
+    fn zpool_trim(&self, pool_name: &str, control: Control) -> CommandOutput;
+    fn zpool_initialize(&self, pool_name: &str, control: Control) -> CommandOutput;
+    fn zpool_set(&self, pool_name: &str, property: &str, value: &str) -> CommandOutput;
+
+    fn zpool_offline(&self, pool_name: &str, device: &str) -> CommandOutput;
+    fn zpool_online(&self, pool_name: &str, device: &str) -> CommandOutput;
+    fn zpool_attach(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput;
+    fn zpool_detach(&self, pool_name: &str, device: &str) -> CommandOutput;
+    fn zpool_replace(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput;
+    fn zpool_add(&self, pool_name: &str, vdevs: &[String], dry_run: bool) -> CommandOutput;
+    fn zpool_remove(&self, pool_name: &str, device: &str) -> CommandOutput;
+    fn zpool_clear(&self, pool_name: &str, device: Option<&str>) -> CommandOutput;
+}
@@ -0,0 +1,150 @@
+
+use std::mem;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Format {
+    Text,
+    Bytes,
+    Ratio,
+    Percent,
+    Time,
+}
+
+pub struct Column {
+
+    pub property: &'static str,
+    pub header: &'static str,
+    pub width: usize,
+    pub format: Format,
+    pub visible: bool,
+    pub computed: bool,
+}
+
+const fn column(property: &'static str, header: &'static str, width: usize, format: Format, visible: bool) -> Column {
+    Column { property, header, width, format, visible, computed: false }
+}
+
+const fn computed(property: &'static str, header: &'static str, width: usize, format: Format, visible: bool) -> Column {
+    Column { property, header, width, format, visible, computed: true }
+}
+
+pub const SUBTREE: &str = "subtree";
+
+pub const RESUME_TOKEN: &str = "receive_resume_token";
+
+pub const ACTIVITY: &str = "activity";
+
+pub const HOLDS: &str = "holds";
+
+pub const POOL_COLUMNS: &[Column] = &[
+    column("size",          "SIZE",   6, Format::Bytes,   true),
+    column("allocated",     "ALLOC",  6, Format::Bytes,   true),
+    column("free",          "FREE",   6, Format::Bytes,   true),
+    column("fragmentation", "FRAG",   4, Format::Percent, true),
+    column("capacity",      "CAP",    4, Format::Percent, true),
+    column("dedupratio",    "DEDUP",  5, Format::Ratio,   false),
+    column("health",        "HEALTH", 8, Format::Text,    true),
+];
+
+pub const DATASET_COLUMNS: &[Column] = &[
+    column("used",          "USED",       6, Format::Bytes, true),
+    column("available",     "AVAIL",      6, Format::Bytes, true),
+    column("referenced",    "REFER",      6, Format::Bytes, true),
+    column("compressratio", "RATIO",      5, Format::Ratio, true),
+    column("mountpoint",    "MOUNTPOINT", 16, Format::Text, false),
+    column("creation",      "CREATION",   16, Format::Time, false),
+    column("origin",        "ORIGIN",     20, Format::Text, false),
+    column(RESUME_TOKEN,    "RESUME",     16, Format::Text, false),
+    computed(SUBTREE,       "SUBTREE",    7, Format::Bytes, true),
+];
+
+pub const VOLUME_COLUMNS: &[Column] = &[
+    column("used",          "USED",     6, Format::Bytes, true),
+    column("available",     "AVAIL",    6, Format::Bytes, false),
+    column("referenced",    "REFER",    6, Format::Bytes, true),
+    column("volsize",       "VOLSIZE",  7, Format::Bytes, true),
+    column("compressratio", "RATIO",    5, Format::Ratio, true),
+    column("creation",      "CREATION", 16, Format::Time, false),
+    column("origin",        "ORIGIN",   20, Format::Text, false),
+    column(RESUME_TOKEN,    "RESUME",   16, Format::Text, false),
+];
+
+pub const SNAPSHOT_COLUMNS: &[Column] = &[
+    column("used",          "USED",     6, Format::Bytes, true),
+    column("referenced",    "REFER",    6, Format::Bytes, true),
+    column("written",       "WRITTEN",  7, Format::Bytes, false),
+    column("compressratio", "RATIO",    5, Format::Ratio, false),
+    column("creation",      "CREATION", 16, Format::Time, true),
+    column("clones",        "CLONES",   16, Format::Text, true),
+    column("userrefs",      "USERREFS", 8, Format::Text, false),
+    computed(HOLDS,         "HOLDS",    12, Format::Text, true),
+];
+
+pub const BOOKMARK_COLUMNS: &[Column] = &[
+    column("creation",      "CREATION", 16, Format::Time, true),
+    column("guid",          "GUID",     20, Format::Text, true),
+];
+
+pub fn properties(columns: &[Column]) -> String {
+
+    let mut properties = vec!["name"];
+    properties.extend(columns.iter().filter(|column| !column.computed).map(|column| column.property));
+
+    properties.join(",")
+}
+
+pub fn format_value(value: &str, format: Format) -> String {
+
+    if value.is_empty() || value == "-" {
+        return "-".to_string();
+    }
+
+    match format {
+        Format::Text => value.to_string(),
+        Format::Bytes => match value.parse::<u64>() {
+            Ok(bytes) => format_size(bytes),
+            Err(_) => value.to_string(),
+        },
+        Format::Ratio => format!("{}x", value.trim_end_matches('x')),
+        Format::Percent => format!("{}%", value.trim_end_matches('%')),
+        Format::Time => match value.parse::<i64>() {
+            Ok(time) => format_time(time),
+            Err(_) => value.to_string(),
+        },
+    }
+}
+
+pub fn format_size(bytes: u64) -> String {
+
+    const UNITS: [&str; 6] = ["B", "K", "M", "G", "T", "P"];
+
+    let mut size = bytes as f64;
+    let mut unit = 0;
+
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+
+    if unit == 0 {
+        format!("{}{}", bytes, UNITS[unit])
+    } else if size < 10.0 {
+        format!("{:.2}{}", size, UNITS[unit])
+    } else if size < 100.0 {
+        format!("{:.1}{}", size, UNITS[unit])
+    } else {
+        format!("{:.0}{}", size, UNITS[unit])
+    }
+}
+
+pub fn format_time(time: i64) -> String {
+
+    let time = time as libc::time_t;
+    let mut tm: libc::tm = unsafe { mem::zeroed() };
+
+    if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+        return time.to_string();
+    }
+
+    format!("{:04}-{:02}-{:02} {:02}:{:02}", tm.tm_year + 1900, tm.tm_mon + 1, tm.tm_mday, tm.tm_hour, tm.tm_min)
+}
@@ -1,170 +1,823 @@
 
-use std::process;
+use std::collections::HashMap;
+use std::collections::HashSet;
+use std::fs::{self, File};
+use std::io::{BufRead, BufReader, Read, Write};
+use std::os::unix::fs::MetadataExt;
+use std::path::Path;
+use std::process::{self, Stdio};
 use std::thread;
 
+use crate::backend::{Backend, ListResult, PropertyResult, StatusResult};
+use crate::column;
+use crate::diff::{self, Change};
+use crate::files::{self, Entry};
+use crate::hold::{self, Hold};
+use crate::iostat::{self, Monitor};
+use crate::job::Progress;
+use crate::pool::{BlockDevice, Control, CreateOptions, ImportOptions};
+use crate::property;
+use crate::replication::{self, SendOptions, Target};
+use crate::retention;
+use crate::status;
+
+#[derive(Clone)]
 pub struct CommandResult {
 
     pub name: String,
-    pub used: String,   
+    pub properties: HashMap<String, String>,
 }
 
+impl CommandResult {
 
-pub fn list_command(cmd: &str, arguments: &Vec<&str>) -> Vec<CommandResult> {
+    pub fn new(name: String) -> CommandResult {
 
-    let mut command = process::Command::new(cmd);
-    let output = command.args(arguments).output().expect("Failure running command: list_command").stdout;
-    let command_output = String::from_utf8_lossy(&output).to_string();
+        CommandResult {
+            name,
+            properties: HashMap::new(),
+        }
+    }
+
+    pub fn property(&self, property: &str) -> &str {
+
+        match self.properties.get(property) {
+            Some(value) => value.as_str(),
+            None => "-",
+        }
+    }
+}
+
+pub struct CommandOutput {
+
+    pub command: String,
+    pub success: bool,
+    pub code: Option<i32>,
+    pub stdout: String,
+    pub stderr: String,
+}
+
+impl CommandOutput {
+
+    pub fn new(cmd: &str, arguments: &[&str], output: std::io::Result<process::Output>) -> CommandOutput {
+
+        let command = format!("{} {}", cmd, arguments.join(" "));
+
+        match output {
+            Ok(output) => CommandOutput {
+                command,
+                success: output.status.success(),
+                code: output.status.code(),
+                stdout: String::from_utf8_lossy(&output.stdout).to_string(),
+                stderr: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+            },
+            Err(error) => CommandOutput {
+                command,
+                success: false,
+                code: None,
+                stdout: String::new(),
+                stderr: error.to_string(),
+            },
+        }
+    }
+
+    pub fn failed(command: String, error: std::io::Error) -> CommandOutput {
+
+        CommandOutput {
+            command,
+            success: false,
+            code: None,
+            stdout: String::new(),
+            stderr: error.to_string(),
+        }
+    }
+
+    pub fn simulated(cmd: &str, arguments: &[&str], result: Result<String, String>) -> CommandOutput {
+
+        let command = format!("{} {}", cmd, arguments.join(" "));
+
+        match result {
+            Ok(stdout) => CommandOutput {
+                command,
+                success: true,
+                code: Some(0),
+                stdout,
+                stderr: String::new(),
+            },
+            Err(stderr) => CommandOutput {
+                command,
+                success: false,
+                code: Some(1),
+                stdout: String::new(),
+                stderr,
+            },
+        }
+    }
+
+    pub fn error_message(&self) -> String {
+
+        if !self.stderr.is_empty() {
+            self.stderr.to_owned()
+        } else if let Some(code) = self.code {
+            format!("exited with code {}", code)
+        } else {
+            "terminated by signal".to_string()
+        }
+    }
+}
+
+
+pub fn list_command(cmd: &str, arguments: &[&str], properties: &str) -> ListResult {
+
+    let mut arguments = arguments.to_vec();
+    arguments.extend(&["-H", "-p", "-o", properties]);
+
+    let output = run_command(cmd, &arguments);
+    if !output.success {
+        return Err(output);
+    }
 
-    let lines: Vec<&str> = command_output.split("\n").collect();
+    let properties: Vec<&str> = properties.split(',').collect();
 
     let mut result = Vec::new();
 
-    for line in lines.iter() {
+    for line in output.stdout.lines() {
 
-        let mut split = line.split_whitespace();
+        if line.is_empty() { continue }
 
-        let name = match split.next() {
-            Some(n) => n.to_string(),
-            None => String::new(),
-        };
+        let mut command_result = CommandResult::new(String::new());
 
-        let used = match split.next() {
-            Some(n) => n.to_string(),
-            None => String::new(),
-        };
+        for (property, value) in properties.iter().zip(line.split('\t')) {
+            if *property == "name" {
+                command_result.name = value.to_string();
+            } else {
+                command_result.properties.insert(property.to_string(), value.to_string());
+            }
+        }
 
-        let command_result = CommandResult {
-            name: name,
-            used: used,
-        };
-        
         result.push(command_result);
     }
 
-    result
+    Ok(result)
 }
 
-pub fn run_command(cmd: &str, arguments: &Vec<&str>) {
+pub fn get_command(cmd: &str, name: &str) -> PropertyResult {
 
-    let mut command = process::Command::new(cmd);
-    let _output = command.args(arguments).output().expect("Failure running command: run_command");
+    let output = run_command(cmd, &["get", "all", "-H", "-p", name]);
+    if !output.success {
+        return Err(output);
+    }
+
+    Ok(property::parse_properties(output.stdout.as_str()))
 }
 
-pub fn is_zfs_installed() -> bool {
+// -t and -i add the progress of a trim or initialize to every device.
+pub fn status_command(pool_name: &str) -> StatusResult {
 
-    let mut command = process::Command::new("which");
-    let temp_output = command.arg("zfs").output().expect("Failure running command: is_zfs_installed");
-    let output = String::from_utf8_lossy(&temp_output.stdout);
-    
-    output.contains("zfs")
+    let output = run_command("zpool", &["status", "-P", "-t", "-i", pool_name]);
+    if !output.success {
+        return Err(output);
+    }
+
+    Ok(status::parse_status(output.stdout.as_str()))
 }
 
-pub fn zfs_create(dataset_name: String) {
+pub fn status_all_command() -> Result<Vec<status::PoolStatus>, CommandOutput> {
+
+    let output = run_command("zpool", &["status", "-P", "-t", "-i"]);
+    if !output.success {
+        return Err(output);
+    }
+
+    Ok(status::parse_pools(output.stdout.as_str()))
+}
 
-    thread::spawn(move || { 
+fn control_command(operation: &str, pool_name: &str, control: Control) -> CommandOutput {
 
-        let arguments = vec!["create", dataset_name.as_str()];
+    let arguments = control.arguments(operation, pool_name);
+    let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
 
-        run_command("zfs", &arguments);
-    });
+    run_command("zpool", &arguments)
 }
 
-pub fn zfs_rename(old_dataset_name: String, new_dataset_name: String) {
+const BLOCK_CLASS: &str = "/sys/class/block";
+
+// The disk a device belongs to: partitions map to their parent disk, whose
+// sysfs directory holds theirs, and whole disks to themselves.
+fn whole_disk(block_class: &Path, device: &str) -> String {
 
-    thread::spawn(move || { 
+    let block = device.rsplit('/').next().unwrap_or("");
+    if !block_class.join(block).join("partition").exists() {
+        return device.to_string();
+    }
 
-        let arguments = vec!["rename", old_dataset_name.as_str(), new_dataset_name.as_str()];
+    match fs::canonicalize(block_class.join(block).join("..")) {
+        Ok(parent) => match parent.file_name() {
+            Some(disk) => format!("/dev/{}", disk.to_string_lossy()),
+            None => device.to_string(),
+        },
+        Err(_) => device.to_string(),
+    }
+}
 
-        run_command("zfs", &arguments);
-    });
+// The disks holding the given devices, compared whole so that a partition of
+// sda does not mark sdab as in use.
+fn disks_in_use<I: Iterator<Item = String>>(block_class: &Path, devices: I) -> HashSet<String> {
+    devices.map(|device| whole_disk(block_class, device.as_str())).collect()
 }
 
-pub fn zfs_clone(snapshot_name: String, new_dataset_name: String) {
+// Lists the disks in /dev/disk/by-id, leaving out those that are part of an
+// imported pool, mounted, used as swap or held by device-mapper or md, and
+// empty drives.
+pub fn devices_command() -> Result<Vec<BlockDevice>, CommandOutput> {
+
+    let entries = match fs::read_dir("/dev/disk/by-id") {
+        Ok(entries) => entries,
+        Err(error) => return Err(CommandOutput::failed("ls /dev/disk/by-id".to_string(), error)),
+    };
 
-    thread::spawn(move || { 
+    let output = run_command("zpool", &["list", "-v", "-H", "-P"]);
+    let pools = output.stdout.lines()
+        .filter_map(|line| line.split('\t').find(|field| !field.is_empty()))
+        .filter(|field| field.trim().starts_with('/'))
+        .map(|field| field.trim().to_string());
 
-        let arguments = vec!["clone", snapshot_name.as_str(), new_dataset_name.as_str()];
+    let mounts = fs::read_to_string("/proc/mounts").unwrap_or_default();
+    let swaps = fs::read_to_string("/proc/swaps").unwrap_or_default();
+    let mounted = mounts.lines().chain(swaps.lines())
+        .filter_map(|line| line.split_whitespace().next())
+        .filter(|device| device.starts_with("/dev/"))
+        .map(|device| device.to_string());
+
+    let in_use = disks_in_use(Path::new(BLOCK_CLASS), pools.chain(mounted)
+        .filter_map(|path| fs::canonicalize(path).ok())
+        .map(|path| path.to_string_lossy().to_string()));
+
+    let mut seen = HashSet::new();
+    let mut devices = Vec::new();
+
+    let mut paths: Vec<_> = entries.filter_map(|entry| entry.ok()).map(|entry| entry.path()).collect();
+    paths.sort();
+
+    for path in paths {
+
+        let name = path.to_string_lossy().to_string();
+        if name.contains("-part") || name.contains("/wwn-") { continue }
+
+        let device = match fs::canonicalize(&path) {
+            Ok(device) => device.to_string_lossy().to_string(),
+            Err(_) => continue,
+        };
 
-        run_command("zfs", &arguments);
-    });
+        if !seen.insert(device.to_owned()) { continue }
+        if in_use.contains(&device) { continue }
+
+        let block = device.rsplit('/').next().unwrap_or("");
+        let holders = fs::read_dir(format!("{}/{}/holders", BLOCK_CLASS, block)).map(|holders| holders.count()).unwrap_or(0);
+        if holders > 0 { continue }
+
+        let sectors = fs::read_to_string(format!("{}/{}/size", BLOCK_CLASS, block)).unwrap_or_default();
+        let size = sectors.trim().parse::<u64>().unwrap_or(0) * 512;
+        if size == 0 { continue }
+
+        devices.push(BlockDevice { path: name, size });
+    }
+
+    Ok(devices)
 }
 
-pub fn zfs_snapshot(snapshot_name: String) {
+pub fn import_list_command(directories: &[String], destroyed: bool) -> Result<Vec<status::PoolStatus>, CommandOutput> {
 
-    thread::spawn(move || { 
+    let arguments = ImportOptions::search_arguments(directories, destroyed);
+    let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
 
-        let arguments = vec!["snapshot", snapshot_name.as_str()];
+    let output = run_command("zpool", &arguments);
+    if !output.success {
+        if output.stderr.contains("no pools available") {
+            return Ok(Vec::new());
+        }
+        return Err(output);
+    }
 
-        run_command("zfs", &arguments);
-    });
+    Ok(status::parse_pools(output.stdout.as_str()))
 }
 
-pub fn zfs_pools() -> Vec<CommandResult> {
-    
-    let arguments = vec!["list", "-o", "name,size", "-H"];
-    list_command("zpool", &arguments)
+pub fn diff_command(snapshot_name: &str, target: Option<&str>) -> Result<Vec<Change>, CommandOutput> {
+
+    let arguments = diff::arguments(snapshot_name, target);
+    let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+    let output = run_command("zfs", &arguments);
+    if !output.success {
+        return Err(output);
+    }
+
+    Ok(diff::parse_diff(output.stdout.as_str()))
 }
 
-pub fn zfs_dataset() -> Vec<CommandResult> {
-    let arguments = vec!["list", "-o", "name,used", "-H"];
-    list_command("zfs", &arguments)
+pub fn holds_command(snapshot_names: &[String]) -> Result<Vec<Hold>, CommandOutput> {
+
+    let mut arguments = vec!["holds", "-H", "-p"];
+    arguments.extend(snapshot_names.iter().map(|name| name.as_str()));
+
+    let output = run_command("zfs", &arguments);
+    if !output.success {
+        return Err(output);
+    }
+
+    Ok(hold::parse_holds(output.stdout.as_str()))
 }
 
-pub fn zfs_volumes() -> Vec<CommandResult> {
-    
-    let arguments = vec!["list", "-H", "-o", "name,used", "-t", "volume"];
-    list_command("zfs", &arguments)
+pub fn directory_command(path: &str) -> Result<Vec<Entry>, CommandOutput> {
+
+    let entries = match fs::read_dir(path) {
+        Ok(entries) => entries,
+        Err(error) => return Err(CommandOutput::failed(format!("ls {}", path), error)),
+    };
+
+    let mut result: Vec<Entry> = entries.filter_map(|entry| entry.ok()).filter_map(|entry| {
+        let metadata = entry.path().symlink_metadata().ok()?;
+
+        Some(Entry {
+            name: entry.file_name().to_string_lossy().to_string(),
+            file_type: files::file_type(&metadata.file_type()),
+            size: metadata.len(),
+            mode: metadata.mode(),
+            modified: metadata.mtime(),
+        })
+    }).collect();
+
+    files::sort(&mut result);
+    Ok(result)
 }
 
-pub fn zfs_snapshots() -> Vec<CommandResult> {
-    let arguments = vec!["list", "-H", "-o", "name,used", "-t", "snapshot"];
-    list_command("zfs", &arguments)
+pub fn estimate_command(send: &SendOptions) -> Result<u64, CommandOutput> {
+
+    let arguments = send.estimate_arguments();
+    let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+    let output = run_command("zfs", &arguments);
+    if !output.success {
+        return Err(output);
+    }
+
+    let text = format!("{}\n{}", output.stdout, output.stderr);
+    Ok(replication::parse_estimate(text.as_str()).unwrap_or(0))
 }
 
-pub fn zfs_destroy(selected_elements: Vec<String>) {
+// Collects the stderr of a child on a thread of its own, so that a child
+// filling the pipe does not block while its stdout is being copied.
+fn read_stderr(child: &mut process::Child) -> thread::JoinHandle<String> {
 
-    thread::spawn(|| { 
+    let stderr = child.stderr.take();
 
-        for element in selected_elements {
-            let arguments = vec!["destroy", element.as_str()];
+    thread::spawn(move || {
+        let mut bytes = Vec::new();
+        if let Some(mut stderr) = stderr {
+            let _ = stderr.read_to_end(&mut bytes);
+        }
+        String::from_utf8_lossy(&bytes).to_string()
+    })
+}
 
-            run_command("zfs", &arguments);
+// Runs "zfs send | zfs receive" (or "zfs send > file"), copying the stream
+// through zc so the transferred bytes can be reported while it runs.
+pub fn send_command(send: &SendOptions, progress: &Progress) -> CommandOutput {
+
+    let send_arguments = send.send_arguments();
+    let mut command = format!("zfs {}", send_arguments.join(" "));
+
+    let mut sender = match process::Command::new("zfs").args(&send_arguments)
+        .stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(sender) => sender,
+        Err(error) => return CommandOutput::failed(command, error),
+    };
+    let send_errors = read_stderr(&mut sender);
+
+    let (mut output, mut receiver): (Box<dyn Write>, Option<process::Child>) = match &send.target {
+        Target::Dataset(_) => {
+            let receive_arguments = send.receive_arguments();
+            command = format!("{} | zfs {}", command, receive_arguments.join(" "));
+
+            match process::Command::new("zfs").args(&receive_arguments)
+                .stdin(Stdio::piped()).stdout(Stdio::null()).stderr(Stdio::piped()).spawn() {
+                Ok(mut receiver) => (Box::new(receiver.stdin.take().unwrap()), Some(receiver)),
+                Err(error) => { let _ = sender.kill(); let _ = sender.wait(); return CommandOutput::failed(command, error) },
+            }
+        },
+        Target::File(path) => {
+            command = format!("{} > {}", command, path);
+
+            match File::create(path) {
+                Ok(file) => (Box::new(file), None),
+                Err(error) => { let _ = sender.kill(); let _ = sender.wait(); return CommandOutput::failed(command, error) },
+            }
+        },
+    };
+    let receive_errors = receiver.as_mut().map(read_stderr);
+
+    let mut stream = sender.stdout.take().unwrap();
+    let mut buffer = vec![0; 128 * 1024];
+    let mut write_error = None;
+
+    loop {
+        match stream.read(&mut buffer) {
+            Ok(0) => break,
+            Ok(count) => {
+                if let Err(error) = output.write_all(&buffer[..count]) {
+                    write_error = Some(error.to_string());
+                    let _ = sender.kill();
+                    break;
+                }
+                progress.add(count as u64);
+            },
+            Err(error) => { write_error = Some(error.to_string()); break },
         }
-    });
+    }
+
+    drop(output);
+
+    let send_status = (sender.wait(), send_errors);
+    let receive_status = receiver.zip(receive_errors).map(|(mut receiver, receive_errors)| (receiver.wait(), receive_errors));
+
+    let mut success = true;
+    let mut code = Some(0);
+    let mut stderr = Vec::new();
+
+    for (status, errors) in std::iter::once(send_status).chain(receive_status) {
+        match status {
+            Ok(status) => {
+                if !status.success() {
+                    success = false;
+                    code = status.code();
+                }
+            },
+            Err(error) => {
+                success = false;
+                code = None;
+                stderr.push(error.to_string());
+            },
+        }
+        stderr.push(errors.join().unwrap_or_default().trim().to_string());
+    }
+
+    if let Some(error) = write_error {
+        if success { success = false; code = None; }
+        stderr.push(error);
+    }
+
+    stderr.retain(|message| !message.is_empty());
+
+    CommandOutput {
+        command,
+        success,
+        code,
+        stdout: String::new(),
+        stderr: stderr.join("\n"),
+    }
 }
 
-pub fn zfs_rollback(selected_elements: Vec<String>) {
+// Follows `zpool iostat` until the monitor is stopped, passing every line to
+// it as soon as it is printed. -y leaves out the averages since boot.
+pub fn iostat_command(pool_names: &[String], monitor: &Monitor) -> CommandOutput {
 
-    thread::spawn(|| { 
+    let interval = iostat::INTERVAL.to_string();
 
-        for element in selected_elements {
-            let arguments = vec!["rollback", "-rf", element.as_str()];
+    let mut arguments = vec!["iostat", "-Hpvy", "-l", "-q"];
+    arguments.extend(pool_names.iter().map(|pool_name| pool_name.as_str()));
+    arguments.push(interval.as_str());
 
-            run_command("zfs", &arguments);
+    let mut child = match process::Command::new("zpool").args(&arguments)
+        .stdout(Stdio::piped()).stderr(Stdio::piped()).spawn() {
+        Ok(child) => child,
+        Err(error) => return CommandOutput::failed(format!("zpool {}", arguments.join(" ")), error),
+    };
+
+    let mut pool = String::new();
+
+    for line in BufReader::new(child.stdout.take().unwrap()).lines() {
+        if monitor.is_stopped() { break }
+
+        match line {
+            Ok(line) => if let Some(stat) = iostat::parse_line(line.as_str(), &mut pool) { monitor.update(stat) },
+            Err(_) => break,
         }
-    });
+    }
+
+    if monitor.is_stopped() {
+        let _ = child.kill();
+    }
+
+    CommandOutput::new("zpool", &arguments, child.wait_with_output())
 }
 
-pub fn zpool_destroy(selected_elements: Vec<String>) {
+pub fn run_command(cmd: &str, arguments: &[&str]) -> CommandOutput {
 
-    thread::spawn(|| { 
+    let mut command = process::Command::new(cmd);
+    let output = command.args(arguments).output();
 
-        for element in selected_elements {
-            let arguments = vec!["destroy", element.as_str()];
+    CommandOutput::new(cmd, arguments, output)
+}
 
-            run_command("zpool", &arguments);
-        }
-    });
+pub fn pool_name(name: &str) -> &str {
+    name.split(['/', '@', '#']).next().unwrap_or(name)
+}
+
+pub fn is_zfs_installed() -> bool {
+
+    let mut command = process::Command::new("which");
+    let temp_output = command.arg("zfs").output().expect("Failure running command: is_zfs_installed");
+    let output = String::from_utf8_lossy(&temp_output.stdout);
+
+    output.contains("zfs")
 }
 
-pub fn zpool_scrub(selected_elements: Vec<String>) {
+pub struct Cli;
 
-    thread::spawn(|| { 
+impl Backend for Cli {
 
-        for element in selected_elements {
-            let arguments = vec!["scrub", element.as_str()];
+    fn zfs_pools(&self) -> ListResult {
+        list_command("zpool", &["list"], column::properties(column::POOL_COLUMNS).as_str())
+    }
+
+    fn zfs_dataset(&self) -> ListResult {
+        list_command("zfs", &["list"], column::properties(column::DATASET_COLUMNS).as_str())
+    }
+
+    fn zfs_volumes(&self) -> ListResult {
+        list_command("zfs", &["list", "-t", "volume"], column::properties(column::VOLUME_COLUMNS).as_str())
+    }
+
+    fn zfs_snapshots(&self) -> ListResult {
+        list_command("zfs", &["list", "-t", "snapshot"], column::properties(column::SNAPSHOT_COLUMNS).as_str())
+    }
 
-            run_command("zpool", &arguments);
+    fn zfs_bookmarks(&self) -> ListResult {
+        list_command("zfs", &["list", "-t", "bookmark"], column::properties(column::BOOKMARK_COLUMNS).as_str())
+    }
+
+    fn zfs_list_properties(&self, types: &str, properties: &[String]) -> ListResult {
+        list_command("zfs", &["list", "-t", types], format!("name,{}", properties.join(",")).as_str())
+    }
+
+    fn zfs_get_all(&self, dataset_name: &str) -> PropertyResult {
+        get_command("zfs", dataset_name)
+    }
+
+    fn zpool_get_all(&self, pool_name: &str) -> PropertyResult {
+        get_command("zpool", pool_name)
+    }
+
+    fn zfs_diff(&self, snapshot_name: &str, target: Option<&str>) -> Result<Vec<Change>, CommandOutput> {
+        diff_command(snapshot_name, target)
+    }
+
+    fn zfs_holds(&self, snapshot_names: &[String]) -> Result<Vec<Hold>, CommandOutput> {
+        holds_command(snapshot_names)
+    }
+
+    fn list_directory(&self, path: &str) -> Result<Vec<Entry>, CommandOutput> {
+        directory_command(path)
+    }
+
+    fn zpool_status(&self, pool_name: &str) -> StatusResult {
+        status_command(pool_name)
+    }
+
+    fn zpool_status_all(&self) -> Result<Vec<status::PoolStatus>, CommandOutput> {
+        status_all_command()
+    }
+
+    fn zpool_devices(&self) -> Result<Vec<BlockDevice>, CommandOutput> {
+        devices_command()
+    }
+
+    fn zpool_importable(&self, directories: &[String], destroyed: bool) -> Result<Vec<status::PoolStatus>, CommandOutput> {
+        import_list_command(directories, destroyed)
+    }
+
+    fn zpool_iostat(&self, pool_names: &[String], monitor: &Monitor) -> CommandOutput {
+        iostat_command(pool_names, monitor)
+    }
+
+    fn zfs_create(&self, dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["create", dataset_name])
+    }
+
+    fn zfs_rename(&self, old_dataset_name: &str, new_dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["rename", old_dataset_name, new_dataset_name])
+    }
+
+    fn zfs_clone(&self, snapshot_name: &str, new_dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["clone", snapshot_name, new_dataset_name])
+    }
+
+    fn zfs_snapshot(&self, snapshot_name: &str) -> CommandOutput {
+        run_command("zfs", &["snapshot", snapshot_name])
+    }
+
+    fn zfs_snapshot_all(&self, snapshot_names: &[String]) -> CommandOutput {
+
+        let mut arguments = vec!["snapshot"];
+        arguments.extend(snapshot_names.iter().map(|name| name.as_str()));
+
+        run_command("zfs", &arguments)
+    }
+
+    fn zfs_bookmark(&self, snapshot_name: &str, bookmark_name: &str) -> CommandOutput {
+        run_command("zfs", &["bookmark", snapshot_name, bookmark_name])
+    }
+
+    fn zfs_destroy(&self, dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["destroy", dataset_name])
+    }
+
+    fn zfs_destroy_estimate(&self, snapshot_names: &[String]) -> Result<u64, CommandOutput> {
+
+        let arguments = retention::estimate_arguments(snapshot_names);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        let output = run_command("zfs", &arguments);
+        if !output.success {
+            return Err(output);
+        }
+
+        Ok(retention::parse_reclaim(output.stdout.as_str()).unwrap_or(0))
+    }
+
+    fn zfs_rollback(&self, snapshot_name: &str) -> CommandOutput {
+        run_command("zfs", &["rollback", "-rf", snapshot_name])
+    }
+
+    fn zfs_promote(&self, dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["promote", dataset_name])
+    }
+
+    fn zfs_hold(&self, tag: &str, snapshot_name: &str) -> CommandOutput {
+        run_command("zfs", &["hold", tag, snapshot_name])
+    }
+
+    fn zfs_release(&self, tag: &str, snapshot_name: &str) -> CommandOutput {
+        run_command("zfs", &["release", tag, snapshot_name])
+    }
+
+    fn zfs_set(&self, dataset_name: &str, property: &str, value: &str) -> CommandOutput {
+        run_command("zfs", &["set", format!("{}={}", property, value).as_str(), dataset_name])
+    }
+
+    fn zfs_inherit(&self, dataset_name: &str, property: &str) -> CommandOutput {
+        run_command("zfs", &["inherit", property, dataset_name])
+    }
+
+    fn zfs_send_estimate(&self, send: &SendOptions) -> Result<u64, CommandOutput> {
+        estimate_command(send)
+    }
+
+    fn zfs_send(&self, send: &SendOptions, progress: &Progress) -> CommandOutput {
+        send_command(send, progress)
+    }
+
+    fn zfs_receive_abort(&self, dataset_name: &str) -> CommandOutput {
+        run_command("zfs", &["receive", "-A", dataset_name])
+    }
+
+    fn restore_files(&self, sources: &[String], directory: &str) -> CommandOutput {
+
+        let arguments = files::restore_arguments(sources, directory);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        run_command("cp", &arguments)
+    }
+
+    fn zpool_create(&self, create: &CreateOptions, dry_run: bool) -> CommandOutput {
+
+        let arguments = create.arguments(dry_run);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        run_command("zpool", &arguments)
+    }
+
+    fn zpool_file_vdev(&self, path: &str, size: u64) -> CommandOutput {
+        run_command("truncate", &["-s", size.to_string().as_str(), path])
+    }
+
+    fn zpool_destroy(&self, pool_name: &str) -> CommandOutput {
+        run_command("zpool", &["destroy", pool_name])
+    }
+
+    fn zpool_import(&self, import: &ImportOptions) -> CommandOutput {
+
+        let arguments = import.arguments();
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        run_command("zpool", &arguments)
+    }
+
+    fn zpool_export(&self, pool_name: &str) -> CommandOutput {
+        run_command("zpool", &["export", pool_name])
+    }
+
+    fn zpool_scrub(&self, pool_name: &str, control: Control) -> CommandOutput {
+        control_command("scrub", pool_name, control)
+    }
+
+    fn zpool_trim(&self, pool_name: &str, control: Control) -> CommandOutput {
+        control_command("trim", pool_name, control)
+    }
+
+    fn zpool_initialize(&self, pool_name: &str, control: Control) -> CommandOutput {
+        control_command("initialize", pool_name, control)
+    }
+
+    fn zpool_set(&self, pool_name: &str, property: &str, value: &str) -> CommandOutput {
+        run_command("zpool", &["set", format!("{}={}", property, value).as_str(), pool_name])
+    }
+
+    fn zpool_offline(&self, pool_name: &str, device: &str) -> CommandOutput {
+        run_command("zpool", &["offline", pool_name, device])
+    }
+
+    fn zpool_online(&self, pool_name: &str, device: &str) -> CommandOutput {
+        run_command("zpool", &["online", pool_name, device])
+    }
+
+    fn zpool_attach(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput {
+        run_command("zpool", &["attach", pool_name, device, new_device])
+    }
+
+    fn zpool_detach(&self, pool_name: &str, device: &str) -> CommandOutput {
+        run_command("zpool", &["detach", pool_name, device])
+    }
+
+    fn zpool_replace(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput {
+        run_command("zpool", &["replace", pool_name, device, new_device])
+    }
+
+    fn zpool_add(&self, pool_name: &str, vdevs: &[String], dry_run: bool) -> CommandOutput {
+
+        let mut arguments = vec!["add"];
+        if dry_run { arguments.push("-n"); }
+        arguments.push(pool_name);
+        arguments.extend(vdevs.iter().map(|vdev| vdev.as_str()));
+
+        run_command("zpool", &arguments)
+    }
+
+    fn zpool_remove(&self, pool_name: &str, device: &str) -> CommandOutput {
+        run_command("zpool", &["remove", pool_name, device])
+    }
+
+    fn zpool_clear(&self, pool_name: &str, device: Option<&str>) -> CommandOutput {
+
+        match device {
+            Some(device) => run_command("zpool", &["clear", pool_name, device]),
+            None => run_command("zpool", &["clear", pool_name]),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::os::unix::fs::symlink;
+
+    #[test]
+    fn excludes_only_the_disks_in_use() {
+
+        let root = std::env::temp_dir().join(format!("zc-block-{}", std::process::id()));
+        let block_class = root.join("class");
+        fs::create_dir_all(&block_class).unwrap();
+        for (device, parent) in &[("sda", ""), ("sdab", ""), ("sda1", "sda/"), ("nvme0n1", ""), ("nvme0n1p2", "nvme0n1/")] {
+            fs::create_dir_all(root.join("devices").join(format!("{}{}", parent, device))).unwrap();
+            symlink(format!("../devices/{}{}", parent, device), block_class.join(device)).unwrap();
         }
-    });
-}
\ No newline at end of file
+        fs::write(root.join("devices/sda/sda1/partition"), "1\n").unwrap();
+        fs::write(root.join("devices/nvme0n1/nvme0n1p2/partition"), "2\n").unwrap();
+
+        let used = vec!["/dev/sda1".to_string(), "/dev/nvme0n1p2".to_string(), "/dev/sdc".to_string()];
+        let in_use = disks_in_use(&block_class, used.into_iter());
+        fs::remove_dir_all(&root).unwrap();
+
+        assert!(in_use.contains("/dev/sda"));
+        assert!(in_use.contains("/dev/nvme0n1"));
+        assert!(in_use.contains("/dev/sdc"));
+        assert!(!in_use.contains("/dev/sdab"));
+        assert!(!in_use.contains("/dev/sda1"));
+    }
+
+    #[test]
+    fn reads_stderr_while_stdout_is_copied() {
+
+        // More than a pipe holds, so the child would block on stderr if it
+        // were only read after stdout.
+        let mut child = process::Command::new("sh").args(["-c", "head -c 200000 /dev/zero | tr '\\0' x >&2; echo sent"])
+            .stdout(Stdio::piped()).stderr(Stdio::piped()).spawn().unwrap();
+        let errors = read_stderr(&mut child);
+
+        let mut stdout = String::new();
+        child.stdout.take().unwrap().read_to_string(&mut stdout).unwrap();
+        assert!(child.wait().unwrap().success());
+
+        assert_eq!(stdout, "sent\n");
+        assert_eq!(errors.join().unwrap().len(), 200000);
+    }
+}
@@ -0,0 +1,127 @@
+
+use std::collections::HashSet;
+
+use crate::command::CommandResult;
+
+// The origin of a clone, None for a dataset that was not cloned.
+pub fn origin<'a>(datasets: &'a [CommandResult], dataset_name: &str) -> Option<&'a str> {
+
+    datasets.iter()
+        .find(|dataset| dataset.name == dataset_name)
+        .map(|dataset| dataset.property("origin"))
+        .filter(|origin| !origin.is_empty() && *origin != "-")
+}
+
+// The datasets cloned from a snapshot.
+pub fn clones_of<'a>(datasets: &'a [CommandResult], snapshot_name: &str) -> Vec<&'a str> {
+
+    datasets.iter()
+        .filter(|dataset| dataset.property("origin") == snapshot_name)
+        .map(|dataset| dataset.name.as_str())
+        .collect()
+}
+
+// The snapshots a dataset descends from: its origin, the origin of that
+// snapshot's filesystem, and so on up to a dataset that is not a clone.
+pub fn origin_chain(datasets: &[CommandResult], dataset_name: &str) -> Vec<String> {
+
+    let mut chain = Vec::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    let mut current = dataset_name.to_string();
+
+    while let Some(origin) = origin(datasets, current.as_str()) {
+        if !visited.insert(current.to_owned()) { break }
+
+        chain.push(origin.to_string());
+        current = origin.split('@').next().unwrap_or(origin).to_string();
+    }
+
+    chain
+}
+
+// The snapshots of the dataset that have clones, each followed by its clones
+// and what depends on those in turn, with their depth in the tree.
+pub fn dependents(datasets: &[CommandResult], dataset_name: &str) -> Vec<(usize, String)> {
+
+    let mut result = Vec::new();
+    let mut visited: HashSet<String> = HashSet::new();
+    add_dependents(datasets, dataset_name, 0, &mut visited, &mut result);
+    result
+}
+
+fn add_dependents(datasets: &[CommandResult], dataset_name: &str, depth: usize,
+                  visited: &mut HashSet<String>, result: &mut Vec<(usize, String)>) {
+
+    if !visited.insert(dataset_name.to_string()) { return }
+
+    let prefix = format!("{}@", dataset_name);
+    let mut origins: Vec<&str> = datasets.iter()
+        .map(|dataset| dataset.property("origin"))
+        .filter(|origin| origin.starts_with(prefix.as_str()))
+        .collect();
+    origins.sort();
+    origins.dedup();
+
+    for origin in origins {
+        result.push((depth, origin.to_string()));
+
+        for clone in clones_of(datasets, origin) {
+            result.push((depth + 1, clone.to_string()));
+            add_dependents(datasets, clone, depth + 2, visited, result);
+        }
+    }
+}
+
+// Snapshots list their clones comma separated, with "-" or nothing for none.
+pub fn has_clones(clones: &str) -> bool {
+    !clones.is_empty() && clones != "-"
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn dataset(name: &str, origin: &str) -> CommandResult {
+
+        let mut dataset = CommandResult::new(name.to_string());
+        dataset.properties.insert("origin".to_string(), origin.to_string());
+        dataset
+    }
+
+    #[test]
+    fn walks_origins_and_dependents() {
+
+        let datasets = vec![
+            dataset("tank/base", "-"),
+            dataset("tank/a", "tank/base@one"),
+            dataset("tank/b", "tank/base@two"),
+            dataset("tank/c", "tank/a@three"),
+            dataset("tank/d", "tank/base@one"),
+        ];
+
+        assert_eq!(origin(&datasets, "tank/base"), None);
+        assert_eq!(clones_of(&datasets, "tank/base@one"), ["tank/a", "tank/d"]);
+        assert_eq!(origin_chain(&datasets, "tank/c"), ["tank/a@three", "tank/base@one"]);
+
+        let dependents = dependents(&datasets, "tank/base");
+        let tree: Vec<(usize, &str)> = dependents.iter().map(|(depth, name)| (*depth, name.as_str())).collect();
+        assert_eq!(tree, [
+            (0, "tank/base@one"), (1, "tank/a"), (2, "tank/a@three"), (3, "tank/c"), (1, "tank/d"),
+            (0, "tank/base@two"), (1, "tank/b"),
+        ]);
+
+        assert!(has_clones("tank/a,tank/d"));
+        assert!(!has_clones("-"));
+        assert!(!has_clones(""));
+    }
+
+    #[test]
+    fn stops_at_origin_loops() {
+
+        let datasets = vec![dataset("tank/a", "tank/b@x"), dataset("tank/b", "tank/a@y")];
+
+        assert_eq!(origin_chain(&datasets, "tank/a"), ["tank/b@x", "tank/a@y"]);
+        assert_eq!(dependents(&datasets, "tank/a").len(), 4);
+    }
+}
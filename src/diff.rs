@@ -0,0 +1,174 @@
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Kind {
+    Added,
+    Removed,
+    Modified,
+    Renamed,
+}
+
+impl Kind {
+
+    pub const ALL: [Kind; 4] = [Kind::Added, Kind::Removed, Kind::Modified, Kind::Renamed];
+
+    fn parse(marker: &str) -> Option<Kind> {
+
+        match marker {
+            "+" => Some(Kind::Added),
+            "-" => Some(Kind::Removed),
+            "M" => Some(Kind::Modified),
+            "R" => Some(Kind::Renamed),
+            _ => None,
+        }
+    }
+
+    pub fn marker(self) -> char {
+
+        match self {
+            Kind::Added => '+',
+            Kind::Removed => '-',
+            Kind::Modified => 'M',
+            Kind::Renamed => 'R',
+        }
+    }
+
+    pub fn label(self) -> &'static str {
+
+        match self {
+            Kind::Added => "added",
+            Kind::Removed => "removed",
+            Kind::Modified => "modified",
+            Kind::Renamed => "renamed",
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Change {
+
+    pub kind: Kind,
+    pub file_type: char,
+    pub path: String,
+    pub new_path: Option<String>,
+    pub time: i64,
+}
+
+impl Change {
+
+    // A rename matches when either of its paths starts with the prefix.
+    pub fn matches(&self, kind: Option<Kind>, prefix: &str) -> bool {
+
+        kind.is_none_or(|kind| kind == self.kind) &&
+            (self.path.starts_with(prefix) || self.new_path.as_deref().is_some_and(|path| path.starts_with(prefix)))
+    }
+}
+
+// Arguments of `zfs diff` between a snapshot and a later snapshot of the same
+// filesystem, or the filesystem itself when there is no target.
+pub fn arguments(snapshot_name: &str, target: Option<&str>) -> Vec<String> {
+
+    let mut arguments: Vec<String> = ["diff", "-F", "-H", "-t", snapshot_name].iter().map(|argument| argument.to_string()).collect();
+    arguments.extend(target.map(|target| target.to_string()));
+    arguments
+}
+
+// Parses `zfs diff -FHt`: the inode change time with nanoseconds, the change,
+// the file type and the path, followed by the new path of a rename, all
+// separated by tabs.
+pub fn parse_diff(output: &str) -> Vec<Change> {
+
+    output.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 4 { return None }
+
+        Some(Change {
+            kind: Kind::parse(fields[1])?,
+            file_type: fields[2].chars().next()?,
+            path: unescape(fields[3]),
+            new_path: fields.get(4).map(|path| unescape(path)),
+            time: fields[0].split('.').next().and_then(|seconds| seconds.parse().ok()).unwrap_or(0),
+        })
+    }).collect()
+}
+
+// zfs diff writes spaces, backslashes and other unprintable bytes of a path
+// as a backslash and four octal digits.
+fn unescape(path: &str) -> String {
+
+    let bytes = path.as_bytes();
+    let mut result = Vec::with_capacity(bytes.len());
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let octal = bytes.get(i + 1..i + 5)
+            .filter(|digits| digits.iter().all(|digit| (b'0'..=b'7').contains(digit)))
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).ok()?, 8).ok());
+
+        match octal {
+            Some(byte) if bytes[i] == b'\\' => { result.push(byte); i += 5; },
+            _ => { result.push(bytes[i]); i += 1; },
+        }
+    }
+
+    String::from_utf8_lossy(&result).into_owned()
+}
+
+// The inverse of `unescape`, used by the fake backend.
+pub fn escape(path: &str) -> String {
+
+    path.bytes().map(|byte| match byte {
+        b' ' | b'\\' | 0..=0x1f | 0x7f..=0xff => format!("\\{:04o}", byte),
+        _ => (byte as char).to_string(),
+    }).collect()
+}
+
+// The -F type markers.
+pub fn type_name(file_type: char) -> &'static str {
+
+    match file_type {
+        'F' => "file",
+        '/' => "directory",
+        '@' => "symlink",
+        'B' => "block device",
+        'C' => "character device",
+        '|' => "FIFO",
+        '=' => "socket",
+        '>' => "door",
+        'P' => "event port",
+        _ => "unknown",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parses_changes_and_renames() {
+
+        let output = "1760774400.123456789\tM\t/\t/tank/home\n\
+                      1760774401.000000000\tR\tF\t/tank/home/a\\0040b\t/tank/home/c\n\
+                      garbage\n";
+        let changes = parse_diff(output);
+
+        assert_eq!(changes.len(), 2);
+        assert!(changes[0].kind == Kind::Modified);
+        assert_eq!(changes[0].file_type, '/');
+        assert_eq!(changes[0].time, 1760774400);
+        assert_eq!(changes[1].path, "/tank/home/a b");
+        assert_eq!(changes[1].new_path.as_deref(), Some("/tank/home/c"));
+        assert!(changes[1].matches(Some(Kind::Renamed), "/tank/home/c"));
+        assert!(!changes[1].matches(Some(Kind::Added), "/tank"));
+    }
+
+    #[test]
+    fn escape_round_trips() {
+
+        for path in ["/plain/path", "/with space", "/back\\slash", "/tab\tand\nnewline", "/caf\u{e9}/\u{65e5}\u{672c}"] {
+            assert_eq!(unescape(escape(path).as_str()), path);
+        }
+        assert_eq!(escape("/a b"), "/a\\0040b");
+        assert_eq!(unescape("/not\\08"), "/not\\08");
+    }
+}
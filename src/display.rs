@@ -1,14 +1,39 @@
 
 use std::char;
+use std::cmp::Ordering;
+use std::collections::{HashMap, HashSet};
+use std::sync::mpsc;
 use std::thread;
 use std::time;
 
 extern crate ncurses;
 use ncurses::*;
 
+use crate::backend::{Backend, SharedBackend};
+use crate::column::{self, Column, Format};
 use crate::command;
+use crate::dependency;
+use crate::diff::{self, Change};
+use crate::files;
+use crate::hold;
+use crate::iostat;
+use crate::job::{JobState, Jobs, Progress};
+use crate::pattern::{self, Filter};
+use crate::pool::{self, Control, CreateOptions, ImportOptions};
+use crate::property::{self, Property};
+use crate::replication::{SendOptions, Target};
+use crate::retention;
+use crate::schedule::{self, Period, Policy, Schedule};
+use crate::status::{PoolStatus, ScanProgress};
 
 const BOTTOM_BORDER_SIZE: usize = 3;
+const HEADER_SIZE: usize = 1;
+const MIN_NAME_WIDTH: usize = 20;
+const JOBS_PANEL_HEIGHT: i32 = 10;
+
+type Action = fn(&mut Screen, Vec<String>);
+
+type VdevAction = fn(&Screen, &str, &PoolStatus, usize) -> Option<usize>;
 
 
 pub struct Screen {
@@ -18,6 +43,13 @@ pub struct Screen {
 
     left_content: Content,
     right_content: Content,
+
+    backend: SharedBackend,
+    jobs: Jobs,
+    job_notifications: mpsc::Receiver<usize>,
+    show_jobs: bool,
+    link: Link,
+    schedule_path: String,
 }
 
 impl Screen {
@@ -25,7 +57,9 @@ impl Screen {
     const KEY_ESC:   i32 = 0x1b;
     const KEY_ENTER: i32 = 0xa;
 
-    pub fn new() -> Screen {
+    const KEY_TIMEOUT: i32 = 100;
+
+    pub fn new(backend: SharedBackend, schedule_path: String) -> Screen {
 
         initscr();
         cbreak();
@@ -34,6 +68,7 @@ impl Screen {
         curs_set(CURSOR_VISIBILITY::CURSOR_INVISIBLE);
         keypad(stdscr(), true);
         // nodelay(stdscr(), true);
+        wtimeout(stdscr(), Screen::KEY_TIMEOUT);
 
         let mut max_y: i32 = 0;
         let mut max_x: i32 = 0;
@@ -43,12 +78,21 @@ impl Screen {
         let left_content = Content::new(true, ContentType::Datasets);
         let right_content = Content::new(false, ContentType::Snapshots);
 
+        let (jobs, job_notifications) = Jobs::new(backend.clone());
+
         Screen {
             max_y: 0,
             max_x: 0,
 
             left_content,
             right_content,
+
+            backend,
+            jobs,
+            job_notifications,
+            show_jobs: false,
+            link: Link::Off,
+            schedule_path,
         }
     }
 
@@ -58,16 +102,49 @@ impl Screen {
 
         loop {
 
+            self.follow_link();
             self.draw();
 
-            let should_update = match self.handle_keys() {
+            let mut should_update = match self.handle_keys() {
                 Err(_) => { break; },
                 Ok(should_update) => should_update,
             };
 
+            if self.check_jobs() { should_update = true; }
+
             thread::sleep(time::Duration::from_millis(10));
-            if should_update == true { self.update_content(); }
+            if should_update { self.update_content(); }
+        }
+    }
+
+    fn check_jobs(&mut self) -> bool {
+
+        let mut received = false;
+        let mut failures = Vec::new();
+
+        while let Ok(id) = self.job_notifications.try_recv() {
+            received = true;
+
+            if let Some(job) = self.jobs.get(id) {
+                failures.extend(job.failures());
+            }
+        }
+
+        if !failures.is_empty() {
+            self.draw();
+
+            let mut text = String::new();
+            for failure in failures.iter() {
+                text.push_str(format!("{}\n", failure.command).as_str());
+                for line in failure.error_message().lines() {
+                    text.push_str(format!("  {}\n", line).as_str());
+                }
+            }
+
+            self.message_dialog(" Command Failed: ", text.trim_end());
         }
+
+        received
     }
 
     fn handle_keys(&mut self) -> Result<bool,()> {
@@ -76,6 +153,23 @@ impl Screen {
         const KEY_PUP: i32 = 0x153;
         const KEY_PDN: i32 = 0x152;
 
+        const KEY_SPACE:  i32 = 0x20;
+        const KEY_PLUS:   i32 = 0x2b;
+        const KEY_MINUS:  i32 = 0x2d;
+        const KEY_STAR:   i32 = 0x2a;
+        const KEY_COLUMNS: i32 = 0x63;
+        const KEY_SORT:    i32 = 0x73;
+        const KEY_FILTER:  i32 = 0x66;
+        const KEY_SEARCH:  i32 = 0x2f;
+        const KEY_TREE:    i32 = 0x74;
+        const KEY_LINK:    i32 = 0x6c;
+        const KEY_ENTER:   i32 = Screen::KEY_ENTER;
+        const KEY_REVERSE: i32 = 0x72;
+        const KEY_JOBS:   i32 = 0x6a;
+        const KEY_CLEAR:  i32 = 0x4a;
+        const KEY_IOSTAT: i32 = 0x69;
+        const KEY_SCHEDULE: i32 = 0x61;
+
         let key = wgetch(stdscr());
 
         match key {
@@ -109,6 +203,25 @@ impl Screen {
             KEY_IL    => {},
             KEY_TAB   => { self.switch_mode(); return Ok(true); },
 
+            KEY_IC    => { self.selected_content_mut().toggle_tag(); },
+            KEY_SPACE => { self.selected_content_mut().toggle_tag(); },
+            KEY_PLUS  => { self.input_tag_pattern(true); },
+            KEY_MINUS => { self.input_tag_pattern(false); },
+            KEY_STAR  => { self.selected_content_mut().invert_tags(); },
+
+            KEY_COLUMNS => { self.input_columns(); },
+            KEY_SORT    => { self.input_sort(); },
+            KEY_REVERSE => { self.selected_content_mut().reverse_sort(); },
+            KEY_FILTER  => { self.input_filter(); },
+            KEY_SEARCH  => { self.quick_search(); },
+            KEY_TREE    => { self.selected_content_mut().toggle_tree(); },
+            KEY_ENTER   => { self.key_enter(); return Ok(true); },
+            KEY_LINK    => { self.link = self.link.next(); },
+            KEY_JOBS  => { self.show_jobs = !self.show_jobs; },
+            KEY_CLEAR => { self.jobs.clear_finished(); },
+            KEY_IOSTAT => { self.iostat_view(); return Ok(true); },
+            KEY_SCHEDULE => { self.schedule_view(); return Ok(true); },
+
             _ => {},
         }
 
@@ -116,375 +229,2853 @@ impl Screen {
     }
 
     fn key_home(&mut self) {
-
-        if self.left_content.is_selected {
-            self.left_content.position = 0;
-        } else {
-            self.right_content.position = 0;
-        }
+        self.selected_content_mut().move_to(0);
     }
 
     fn key_end(&mut self) {
-
-        if self.left_content.is_selected {
-            self.left_content.position = self.left_content.command_result.len()-1;
-        } else {
-            self.right_content.position = self.right_content.command_result.len()-1;
-        }
+        self.selected_content_mut().move_to(usize::MAX);
     }
 
     fn key_up(&mut self) {
+        self.selected_content_mut().move_by(-1);
+    }
 
-        if self.left_content.is_selected && self.left_content.position > 0 {
-            self.left_content.position -= 1;
-        } else if self.right_content.is_selected && self.right_content.position > 0{
-            self.right_content.position -= 1;
+    fn key_down(&mut self) {
+        self.selected_content_mut().move_by(1);
+    }
+
+    fn key_pgup(&mut self) {
+        self.selected_content_mut().move_by(-10);
+    }
+
+    fn key_pgdown(&mut self) {
+        self.selected_content_mut().move_by(10);
+    }
+
+    fn input_tag_pattern(&mut self, tag: bool) {
+
+        let (title, prompt) = if tag {
+            (" Select Group: ", "Tag the elements matching the pattern")
+        } else {
+            (" Unselect Group: ", "Untag the elements matching the pattern")
+        };
+
+        if let Ok(pattern) = self.input_dialog(title, prompt, "*") {
+            self.selected_content_mut().tag_matching(pattern.as_str(), tag);
         }
     }
 
-    fn key_down(&mut self) {
+    fn input_columns(&mut self) {
 
-        if self.left_content.is_selected && 
-           self.left_content.position+2 < self.left_content.command_result.len() {
-            self.left_content.position += 1;
-    
-        } else if self.right_content.is_selected && 
-           self.right_content.position+2 < self.right_content.command_result.len() {
-            self.right_content.position += 1;                
+        let columns = self.selected_content().c_type.columns();
+
+        let items: Vec<String> = columns.iter().map(|column| column.header.to_string()).collect();
+        let mut checked: Vec<bool> = columns.iter()
+            .map(|column| self.selected_content().visible_columns.contains(&column.property))
+            .collect();
+
+        if self.check_dialog(" Columns: ", &items, &mut checked).is_ok() {
+            self.selected_content_mut().visible_columns = columns.iter()
+                .zip(checked)
+                .filter(|(_, checked)| *checked)
+                .map(|(column, _)| column.property)
+                .collect();
         }
     }
 
-    fn key_pgup(&mut self) {
+    fn input_sort(&mut self) {
 
-        if self.left_content.is_selected {
-            if self.left_content.position > 10 {
-                self.left_content.position -= 10;
+        let columns = self.selected_content().c_type.columns();
+
+        let mut items = vec!["Unsorted".to_string(), "Name".to_string()];
+        items.extend(columns.iter().map(|column| column.header.to_string()));
+
+        let mut properties = vec![None, Some("name")];
+        properties.extend(columns.iter().map(|column| Some(column.property)));
+
+        let current = properties.iter().position(|property| *property == self.selected_content().sort_by).unwrap_or(0);
+
+        if let Ok(choice) = self.menu_dialog(" Sort By: ", &items, current) {
+            let content = self.selected_content_mut();
+
+            if properties[choice] == content.sort_by && choice > 0 {
+                content.reverse_sort();
             } else {
-                self.left_content.position = 0;
+                content.sort_by = properties[choice];
+                content.sort_descending = false;
+                content.apply_view();
             }
+        }
+    }
 
-        } else if self.right_content.is_selected {
-            if self.right_content.position > 10 {
-                self.right_content.position -= 10;
-            } else {
-                self.right_content.position = 0;
+    fn input_filter(&mut self) {
+
+        let current = match &self.selected_content().filter {
+            Some((expression, _)) => expression.to_owned(),
+            None => String::new(),
+        };
+
+        let prompt = "Show names matching text, wildcard (*?[]) or /regex/";
+
+        if let Ok(expression) = self.input_dialog(" Filter: ", prompt, current.as_str()) {
+            if let Err(error) = self.selected_content_mut().set_filter(expression.as_str()) {
+                self.message_dialog(" Invalid Filter: ", error.as_str());
             }
         }
     }
 
-    fn key_pgdown(&mut self) {
+    fn quick_search(&mut self) {
 
-        if self.left_content.is_selected {
-            if self.left_content.position+11 < self.left_content.command_result.len() {
-                self.left_content.position += 10;
-            } else {
-                self.left_content.position = self.left_content.command_result.len()-2;
+        let mut search = String::new();
+        let mut found = true;
+
+        loop {
+            self.draw();
+
+            let status = if found { "" } else { " (not found)" };
+            let mut line = format!(" Search: {}_{}   UP/DOWN Previous/Next   ENTER Done", search, status);
+            for _ in line.len()..self.max_x as usize {
+                line.push(' ');
             }
 
-        } else if self.right_content.is_selected {
-            if self.right_content.position+11 < self.right_content.command_result.len() {
-                self.right_content.position += 10;
-            } else {
-                self.right_content.position = self.right_content.command_result.len()-2;
+            wattron(stdscr(), A_BOLD());
+            mvwaddstr(stdscr(), self.max_y-1, 0, line.as_str());
+            wattroff(stdscr(), A_BOLD());
+            wrefresh(stdscr());
+
+            let key = getch();
+            let content = self.selected_content_mut();
+
+            match key {
+                ERR               => {},
+                Screen::KEY_ENTER => { break },
+                Screen::KEY_ESC   => { break },
+                KEY_DOWN          => { found = content.search(search.as_str(), true, true); },
+                KEY_UP            => { found = content.search(search.as_str(), true, false); },
+                KEY_BACKSPACE     => { search.pop(); found = content.search(search.as_str(), false, true); },
+                0x20..=0x7e       => {
+                    search.push(char::from_u32(key as u32).unwrap());
+                    found = content.search(search.as_str(), false, true);
+                },
+                _                 => { break },
             }
         }
     }
 
-    fn key_f1(&self) { 
-        // TODO
+    fn key_enter(&mut self) {
+
+        match self.content_type() {
+            ContentType::Pools => {
+                if let Some(result) = self.selected_content().focused() {
+                    let pool_name = result.name.to_owned();
+                    self.pool_status_view(pool_name.as_str());
+                }
+            },
+            _ => { self.selected_content_mut().toggle_collapse(); },
+        }
     }
 
-    fn key_f2(&self) { 
+    fn pool_status_view(&mut self, pool_name: &str) {
 
-        let selected_elements = self.selected_elements();
+        const REFRESH_TICKS: u32 = 20;
+        const KEY_ADD: i32 = 0x61;
 
-        match self.content_type() {
-            ContentType::Pools =>     { },
-            ContentType::Datasets =>  { self.input_dataset_create(selected_elements); },
-            ContentType::Volumes =>   { },
-            ContentType::Snapshots => { },
+        let mut status = match self.backend.zpool_status(pool_name) {
+            Ok(status) => status,
+            Err(output) => {
+                self.message_dialog(" Pool Status: ", output.error_message().as_str());
+                return;
+            },
         };
-    }
 
-    fn input_dataset_create(&self, selected_elements: Vec<String>) {
+        let height = self.max_y - 1;
+        let width = self.max_x;
+        let text_width = width as usize - 4;
 
-        let selected_string = self.seleted_string(&selected_elements);
+        let title = format!(" Status: {} ", pool_name);
+        let window = Screen::draw_window(height, width, 0, 0, title.as_str());
 
-        match self.input_dialog(" Create Dataset: ", "Enter the name of the new Dataset", selected_string.as_str()) {
-            Ok(dataset_name) => { command::zfs_create(dataset_name); },
-            Err(_)    => { },
-        }
+        let footnote = "ENTER Actions     a Add     ESC Close";
+        let visible = (height - 4) as usize;
 
-        wrefresh(stdscr());
-    }
+        let mut position = 0;
+        let mut start_from = 0;
+        let mut ticks = 0;
+        let mut pending: Vec<usize> = Vec::new();
 
-    fn key_f3(&self) { 
-        // TODO
+        loop {
+            let lines = Screen::status_lines(&status, text_width);
+            let vdev_lines: Vec<usize> = lines.iter().enumerate()
+                .filter(|(_, (_, _, vdev))| vdev.is_some())
+                .map(|(i, _)| i)
+                .collect();
+
+            position = position.min(vdev_lines.len().saturating_sub(1));
+            let current = vdev_lines.get(position).copied().unwrap_or(0);
+
+            if current < start_from { start_from = current; }
+            if current >= start_from + visible { start_from = current + 1 - visible; }
+            start_from = start_from.min(lines.len().saturating_sub(visible));
+
+            for row in 0..visible {
+                let (text, attributes) = match lines.get(start_from + row) {
+                    Some((text, attributes, _)) => (text.as_str(), *attributes),
+                    None => ("", A_NORMAL()),
+                };
+
+                let attributes = if start_from + row == current && !vdev_lines.is_empty() { attributes | A_REVERSE() } else { attributes };
+
+                wattron(window, attributes);
+                mvwaddstr(window, 1 + row as i32, 2, format!("{:<width$}", text.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+                wattroff(window, attributes);
+            }
+
+            let progress = if pending.is_empty() { String::new() } else { format!(" applying {} change(s)... ", pending.len()) };
+            mvwaddstr(window, height-3, 2, format!("{:-<width$}", progress, width = text_width).as_str());
+            mvwaddstr(window, height-2, width/2 - footnote.len() as i32/2, footnote);
+            touchwin(window);
+            wrefresh(window);
+
+            let selected = lines.get(current).and_then(|(_, _, vdev)| *vdev);
+
+            match getch() {
+                ERR => {
+                    ticks += 1;
+
+                    let finished = pending.iter().any(|id| self.jobs.get(*id).is_none_or(|job| job.is_finished()));
+                    if finished {
+                        pending.retain(|id| self.jobs.get(*id).is_some_and(|job| !job.is_finished()));
+                        self.check_jobs();
+                    }
+
+                    if ticks >= REFRESH_TICKS || finished {
+                        ticks = 0;
+                        if let Ok(refreshed) = self.backend.zpool_status(pool_name) {
+                            status = refreshed;
+                        }
+                    }
+                },
+                Screen::KEY_ESC => { delwin(window); return },
+                Screen::KEY_ENTER => {
+                    if let Some(index) = selected {
+                        if let Some(id) = self.vdev_actions_dialog(pool_name, &status, index) {
+                            pending.push(id);
+                        }
+                    }
+                },
+                KEY_ADD => {
+                    if let Some(id) = self.input_vdev_add(pool_name, &status, 0) {
+                        pending.push(id);
+                    }
+                },
+                KEY_UP          => { position = position.saturating_sub(1); },
+                KEY_DOWN        => { position += 1; },
+                KEY_PPAGE       => { position = position.saturating_sub(visible); },
+                KEY_NPAGE       => { position = (position + visible).min(vdev_lines.len().saturating_sub(1)); },
+                KEY_HOME        => { position = 0; start_from = 0; },
+                KEY_END         => { position = usize::MAX; },
+                _               => {},
+            }
+        }
     }
 
-    fn key_f4(&self) { 
+    // Live `zpool iostat` of the tagged pools, or of all pools. A thread
+    // follows the command and fills the monitor; the screen only redraws
+    // what it has, so keys are handled while waiting for the next sample.
+    fn iostat_view(&mut self) {
 
-        // let selected_elements = self.selected_elements();
+        const KEY_LATENCY: i32 = 0x6c;
+        const KEY_QUEUES:  i32 = 0x71;
+        const COLUMN_WIDTH: usize = 7;
 
-        match self.content_type() {
-            ContentType::Pools =>     { },
-            ContentType::Datasets =>  { },
-            ContentType::Volumes =>   { },
-            ContentType::Snapshots => { },
+        let pool_names = match self.content_type() {
+            ContentType::Pools => self.selected_content().selected_elements.to_owned(),
+            _ => Vec::new(),
         };
-    }
 
-    fn key_f5(&self) { 
+        let monitor = iostat::Monitor::new();
+        let feed = monitor.clone();
+        let backend = self.backend.clone();
+        let names = pool_names.clone();
 
-        let selected_elements = self.selected_elements();
+        thread::spawn(move || {
+            let output = backend.zpool_iostat(&names, &feed);
+            if !output.success && !feed.is_stopped() {
+                feed.fail(output.error_message());
+            }
+        });
 
-        match self.content_type() {
-            ContentType::Pools =>     { },
-            ContentType::Datasets =>  { self.input_snapshot_dataset(selected_elements); },
-            ContentType::Volumes =>   { self.input_snapshot_dataset(selected_elements); },
-            ContentType::Snapshots => { self.input_snapshot_clone(selected_elements);   },
-        };
-    }
+        let height = self.max_y - 1;
+        let width = self.max_x;
+        let text_width = width as usize - 4;
 
-    fn input_snapshot_clone(&self, selected_elements: Vec<String>) {
+        let pools = if pool_names.is_empty() { "all pools".to_string() } else { pool_names.join(", ") };
+        let title = format!(" I/O Statistics: {} ", pools);
+        let window = Screen::draw_window(height, width, 0, 0, title.as_str());
 
-        let selected_string = self.seleted_string(&selected_elements);
+        let footnote = "l Latency     q Queues     ESC Close";
+        let visible = (height - 5) as usize;
 
-        match self.input_dialog(" Clone Snapshot: ", "Enter the name of the new Snapshot", "") {
-            Ok(dataset_name) => { command::zfs_clone(selected_string, dataset_name); },
-            Err(_)    => { },
-        }
+        let mut latency = true;
+        let mut queues = true;
+        let mut start_from = 0;
 
-        wrefresh(stdscr());
+        loop {
+            let rows = monitor.rows();
+
+            let mut headers = vec!["R-OPS", "W-OPS", "R-BW", "W-BW"];
+            if latency { headers.extend(&["R-WAIT", "W-WAIT", "DISK-R", "DISK-W"]); }
+            if queues { headers.extend(&["SYNCQ", "ASYNCQ"]); }
+
+            let name_width = rows.iter()
+                .map(|(stat, _)| stat.depth * 2 + stat.name.len())
+                .max()
+                .unwrap_or(4)
+                .clamp(12, 32);
+            let columns_width = headers.len() * (COLUMN_WIDTH + 1);
+            let history_width = text_width.saturating_sub(name_width + columns_width + 2);
+
+            let mut header = Screen::fit_name("NAME", name_width);
+            for column in headers.iter() {
+                header.push_str(format!(" {:>width$}", column, width = COLUMN_WIDTH).as_str());
+            }
+            if history_width >= 8 {
+                let label = if history_width >= 17 { "BANDWIDTH (5 min)" } else { "HISTORY" };
+                header.push_str(format!("  {:<width$}", label, width = history_width).as_str());
+            }
+
+            wattron(window, A_BOLD());
+            mvwaddstr(window, 1, 2, format!("{:<width$}", header.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+            wattroff(window, A_BOLD());
+
+            start_from = start_from.min(rows.len().saturating_sub(visible));
+
+            for row in 0..visible {
+                let (line, attributes) = match rows.get(start_from + row) {
+                    Some((stat, history)) => {
+                        let name = format!("{}{}", "  ".repeat(stat.depth), stat.name);
+                        let mut line = Screen::fit_name(name.as_str(), name_width);
+
+                        let mut values = vec![
+                            iostat::format_count(stat.read_ops),
+                            iostat::format_count(stat.write_ops),
+                            iostat::format_bandwidth(stat.read_bytes),
+                            iostat::format_bandwidth(stat.write_bytes),
+                        ];
+                        if latency {
+                            values.extend([stat.read_wait, stat.write_wait, stat.disk_read_wait, stat.disk_write_wait].iter()
+                                .map(|wait| iostat::format_latency(*wait)));
+                        }
+                        if queues {
+                            values.extend([stat.sync_queue, stat.async_queue].iter().map(|queue| iostat::format_count(*queue)));
+                        }
+
+                        for value in values {
+                            line.push_str(format!(" {:>width$}", value, width = COLUMN_WIDTH).as_str());
+                        }
+                        if history_width >= 8 {
+                            line.push_str(format!("  {}", iostat::sparkline(history, history_width)).as_str());
+                        }
+
+                        let attributes = if stat.depth == 0 && stat.key == stat.name { A_BOLD() } else { A_NORMAL() };
+                        (line, attributes)
+                    },
+                    None if row == 0 => {
+                        let text = monitor.error().unwrap_or_else(|| "Waiting for the first sample...".to_string());
+                        (text, A_NORMAL())
+                    },
+                    None => (String::new(), A_NORMAL()),
+                };
+
+                wattron(window, attributes);
+                mvwaddstr(window, 2 + row as i32, 2, format!("{:<width$}", line.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+                wattroff(window, attributes);
+            }
+
+            mvwaddstr(window, height-3, 2, "-".repeat(text_width).as_str());
+            mvwaddstr(window, height-2, width/2 - footnote.len() as i32/2, footnote);
+            touchwin(window);
+            wrefresh(window);
+
+            match getch() {
+                Screen::KEY_ESC => { monitor.stop(); delwin(window); return },
+                KEY_LATENCY     => { latency = !latency; },
+                KEY_QUEUES      => { queues = !queues; },
+                KEY_UP          => { start_from = start_from.saturating_sub(1); },
+                KEY_DOWN        => { start_from += 1; },
+                KEY_PPAGE       => { start_from = start_from.saturating_sub(visible); },
+                KEY_NPAGE       => { start_from += visible; },
+                KEY_HOME        => { start_from = 0; },
+                KEY_END         => { start_from = usize::MAX; },
+                _               => {},
+            }
+        }
     }
 
-    fn input_snapshot_dataset(&self, selected_elements: Vec<String>) {
+    // The output of `zfs diff`, filtered by the kind of change and the start
+    // of the path. ENTER shows the focused change in full.
+    fn diff_view(&mut self, title: &str, changes: &[Change]) {
 
-        let selected_string = self.seleted_string(&selected_elements);
+        const KEY_TYPE:   i32 = 0x74;
+        const KEY_PREFIX: i32 = 0x2f;
 
-        let snapshot = format!("{}@", selected_string);
+        let height = self.max_y - 1;
+        let width = self.max_x;
+        let text_width = width as usize - 4;
 
-        match self.input_dialog(" Snapshot Dataset: ", "Enter the name of the new Snapshot", snapshot.as_str()) {
-            Ok(dataset_name) => { command::zfs_snapshot(dataset_name); },
-            Err(_)    => { },
-        }
+        let window = Screen::draw_window(height, width, 0, 0, title);
 
-        wrefresh(stdscr());
-    }
+        let footnote = "ENTER Details     t Type     / Prefix     ESC Close";
+        let visible = (height - 5) as usize;
 
-    fn key_f6(&self) { 
+        let mut kind: Option<diff::Kind> = None;
+        let mut prefix = String::new();
+        let mut position = 0;
+        let mut start_from = 0;
 
-        let selected_elements = self.selected_elements();
+        loop {
+            let shown: Vec<&Change> = changes.iter().filter(|change| change.matches(kind, prefix.as_str())).collect();
+
+            position = position.min(shown.len().saturating_sub(1));
+            if position < start_from { start_from = position; }
+            if position >= start_from + visible { start_from = position + 1 - visible; }
+
+            let header = format!("  T  {:<16}  PATH", "CHANGED");
+            wattron(window, A_BOLD());
+            mvwaddstr(window, 1, 2, format!("{:<width$}", header, width = text_width).as_str());
+            wattroff(window, A_BOLD());
+
+            for row in 0..visible {
+                let text = match shown.get(start_from + row) {
+                    Some(change) => {
+                        let path = match &change.new_path {
+                            Some(new_path) => format!("{} -> {}", change.path, new_path),
+                            None => change.path.to_owned(),
+                        };
+                        format!("{} {}  {}  {}", change.kind.marker(), change.file_type, column::format_time(change.time), path)
+                    },
+                    None if row == 0 && start_from == 0 => "No changes.".to_string(),
+                    None => String::new(),
+                };
+
+                let attributes = if start_from + row == position && !shown.is_empty() { A_REVERSE() } else { A_NORMAL() };
+
+                wattron(window, attributes);
+                mvwaddstr(window, 2 + row as i32, 2, format!("{:<width$}", text.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+                wattroff(window, attributes);
+            }
 
-        match self.content_type() {
-            ContentType::Pools =>     { },
-            ContentType::Datasets =>  { self.input_dataset_rename(selected_elements); },
-            ContentType::Volumes =>   { self.input_dataset_rename(selected_elements); },
-            ContentType::Snapshots => { self.input_dataset_rename(selected_elements); },
-        };
+            let counts: Vec<String> = diff::Kind::ALL.iter()
+                .map(|each| format!("{}{}", each.marker(), changes.iter().filter(|change| change.kind == *each).count()))
+                .collect();
+            let summary = format!(" {} of {} ({}) type: {} prefix: {} ", shown.len(), changes.len(), counts.join(" "),
+                kind.map_or("all", |kind| kind.label()), if prefix.is_empty() { "-" } else { prefix.as_str() });
+
+            mvwaddstr(window, height-3, 2, format!("{:-<width$}", summary.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+            mvwaddstr(window, height-2, width/2 - footnote.len() as i32/2, footnote);
+            touchwin(window);
+            wrefresh(window);
+
+            match getch() {
+                Screen::KEY_ESC => { delwin(window); return },
+                Screen::KEY_ENTER => {
+                    if let Some(change) = shown.get(position) {
+                        let mut text = format!("{} {}, changed {}\n\n{}", diff::type_name(change.file_type), change.kind.label(),
+                            column::format_time(change.time), change.path);
+                        if let Some(new_path) = &change.new_path {
+                            text.push_str(format!("\n-> {}", new_path).as_str());
+                        }
+                        self.message_dialog(" Change: ", text.as_str());
+                    }
+                },
+                KEY_TYPE => {
+                    kind = match kind {
+                        None => Some(diff::Kind::ALL[0]),
+                        Some(current) => diff::Kind::ALL.iter().skip_while(|each| **each != current).nth(1).copied(),
+                    };
+                    position = 0;
+                    start_from = 0;
+                },
+                KEY_PREFIX => {
+                    if let Ok(input) = self.input_dialog(" Path Prefix: ", "Show paths starting with, empty for all", prefix.as_str()) {
+                        prefix = input.trim().to_string();
+                        position = 0;
+                        start_from = 0;
+                    }
+                },
+                KEY_UP          => { position = position.saturating_sub(1); },
+                KEY_DOWN        => { position += 1; },
+                KEY_PPAGE       => { position = position.saturating_sub(visible); },
+                KEY_NPAGE       => { position += visible; },
+                KEY_HOME        => { position = 0; },
+                KEY_END         => { position = usize::MAX; },
+                _               => {},
+            }
+        }
     }
 
-    fn input_dataset_rename(&self, selected_elements: Vec<String>) {
+    // The snapshot policies of the schedule file, which `zc --scheduler`
+    // follows. The line below the list shows which datasets the policy
+    // under the cursor covers once com.sun:auto-snapshot is taken into account.
+    fn schedule_view(&mut self) {
+
+        const KEY_ADD:    i32 = 0x61;
+        const KEY_DELETE: i32 = 0x64;
+        const KEY_PRUNE:  i32 = 0x70;
+
+        let mut schedule = match Schedule::load(self.schedule_path.as_str()) {
+            Ok(schedule) => schedule,
+            Err(error) => {
+                self.message_dialog(" Snapshot Policies: ", error.as_str());
+                return;
+            },
+        };
 
-        let selected_string = self.seleted_string(&selected_elements);
+        let datasets = self.backend.zfs_list_properties("filesystem,volume", &Schedule::properties()).unwrap_or_default();
 
-        match self.input_dialog(" Rename Dataset: ", "Enter the new name for the Dataset", selected_string.as_str()) {
-            Ok(new_dataset_name) => { command::zfs_rename(selected_string, new_dataset_name); },
-            Err(_)    => { },
-        }
+        let height = self.max_y - 1;
+        let width = self.max_x;
+        let text_width = width as usize - 4;
 
-        wrefresh(stdscr());
-    }
+        let title = format!(" Snapshot Policies: {} ", self.schedule_path);
+        let window = Screen::draw_window(height, width, 0, 0, title.as_str());
 
-    fn key_f7(&self) {
+        let footnote = "ENTER Edit     a Add     d Delete     p Prune     ESC Close";
+        let visible = (height - 6) as usize;
+        let dataset_width = text_width.saturating_sub(110).max(MIN_NAME_WIDTH);
 
-        let selected_elements = self.selected_elements();
+        let mut position = 0;
+        let mut start_from = 0;
 
-        match self.content_type() {
-            ContentType::Pools =>     { self.confirm_pool_scrub(selected_elements); },
-            ContentType::Datasets =>  { },
-            ContentType::Volumes =>   { },
-            ContentType::Snapshots => { self.confirm_snapshot_rollback(selected_elements); },
-        };
+        loop {
+            position = position.min(schedule.policies.len().saturating_sub(1));
+            if position < start_from { start_from = position; }
+            if position >= start_from + visible { start_from = position + 1 - visible; }
+
+            let header = format!("{} {:<36} {:<9} {:<6} {:<24} KEEP", Screen::fit_name("DATASET", dataset_width), "PERIODS", "CHILDREN", "ONLY", "TEMPLATE");
+            wattron(window, A_BOLD());
+            mvwaddstr(window, 1, 2, format!("{:<width$}", header.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+            wattroff(window, A_BOLD());
+
+            for row in 0..visible {
+                let text = match schedule.policies.get(start_from + row) {
+                    Some(policy) => format!("{} {:<36} {:<9} {:<6} {:<24} {}", Screen::fit_name(policy.dataset.as_str(), dataset_width),
+                        policy.periods_label(), if policy.recursive { "yes" } else { "no" },
+                        if policy.marked_only { "marked" } else { "-" }, policy.template, policy.keep_label()),
+                    None if row == 0 && start_from == 0 => "No policies: press a to add one.".to_string(),
+                    None => String::new(),
+                };
+
+                let attributes = if start_from + row == position && !schedule.policies.is_empty() { A_REVERSE() } else { A_NORMAL() };
+
+                wattron(window, attributes);
+                mvwaddstr(window, 2 + row as i32, 2, format!("{:<width$}", text.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+                wattroff(window, attributes);
+            }
+
+            let covered = match schedule.policies.get(position) {
+                Some(policy) => {
+                    let mut names: Vec<String> = Vec::new();
+                    for period in policy.periods.iter() {
+                        for name in policy.datasets(&datasets, *period) {
+                            if !names.contains(&name) { names.push(name); }
+                        }
+                    }
+                    format!(" covers {}: {} ", names.len(), names.join(" "))
+                },
+                None => String::new(),
+            };
+
+            mvwaddstr(window, height-3, 2, format!("{:-<width$}", covered.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+            mvwaddstr(window, height-2, width/2 - footnote.len() as i32/2, footnote);
+            touchwin(window);
+            wrefresh(window);
+
+            match getch() {
+                Screen::KEY_ESC => { delwin(window); return },
+                Screen::KEY_ENTER => {
+                    if let Some(policy) = schedule.policies.get(position).cloned() {
+                        if let Some(policy) = self.input_policy(policy) {
+                            self.save_policy(&mut schedule, policy, Some(position));
+                        }
+                    }
+                },
+                KEY_ADD => {
+                    let dataset_name = self.selected_content().focused().map(|result| result.name.split('@').next().unwrap_or("").to_string());
+                    let policy = Policy::new(dataset_name.unwrap_or_default().as_str());
+                    if let Some(policy) = self.input_policy(policy) {
+                        let dataset = policy.dataset.to_owned();
+                        self.save_policy(&mut schedule, policy, None);
+                        position = schedule.policies.iter().position(|policy| policy.dataset == dataset).unwrap_or(position);
+                    }
+                },
+                KEY_PRUNE => {
+                    if let Some(policy) = schedule.policies.get(position).cloned() {
+                        self.prune_policy(&policy, &datasets);
+                    }
+                },
+                KEY_DELETE | KEY_DC => {
+                    if let Some(policy) = schedule.policies.get(position) {
+                        let prompt = "Delete the snapshot policy of:";
+                        if self.confirm_dialog(" Confirm Delete: ", prompt, policy.dataset.as_str()).is_ok() {
+                            let removed = schedule.policies.remove(position);
+                            if let Err(error) = schedule.save(self.schedule_path.as_str()) {
+                                schedule.policies.insert(position, removed);
+                                self.message_dialog(" Snapshot Policies: ", error.as_str());
+                            }
+                        }
+                    }
+                },
+                KEY_UP          => { position = position.saturating_sub(1); },
+                KEY_DOWN        => { position += 1; },
+                KEY_PPAGE       => { position = position.saturating_sub(visible); },
+                KEY_NPAGE       => { position += visible; },
+                KEY_HOME        => { position = 0; },
+                KEY_END         => { position = usize::MAX; },
+                _               => {},
+            }
+        }
     }
 
-    fn confirm_pool_scrub(&self, selected_elements: Vec<String>) {
+    // Asks for every setting of a policy, starting from `policy`.
+    fn input_policy(&self, mut policy: Policy) -> Option<Policy> {
 
-        let selected_string = self.seleted_string(&selected_elements);
+        let title = " Snapshot Policy: ";
 
-        let title = " Confirm Scrub: ";
-        let prompt = "The following pools(s) will be scrubbed: ";
+        policy.dataset = self.input_dialog(title, "Dataset or volume", policy.dataset.as_str()).ok()?.trim().to_string();
 
-        if let Err(_) = self.confirm_dialog(title, prompt, selected_string.as_str()) {
-            return;
+        let items: Vec<String> = Period::ALL.iter().map(|period| period.description().to_string()).collect();
+        let mut checked: Vec<bool> = Period::ALL.iter().map(|period| policy.periods.contains(period)).collect();
+        self.check_dialog(title, &items, &mut checked).ok()?;
+        policy.periods = Period::ALL.iter().zip(checked).filter(|(_, checked)| *checked).map(|(period, _)| *period).collect();
+
+        let items = vec!["This dataset only".to_string(), "This dataset and its children".to_string()];
+        policy.recursive = self.menu_dialog(title, &items, policy.recursive as usize).ok()? == 1;
+
+        let items = vec![
+            format!("All, except where {}=false", schedule::PROPERTY),
+            format!("Only where {}=true", schedule::PROPERTY),
+        ];
+        policy.marked_only = self.menu_dialog(title, &items, policy.marked_only as usize).ok()? == 1;
+
+        let prompt = "Name template: %P period, %Y %m %d %H %M %S time";
+        policy.template = self.input_dialog(title, prompt, policy.template.as_str()).ok()?.trim().to_string();
+
+        let prompt = "Keep, e.g. hourly=24,daily=30,monthly=12 (empty keeps all)";
+        let keep = if policy.keep.is_empty() { String::new() } else { policy.keep_label() };
+        match Policy::parse_keep(self.input_dialog(title, prompt, keep.as_str()).ok()?.trim()) {
+            Ok(keep) => policy.keep = keep,
+            Err(error) => {
+                self.message_dialog(title, format!("The policy was not saved: {}.", error).as_str());
+                return None;
+            },
         }
 
-        command::zpool_scrub(selected_elements);
+        match policy.validate() {
+            Ok(_) => Some(policy),
+            Err(error) => {
+                self.message_dialog(title, format!("The policy was not saved: {}.", error).as_str());
+                None
+            },
+        }
     }
 
-    fn confirm_snapshot_rollback(&self, selected_elements: Vec<String>) {
-
-        let selected_string = self.seleted_string(&selected_elements);
+    // Shows which snapshots of the policy are past its retention rules and
+    // the space destroying them frees, then destroys them the way F8 does.
+    // Snapshots zfs would refuse to destroy are listed as kept.
+    fn prune_policy(&mut self, policy: &Policy, datasets: &[command::CommandResult]) {
 
-        let title = " Confirm Rollback: ";
-        let prompt = "The Dataset(s) will be rolled back to the following snapshot(s): ";
+        let title = " Prune Snapshots: ";
 
-        if let Err(_) = self.confirm_dialog(title, prompt, selected_string.as_str()) {
+        if policy.keep.is_empty() {
+            self.message_dialog(title, format!("The policy of {} keeps every snapshot: edit it to add retention rules.", policy.dataset).as_str());
             return;
         }
 
-        command::zfs_rollback(selected_elements);
-    }
+        let snapshots = match self.backend.zfs_list_properties("snapshot", &retention::properties()) {
+            Ok(snapshots) => snapshots,
+            Err(output) => { self.message_dialog(title, output.error_message().as_str()); return },
+        };
 
-    fn key_f8(&self) {
+        let plan = retention::plan(policy, &policy.covered(datasets), &snapshots);
 
-        let selected_elements = self.selected_elements();
-        let selected_string = self.seleted_string(&selected_elements);
+        let mut reclaim = 0;
+        for (_, names) in plan.by_dataset() {
+            match self.backend.zfs_destroy_estimate(&names) {
+                Ok(size) => reclaim += size,
+                Err(output) => { self.message_dialog(title, output.error_message().as_str()); return },
+            }
+        }
 
-        let title = " Confirm Destroy: ";
-        let prompt = "The following element(s) will be destroyed: ";
+        let mut text = match plan.destroy.len() {
+            0 => format!("Nothing to destroy (keep {}).\n", policy.keep_label()),
+            count => format!("Destroy {} snapshot(s), reclaiming {} (keep {}).\n", count, column::format_size(reclaim), policy.keep_label()),
+        };
 
-        if let Err(_) = self.confirm_dialog(title, prompt, selected_string.as_str()) {
-            return;
+        for name in plan.held.iter() {
+            text.push_str(format!("Not destroyed, held: {}\n", name).as_str());
+        }
+        for (name, clones) in plan.cloned.iter() {
+            text.push_str(format!("Not destroyed, cloned as {}: {}\n", clones, name).as_str());
+        }
+        if !plan.destroy.is_empty() {
+            text.push('\n');
+        }
+        for name in plan.destroy.iter() {
+            text.push_str(format!("  {}\n", name).as_str());
         }
 
-        match self.content_type() {
-            ContentType::Pools =>     { command::zpool_destroy(selected_elements) },
-            ContentType::Datasets =>  { command::zfs_destroy(selected_elements) },
-            ContentType::Volumes =>   { command::zfs_destroy(selected_elements) },
-            ContentType::Snapshots => { command::zfs_destroy(selected_elements) },
+        if plan.destroy.is_empty() {
+            self.message_dialog(title, text.as_str());
+        } else if self.confirm_text_dialog(title, text.as_str()).is_ok() {
+            self.destroy_elements(plan.destroy, false);
         }
     }
 
-    fn key_f9(&self) { 
-        // TODO
-    }
+    // Replaces the policy at `position`, or the one of the same dataset, and
+    // writes the file at once so a running scheduler picks it up.
+    fn save_policy(&self, schedule: &mut Schedule, policy: Policy, position: Option<usize>) {
 
-    fn key_f11(&self) { 
-        // TODO
-    }
+        let previous = schedule.policies.clone();
 
-    fn switch_window(&mut self) {
+        if let Some(position) = position {
+            schedule.policies.remove(position);
+        }
+        schedule.policies.retain(|existing| existing.dataset != policy.dataset);
+        schedule.policies.push(policy);
+        schedule.policies.sort_by(|a, b| a.dataset.cmp(&b.dataset));
 
-        if self.left_content.is_selected {
-            self.left_content.is_selected = false;
-            self.right_content.is_selected = true;
-        } else {
-            self.left_content.is_selected = true;
-            self.right_content.is_selected = false;
+        if let Err(error) = schedule.save(self.schedule_path.as_str()) {
+            schedule.policies = previous;
+            self.message_dialog(" Snapshot Policies: ", error.as_str());
         }
     }
 
-    fn switch_mode(&mut self) {
+    // The snapshot as found under `<mountpoint>/.zfs/snapshot` on the left and
+    // the live filesystem on the right. Opening a directory on the left takes
+    // the right side along while both show the same path. F5 copies what is
+    // tagged on the left into the directory shown on the right.
+    fn restore_view(&mut self, snapshot_name: &str, mountpoint: &str) {
 
-        if self.left_content.is_selected {
-            self.left_content = Content::new(true, self.left_content.next());
+        const KEY_TAB:   i32 = 0x9;
+        const KEY_SPACE: i32 = 0x20;
 
-        } else {
-            self.right_content = Content::new(true, self.right_content.next());
+        let mut snapshot = files::Browser::new(files::snapshot_root(mountpoint, snapshot_name).as_str());
+        let mut live = files::Browser::new(mountpoint);
+
+        if let Err(error) = snapshot.load(self.backend.as_ref()).and_then(|_| live.load(self.backend.as_ref())) {
+            self.message_dialog(" Browse Snapshot: ", error.as_str());
+            return;
         }
-    }
 
-    fn scroll_window(content: &mut Content, height: i32) {
+        let height = self.max_y - 1;
+        let half = self.max_x / 2;
+        let visible = (height - 5) as usize;
 
-        if content.position < content.start_from {
-            content.start_from = content.position
+        let windows = [
+            Screen::draw_window(height, half, 0, 0, ""),
+            Screen::draw_window(height, self.max_x - half, 0, half, ""),
+        ];
+        let footnotes = ["ENTER Open     SPACE Tag     F5 Restore", "TAB Switch     ESC Close"];
 
-        } else if content.position - content.start_from > (height as usize - BOTTOM_BORDER_SIZE - 1) {
-            content.start_from = content.position - (height as usize - BOTTOM_BORDER_SIZE - 1);
-        }
+        let mut left = true;
+        let mut pending: Vec<usize> = Vec::new();
 
-        if content.command_result.len() > 2 && 
-           content.position > content.command_result.len()-2 {
-            content.position = content.command_result.len()-2;
+        loop {
+            for (i, window) in windows.iter().enumerate() {
+                let title = if i == 0 { format!(" {}{} ", snapshot_name, snapshot.relative()) } else { format!(" {} ", live.path) };
+                let (browser, active) = if i == 0 { (&mut snapshot, left) } else { (&mut live, !left) };
+
+                let width = if i == 0 { half } else { self.max_x - half };
+                let text_width = width as usize - 4;
+
+                box_(*window, 0, 0);
+                mvwaddstr(*window, 0, 1, title.chars().take(text_width + 2).collect::<String>().as_str());
+
+                if browser.position < browser.start_from { browser.start_from = browser.position; }
+                if browser.position >= browser.start_from + visible { browser.start_from = browser.position + 1 - visible; }
+
+                for row in 0..visible {
+                    let text = match browser.entries.get(browser.start_from + row) {
+                        Some(entry) => Screen::file_line(entry, browser.tagged.contains(&entry.name), text_width),
+                        None => String::new(),
+                    };
+
+                    let attributes = if active && browser.start_from + row == browser.position { A_REVERSE() } else { A_NORMAL() };
+
+                    wattron(*window, attributes);
+                    mvwaddstr(*window, 1 + row as i32, 2, format!("{:<width$}", text, width = text_width).as_str());
+                    wattroff(*window, attributes);
+                }
+
+                let summary = if i == 0 {
+                    let selected = browser.selected();
+                    let size: u64 = selected.iter().map(|entry| entry.size).sum();
+                    if browser.tagged.is_empty() { String::new() } else { format!(" {} tagged, {} ", selected.len(), column::format_size(size)) }
+                } else if pending.is_empty() {
+                    String::new()
+                } else {
+                    " restoring... ".to_string()
+                };
+
+                mvwaddstr(*window, height-3, 2, format!("{:-<width$}", summary, width = text_width).as_str());
+                mvwaddstr(*window, height-2, 2, format!("{:^width$}", footnotes[i], width = text_width).as_str());
+                touchwin(*window);
+                wnoutrefresh(*window);
+            }
+            doupdate();
+
+            let browser = if left { &mut snapshot } else { &mut live };
+
+            match getch() {
+                ERR => {
+                    let finished = pending.iter().any(|id| self.jobs.get(*id).is_none_or(|job| job.is_finished()));
+                    if finished {
+                        pending.retain(|id| self.jobs.get(*id).is_some_and(|job| !job.is_finished()));
+                        self.check_jobs();
+                        live.load(self.backend.as_ref()).ok();
+                    }
+                },
+                Screen::KEY_ESC => {
+                    for window in windows.iter() { delwin(*window); }
+                    return
+                },
+                Screen::KEY_ENTER => {
+                    if let Some(target) = browser.target() {
+                        let follow = left && live.relative() == snapshot.relative();
+                        let opened = if left { snapshot.open(target.as_str(), self.backend.as_ref()) } else { live.open(target.as_str(), self.backend.as_ref()) };
+
+                        match opened {
+                            Ok(_) if follow => { live.open(target.as_str(), self.backend.as_ref()).ok(); },
+                            Ok(_) => {},
+                            Err(error) => self.message_dialog(" Browse Snapshot: ", error.as_str()),
+                        }
+                    }
+                },
+                KEY_F5 => {
+                    if let Some(id) = self.confirm_restore(snapshot_name, &snapshot, &live) {
+                        pending.push(id);
+                        snapshot.tagged.clear();
+                    }
+                },
+                KEY_TAB | KEY_LEFT | KEY_RIGHT => { left = !left; },
+                KEY_SPACE | KEY_IC if left => { browser.toggle_tag(); },
+                KEY_UP          => { browser.position = browser.position.saturating_sub(1); },
+                KEY_DOWN        => { browser.position = (browser.position + 1).min(browser.entries.len().saturating_sub(1)); },
+                KEY_PPAGE       => { browser.position = browser.position.saturating_sub(visible); },
+                KEY_NPAGE       => { browser.position = (browser.position + visible).min(browser.entries.len().saturating_sub(1)); },
+                KEY_HOME        => { browser.position = 0; },
+                KEY_END         => { browser.position = browser.entries.len().saturating_sub(1); },
+                _               => {},
+            }
         }
     }
 
-    fn test_windows(&self) {
+    // Name, size, mode and modification time of a directory entry, leaving
+    // out the mode and then the time when the pane is narrow.
+    fn file_line(entry: &files::Entry, tagged: bool, width: usize) -> String {
 
-        let s = format!("  Left position: {} len: {}  ", self.left_content.position, self.left_content.command_result.len());
-        mvwprintw(stdscr(), 1, 1, s.as_str());
+        let name = match entry.file_type {
+            '/' if entry.name != ".." => format!("{}/", entry.name),
+            '@' => format!("{}@", entry.name),
+            _ => entry.name.to_owned(),
+        };
 
-        let s = format!(" Right position: {} len: {}  ", self.right_content.position, self.right_content.command_result.len());
-        mvwprintw(stdscr(), 2, 1, s.as_str());
+        let size = if entry.is_directory() { "-".to_string() } else { column::format_size(entry.size) };
+        let mut details = format!(" {:>6}", size);
 
-        let key = getch();
-        let s = format!(" Keystroke: 0x{:x}     ", key);
-        mvwprintw(stdscr(), 3, 1, s.as_str());
+        if entry.name != ".." {
+            if width >= 48 { details.push_str(format!(" {}", files::format_mode(entry.file_type, entry.mode)).as_str()); }
+            if width >= 36 { details.push_str(format!(" {}", column::format_time(entry.modified)).as_str()); }
+        }
 
-        getch();
+        let name_width = width.saturating_sub(details.len() + 1);
+        format!("{}{}{}", if tagged { '*' } else { ' ' }, Screen::fit_name(name.as_str(), name_width), details)
     }
 
-    fn draw(&mut self) {
-
-        getmaxyx(stdscr(), &mut self.max_y, &mut self.max_x);
+    // Asks before copying the selection of the snapshot side into the live
+    // directory, listing what is already there and would be replaced.
+    fn confirm_restore(&mut self, snapshot_name: &str, snapshot: &files::Browser, live: &files::Browser) -> Option<usize> {
 
-        self.draw_menu();
-        self.draw_content();
-    }
+        let selected = snapshot.selected();
+        if selected.is_empty() { return None }
 
-    fn draw_content(&mut self) {
+        let names: Vec<String> = selected.iter().map(|entry| entry.name.to_owned()).collect();
+        let existing: Vec<&String> = names.iter().filter(|name| live.entries.iter().any(|entry| entry.name == **name)).collect();
 
-        let left_start_y = 0;
-        let left_start_x = 0;
-        let left_height = self.max_y;
-        let left_width  = self.max_x/2;
-        let left_title = self.left_content.c_type.text();
+        let mut text = format!("Copy {} file(s) or directories from {}:\n", names.len(), snapshot_name);
+        for name in names.iter() {
+            text.push_str(format!("  {}\n", name).as_str());
+        }
+        text.push_str(format!("into {}\n\nMode, ownership and timestamps are kept.", live.path).as_str());
 
-        let left_window = Screen::draw_window(left_height-1, left_width, left_start_y, left_start_x, left_title.as_str());
-        // self.left_content.update();
-        Screen::scroll_window(&mut self.left_content, left_height);
-        Screen::write_content(&self.left_content, left_window, left_height, left_width);
+        if !existing.is_empty() {
+            text.push_str(format!("\n\n{} already there and overwritten (directories are merged):\n", existing.len()).as_str());
+            for name in existing.iter() {
+                text.push_str(format!("  {}\n", name).as_str());
+            }
+        }
 
-        let right_start_x = left_width;
-        let right_start_y = 0;
-        let right_height = self.max_y;
-        let right_width  = self.max_x - right_start_x;
-        let right_title = self.right_content.c_type.text();
+        let title = if existing.is_empty() { " Confirm Restore: " } else { " Confirm Overwrite: " };
+        self.confirm_text_dialog(title, text.trim_end()).ok()?;
 
-        let right_window = Screen::draw_window(right_height-1, right_width, right_start_y, right_start_x, right_title.as_str());
-        // self.right_content.update();
-        Screen::scroll_window(&mut self.right_content, right_height);
-        Screen::write_content(&self.right_content, right_window, right_height, right_width);
+        let sources: Vec<String> = names.iter().map(|name| files::join(snapshot.path.as_str(), name)).collect();
+        let directory = live.path.to_owned();
+        let description = format!("restore {} from {}", self.seleted_string(&names), snapshot_name);
 
-        wrefresh(stdscr());
-        wrefresh(left_window);
-        wrefresh(right_window);
+        Some(self.jobs.submit(command::pool_name(snapshot_name).to_string(), description, move |zfs| {
+            vec![zfs.restore_files(&sources, &directory)]
+        }))
     }
 
-    fn draw_window(height: i32, width: i32, start_y: i32, start_x: i32, title: &str) -> WINDOW {
+    fn vdev_actions_dialog(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
 
-        let win = newwin(height, width, start_y, start_x);
+        let actions = Screen::vdev_actions(status, index);
+        if actions.is_empty() { return None; }
 
-        box_(win, 0, 0);
-        wmove(win, 0, 1);
-        wprintw(win, title);
+        let items: Vec<String> = actions.iter().map(|(label, _)| label.to_string()).collect();
+        let title = format!(" {} ", Screen::fit_name(device_label(status.config[index].name.as_str()), 30).trim_end());
 
-        win
+        let position = self.menu_dialog(title.as_str(), &items, 0).ok()?;
+        (actions[position].1)(self, pool_name, status, index)
     }
 
-    fn draw_menu(&mut self) {
+    // The operations zpool allows on a row of the vdev tree: devices can be
+    // taken offline, mirrored or replaced, top-level vdevs and cache, log or
+    // spare devices removed.
+    fn vdev_actions(status: &PoolStatus, index: usize) -> Vec<(&'static str, VdevAction)> {
 
-        let pools_menu     = format!(" 1 _____ 2 _____ 3 _____ 4 _____ 5 _____ 6 _____ 7 Scrub 8 Destr 9 _____ 10 Exit ");
-        let datasets_menu  = format!(" 1 _____ 2 Creat 3 _____ 4 _____ 5 Snaps 6 Renam 7 _____ 8 Destr 9 _____ 10 Exit ");
-        let volumes_menu   = format!(" 1 _____ 2 _____ 3 _____ 4 _____ 5 Snaps 6 Renam 7 _____ 8 Destr 9 _____ 10 Exit ");
-        let snapshots_menu = format!(" 1 _____ 2 _____ 3 _____ 4 _____ 5 Clone 6 Renam 7 RollB 8 Destr 9 _____ 10 Exit ");
+        let vdev = &status.config[index];
+        let section = status.section(index);
+        let parent = status.parent(index);
 
-        let mut selected_menu: String;
+        let mut actions: Vec<(&'static str, VdevAction)> = Vec::new();
 
-        match self.content_type() {
-            ContentType::Pools =>     { selected_menu = pools_menu; },
-            ContentType::Datasets =>  { selected_menu = datasets_menu; },
-            ContentType::Volumes =>   { selected_menu = volumes_menu; },
-            ContentType::Snapshots => { selected_menu = snapshots_menu; },
-        };
+        if vdev.depth == 0 {
+            actions.push(("Add vdevs", Screen::input_vdev_add));
+            if !vdev.is_section() {
+                actions.push(("Clear errors", Screen::confirm_vdev_clear));
+            }
+            return actions;
+        }
+
+        if section == "spares" {
+            actions.push(("Remove", Screen::confirm_vdev_remove));
+            return actions;
+        }
+
+        if !vdev.is_device() {
+            actions.push(("Remove", Screen::confirm_vdev_remove));
+            actions.push(("Clear errors", Screen::confirm_vdev_clear));
+            return actions;
+        }
+
+        let top_level = parent.is_some_and(|parent| parent.depth == 0);
+        let mirrored = parent.is_some_and(|parent| parent.name.starts_with("mirror") || parent.name.starts_with("replacing") || parent.name.starts_with("spare"));
+
+        actions.push(("Online", Screen::confirm_vdev_online));
+        actions.push(("Offline", Screen::confirm_vdev_offline));
+
+        if section != "cache" {
+            if top_level || parent.is_some_and(|parent| parent.name.starts_with("mirror")) {
+                actions.push(("Attach mirror device", Screen::input_vdev_attach));
+            }
+            if mirrored {
+                actions.push(("Detach", Screen::confirm_vdev_detach));
+            }
+            actions.push(("Replace", Screen::input_vdev_replace));
+        }
+
+        if top_level {
+            actions.push(("Remove", Screen::confirm_vdev_remove));
+        }
+
+        actions.push(("Clear errors", Screen::confirm_vdev_clear));
+        actions
+    }
+
+    fn confirm_vdev_offline(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+        self.confirm_dialog(" Confirm Offline: ", "The following device will be taken offline: ", device.as_str()).ok()?;
+
+        let description = format!("offline {} in {}", device, pool_name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_offline(pool_name, &device)))
+    }
+
+    fn confirm_vdev_online(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+        self.confirm_dialog(" Confirm Online: ", "The following device will be brought online: ", device.as_str()).ok()?;
+
+        let description = format!("online {} in {}", device, pool_name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_online(pool_name, &device)))
+    }
+
+    fn input_vdev_attach(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+
+        let new_device = self.input_dialog(" Attach Device: ", "Enter the device to attach as a mirror", "/dev/disk/by-id/").ok()?;
+        if new_device.trim().is_empty() { return None; }
+
+        let prompt = format!("{} will mirror: ", device_label(new_device.trim()));
+        self.confirm_dialog(" Confirm Attach: ", prompt.as_str(), device.as_str()).ok()?;
+
+        let new_device = new_device.trim().to_string();
+        let description = format!("attach {} to {}", new_device, device);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_attach(pool_name, &device, &new_device)))
+    }
+
+    fn confirm_vdev_detach(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+        self.confirm_dialog(" Confirm Detach: ", "The following device will be detached from its mirror: ", device.as_str()).ok()?;
+
+        let description = format!("detach {} from {}", device, pool_name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_detach(pool_name, &device)))
+    }
+
+    fn input_vdev_replace(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+
+        let suggestion = match status.spares().first() {
+            Some(spare) => spare.name.to_owned(),
+            None => "/dev/disk/by-id/".to_string(),
+        };
+
+        let new_device = self.input_dialog(" Replace Device: ", "Enter the new device (a spare of the pool or a new disk)", suggestion.as_str()).ok()?;
+        if new_device.trim().is_empty() { return None; }
+
+        let prompt = format!("{} will replace: ", device_label(new_device.trim()));
+        self.confirm_dialog(" Confirm Replace: ", prompt.as_str(), device.as_str()).ok()?;
+
+        let new_device = new_device.trim().to_string();
+        let description = format!("replace {} with {}", device, new_device);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_replace(pool_name, &device, &new_device)))
+    }
+
+    // The vdevs are checked with `zpool add -n` first, whose output shows the
+    // resulting layout before anything is changed.
+    fn input_vdev_add(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let prefix = match status.section(index) {
+            "logs"   => "log ",
+            "cache"  => "cache ",
+            "spares" => "spare ",
+            "special" => "special ",
+            "dedup"  => "dedup ",
+            _        => "",
+        };
+
+        let spec = self.input_dialog(" Add Vdevs: ", "Enter the vdevs, e.g. 'mirror sdc sdd' or 'cache sde'", prefix).ok()?;
+        let vdevs: Vec<String> = spec.split_whitespace().map(|word| word.to_string()).collect();
+        if vdevs.is_empty() { return None; }
+
+        let preview = self.backend.zpool_add(pool_name, &vdevs, true);
+        if !preview.success {
+            self.message_dialog(" Add Vdevs: ", preview.error_message().as_str());
+            return None;
+        }
+
+        self.confirm_text_dialog(" Confirm Add: ", preview.stdout.replace('\t', "  ").trim_end()).ok()?;
+
+        let description = format!("add {} to {}", vdevs.join(" "), pool_name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_add(pool_name, &vdevs, false)))
+    }
+
+    fn confirm_vdev_remove(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let device = status.config[index].name.to_owned();
+        self.confirm_dialog(" Confirm Remove: ", "The following vdev will be removed from the pool: ", device.as_str()).ok()?;
+
+        let description = format!("remove {} from {}", device, pool_name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_remove(pool_name, &device)))
+    }
+
+    fn confirm_vdev_clear(&self, pool_name: &str, status: &PoolStatus, index: usize) -> Option<usize> {
+
+        let vdev = &status.config[index];
+        let device = if vdev.depth == 0 { None } else { Some(vdev.name.to_owned()) };
+
+        let prompt = "The error counters will be cleared on: ";
+        self.confirm_dialog(" Confirm Clear: ", prompt, vdev.name.as_str()).ok()?;
+
+        let description = format!("clear errors on {}", vdev.name);
+        Some(self.submit_pool_job(pool_name, description, move |zfs, pool_name| zfs.zpool_clear(pool_name, device.as_deref())))
+    }
+
+    fn submit_pool_job<F>(&self, pool_name: &str, description: String, task: F) -> usize
+    where F: FnOnce(&dyn Backend, &str) -> command::CommandOutput + Send + 'static {
+
+        let pool_name = pool_name.to_string();
+        self.jobs.submit(pool_name.to_owned(), description, move |zfs| vec![task(zfs, &pool_name)])
+    }
+
+    // The lines of the pool status screen, with their attributes and the
+    // index of the vdev shown on the line.
+    fn status_lines(status: &PoolStatus, width: usize) -> Vec<(String, attr_t, Option<usize>)> {
+
+        let mut lines: Vec<(String, attr_t, Option<usize>)> = Vec::new();
+
+        let state_attributes = if status.state == "ONLINE" { A_NORMAL() } else { A_BOLD() };
+        lines.push((format!(" pool: {}", status.name), A_NORMAL(), None));
+        lines.push((format!("state: {}", status.state), state_attributes, None));
+
+        for (label, text) in [("status", &status.status), ("action", &status.action)].iter() {
+            if let Some(text) = text {
+                for (i, line) in Screen::wrap_text(text.as_str(), width - 8).into_iter().enumerate() {
+                    let label = if i == 0 { format!("{}:", label) } else { String::new() };
+                    lines.push((format!("{:>7} {}", label, line), A_NORMAL(), None));
+                }
+            }
+        }
+
+        if let Some(scan) = &status.scan {
+            for (i, line) in scan.lines().enumerate() {
+                let label = if i == 0 { "scan:" } else { "" };
+                lines.push((format!("{:>7} {}", label, line), A_NORMAL(), None));
+            }
+        }
+
+        for progress in status.activities() {
+            let bar_width = 30;
+            let filled = (((progress.percent / 100.0) * bar_width as f64) as usize).min(bar_width);
+            let line = format!("        [{}{}] {}", "#".repeat(filled), "-".repeat(bar_width - filled), progress.summary());
+
+            lines.push((line, A_BOLD(), None));
+        }
+
+        lines.push((String::new(), A_NORMAL(), None));
+
+        let name_width = status.config.iter()
+            .map(|vdev| vdev.depth * 2 + vdev.name.len())
+            .max()
+            .unwrap_or(4)
+            .clamp(4, width.saturating_sub(40).max(20));
+
+        lines.push((format!("{:<name_width$}  {:<8} {:>5} {:>5} {:>5}", "NAME", "STATE", "READ", "WRITE", "CKSUM", name_width = name_width), A_BOLD(), None));
+
+        for (i, vdev) in status.config.iter().enumerate() {
+
+            if vdev.is_section() {
+                lines.push((vdev.name.to_owned(), A_BOLD(), Some(i)));
+                continue;
+            }
+
+            let name = Screen::fit_name(format!("{}{}", "  ".repeat(vdev.depth), vdev.name).as_str(), name_width);
+            let line = format!("{}  {:<8} {:>5} {:>5} {:>5}  {}", name, vdev.state, vdev.read, vdev.write, vdev.cksum, vdev.note);
+
+            let attributes = if vdev.is_healthy() && !vdev.has_errors() { A_NORMAL() } else { A_BOLD() };
+            lines.push((line.trim_end().to_string(), attributes, Some(i)));
+        }
+
+        lines.push((String::new(), A_NORMAL(), None));
+
+        let errors_attributes = if status.error_files.is_empty() { A_NORMAL() } else { A_BOLD() };
+        lines.push((format!("errors: {}", status.errors), errors_attributes, None));
+
+        for file in status.error_files.iter() {
+            lines.push((format!("        {}", file), A_BOLD(), None));
+        }
+
+        lines
+    }
+
+    fn wrap_text(text: &str, width: usize) -> Vec<String> {
+
+        let mut lines = Vec::new();
+        let mut line = String::new();
+
+        for word in text.split_whitespace() {
+            if !line.is_empty() && line.len() + 1 + word.len() > width {
+                lines.push(line);
+                line = String::new();
+            }
+            if !line.is_empty() { line.push(' '); }
+            line.push_str(word);
+        }
+
+        if !line.is_empty() { lines.push(line); }
+        lines
+    }
+
+    fn key_f1(&self) { 
+        // TODO
+    }
+
+    fn key_f2(&self) { 
+
+        match self.content_type() {
+            ContentType::Pools =>     { self.input_pool_create(); },
+            ContentType::Datasets =>  { self.input_dataset_create(); },
+            ContentType::Volumes =>   { },
+            ContentType::Snapshots => { },
+            ContentType::Bookmarks => { },
+        };
+    }
+
+    fn input_dataset_create(&self) {
+
+        let parent = match self.selected_content().focused() {
+            Some(result) => format!("{}/", result.name),
+            None => String::new(),
+        };
+
+        if let Ok(dataset_name) = self.input_dialog(" Create Dataset: ", "Enter the name of the new Dataset", parent.as_str()) {
+            let description = format!("create {}", dataset_name);
+            self.jobs.submit(command::pool_name(&dataset_name).to_string(), description, move |zfs| {
+                vec![zfs.zfs_create(&dataset_name)]
+            });
+        }
+
+        wrefresh(stdscr());
+    }
+
+    fn input_pool_create(&self) {
+
+        let pool_name = match self.input_dialog(" Create Pool: ", "Enter the name of the new pool", "") {
+            Ok(pool_name) if !pool_name.trim().is_empty() => pool_name.trim().to_string(),
+            _ => return,
+        };
+
+        let mut create = CreateOptions::new(pool_name.as_str());
+
+        create.devices = match self.input_pool_devices() {
+            Ok(devices) => devices,
+            Err(_) => return,
+        };
+
+        match self.input_pool_topology(create.devices.len()) {
+            Ok((topology, groups)) => { create.topology = topology; create.groups = groups; },
+            Err(_) => return,
+        }
+
+        create.ashift = match self.input_pool_ashift() {
+            Ok(ashift) => ashift,
+            Err(_) => return,
+        };
+
+        if self.input_pool_properties(&mut create).is_err() {
+            return;
+        }
+
+        let preview = self.backend.zpool_create(&create, true);
+        if !preview.success {
+            self.message_dialog(" Create Pool: ", preview.error_message().as_str());
+            return;
+        }
+
+        let text = format!("zpool {}\n\n{}", create.arguments(false).join(" "), preview.stdout.replace('\t', "  ").trim_end());
+        if self.confirm_text_dialog(" Confirm Create: ", text.as_str()).is_err() {
+            return;
+        }
+
+        self.jobs.submit(pool_name, create.description(), move |zfs| {
+            vec![zfs.zpool_create(&create, false)]
+        });
+    }
+
+    // Unused disks from /dev/disk/by-id, or sparse files for trying out
+    // layouts on machines without spare disks.
+    fn input_pool_devices(&self) -> Result<Vec<String>,()> {
+
+        let items = vec!["Disks in /dev/disk/by-id".to_string(), "Files".to_string()];
+
+        if self.menu_dialog(" Devices: ", &items, 0)? == 1 {
+            return self.input_file_vdevs();
+        }
+
+        let devices = match self.backend.zpool_devices() {
+            Ok(devices) => devices,
+            Err(output) => {
+                self.message_dialog(" Devices: ", output.error_message().as_str());
+                return Err(());
+            },
+        };
+
+        if devices.is_empty() {
+            self.message_dialog(" Devices: ", "No unused disks were found in /dev/disk/by-id.");
+            return Err(());
+        }
+
+        let items: Vec<String> = devices.iter()
+            .map(|device| format!("{:>7}  {}", column::format_size(device.size), device_label(device.path.as_str())))
+            .collect();
+        let mut checked = vec![false; items.len()];
+
+        self.check_dialog(" Devices: ", &items, &mut checked)?;
+
+        let selected: Vec<String> = devices.into_iter().zip(checked)
+            .filter(|(_, checked)| *checked)
+            .map(|(device, _)| device.path)
+            .collect();
+
+        if selected.is_empty() { return Err(()); }
+        Ok(selected)
+    }
+
+    fn input_file_vdevs(&self) -> Result<Vec<String>,()> {
+
+        let paths = self.input_dialog(" File Vdevs: ", "Enter the file paths, separated by spaces", "/var/tmp/zc-0.img /var/tmp/zc-1.img")?;
+        let paths: Vec<String> = paths.split_whitespace().map(|path| path.to_string()).collect();
+        if paths.is_empty() { return Err(()); }
+
+        let size = self.input_dialog(" File Vdevs: ", "Enter the size of each file (at least 64M)", "1G")?;
+        let size = match property::parse_size(size.trim()) {
+            Some(size) => size,
+            None => {
+                self.message_dialog(" File Vdevs: ", format!("bad size '{}'", size.trim()).as_str());
+                return Err(());
+            },
+        };
+
+        let prompt = format!("Sparse files of {} will be created for: ", column::format_size(size));
+        self.confirm_dialog(" Confirm Files: ", prompt.as_str(), Screen::fit_name(paths.join(" ").as_str(), 64).as_str())?;
+
+        for path in paths.iter() {
+            let output = self.backend.zpool_file_vdev(path, size);
+            if !output.success {
+                self.message_dialog(" File Vdevs: ", output.error_message().as_str());
+                return Err(());
+            }
+        }
+
+        Ok(paths)
+    }
+
+    fn input_pool_topology(&self, count: usize) -> Result<(String, usize),()> {
+
+        let topologies: Vec<&str> = pool::TOPOLOGIES.iter()
+            .copied()
+            .filter(|topology| !pool::group_counts(topology, count).is_empty())
+            .collect();
+
+        let items: Vec<String> = topologies.iter().map(|topology| topology.to_string()).collect();
+        let topology = topologies[self.menu_dialog(" Topology: ", &items, 0)?];
+
+        let groups = pool::group_counts(topology, count);
+        if groups.len() == 1 {
+            return Ok((topology.to_string(), groups[0]));
+        }
+
+        let items: Vec<String> = groups.iter()
+            .map(|groups| format!("{} x {} of {} devices", groups, topology, count / groups))
+            .collect();
+
+        let position = self.menu_dialog(" Vdevs: ", &items, 0)?;
+        Ok((topology.to_string(), groups[position]))
+    }
+
+    fn input_pool_ashift(&self) -> Result<Option<u32>,()> {
+
+        const ASHIFTS: &[Option<u32>] = &[None, Some(9), Some(12), Some(13)];
+
+        let items = vec![
+            "auto-detect".to_string(),
+            "ashift=9   512 byte sectors".to_string(),
+            "ashift=12  4K sectors".to_string(),
+            "ashift=13  8K sectors".to_string(),
+        ];
+
+        let position = self.menu_dialog(" Sector Size: ", &items, 2)?;
+        Ok(ASHIFTS[position])
+    }
+
+    fn input_pool_properties(&self, create: &mut CreateOptions) -> Result<(),()> {
+
+        let items: Vec<String> = pool::CREATE_PROPERTIES.iter()
+            .map(|(property, value, _)| format!("{}={}", property, value))
+            .collect();
+        let mut checked: Vec<bool> = pool::CREATE_PROPERTIES.iter().map(|(property, _, _)| *property == "compression").collect();
+
+        self.check_dialog(" Properties: ", &items, &mut checked)?;
+
+        for ((property, value, is_pool), checked) in pool::CREATE_PROPERTIES.iter().zip(checked) {
+            if !checked { continue }
+
+            let assignment = (property.to_string(), value.to_string());
+            if *is_pool { create.pool_properties.push(assignment); } else { create.filesystem_properties.push(assignment); }
+        }
+
+        let other = self.input_dialog(" Properties: ", "Other dataset properties (property=value ...)", "")?;
+
+        for assignment in other.split_whitespace() {
+            let (property, value) = assignment.split_once('=').unwrap_or((assignment, ""));
+
+            if let Err(error) = property::validate(property, value, false) {
+                self.message_dialog(" Properties: ", error.as_str());
+                return Err(());
+            }
+
+            create.filesystem_properties.push((property.to_string(), value.to_string()));
+        }
+
+        Ok(())
+    }
+
+    fn key_f3(&self) { 
+
+        let name = match self.selected_content().focused() {
+            Some(result) => result.name.to_owned(),
+            None => return,
+        };
+
+        let is_pool = matches!(self.content_type(), ContentType::Pools);
+        self.properties_dialog(name.as_str(), is_pool);
+    }
+
+    fn load_properties(&self, name: &str, is_pool: bool) -> Result<Vec<Property>,()> {
+
+        let result = if is_pool { self.backend.zpool_get_all(name) } else { self.backend.zfs_get_all(name) };
+
+        match result {
+            Ok(properties) => Ok(properties),
+            Err(output) => {
+                self.message_dialog(" Properties: ", output.error_message().as_str());
+                Err(())
+            },
+        }
+    }
+
+    fn properties_dialog(&self, name: &str, is_pool: bool) {
+
+        const KEY_INHERIT: i32 = 0x69;
+
+        let mut properties = match self.load_properties(name, is_pool) {
+            Ok(properties) => properties,
+            Err(_) => return,
+        };
+
+        let dialog_height = (self.max_y - 4).max(10);
+        let dialog_width = (self.max_x - 8).clamp(40, 100);
+        let visible = (dialog_height - 5) as usize;
+
+        let start_y = self.max_y/2 - dialog_height/2;
+        let start_x = self.max_x/2 - dialog_width/2;
+
+        let title = format!(" Properties: {} ", name);
+        let footnote = if is_pool { "ENTER Edit     ESC Close" } else { "ENTER Edit     i Inherit     ESC Close" };
+
+        let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title.as_str());
+
+        let text_width = dialog_width as usize - 4;
+        let source_width = 24;
+        let value_width = text_width - 22 - source_width - 2;
+
+        mvwaddstr(dialog, dialog_height-3, 2, "-".repeat(text_width).as_str());
+        let foot_x = dialog_width/2 - footnote.len() as i32/2;
+        mvwaddstr(dialog, dialog_height-2, foot_x, footnote);
+
+        let mut position = 0;
+        let mut start_from = 0;
+        let mut pending: Vec<usize> = Vec::new();
+
+        loop {
+            if position < start_from { start_from = position; }
+            if position >= start_from + visible { start_from = position + 1 - visible; }
+
+            let header = format!("{:<22} {:<value_width$} {}", "PROPERTY", "VALUE", "SOURCE", value_width = value_width);
+            wattron(dialog, A_BOLD());
+            mvwaddstr(dialog, 1, 2, Screen::fit_name(header.as_str(), text_width).as_str());
+            wattroff(dialog, A_BOLD());
+
+            for row in 0..visible {
+                let line = match properties.get(start_from + row) {
+                    Some(property) => format!("{} {} {}", Screen::fit_name(property.name.as_str(), 22),
+                        Screen::fit_name(property.display_value().as_str(), value_width), Screen::fit_name(property.source.as_str(), source_width)),
+                    None => String::new(),
+                };
+
+                if start_from + row == position { wattron(dialog, A_REVERSE()); }
+                if properties.get(start_from + row).is_some_and(|property| property.is_local()) { wattron(dialog, A_BOLD()); }
+                mvwaddstr(dialog, 2 + row as i32, 2, format!("{:<width$}", line, width = text_width).as_str());
+                wattroff(dialog, A_REVERSE());
+                wattroff(dialog, A_BOLD());
+            }
+
+            let status = if pending.is_empty() { String::new() } else { format!(" applying {} change(s)... ", pending.len()) };
+            mvwaddstr(dialog, dialog_height-3, 2, format!("{:-<width$}", status, width = text_width).as_str());
+
+            touchwin(dialog);
+            wrefresh(dialog);
+
+            let key = getch();
+
+            match key {
+                ERR => {
+                    let finished = pending.iter().any(|id| self.jobs.get(*id).is_none_or(|job| job.is_finished()));
+                    if finished {
+                        pending.retain(|id| self.jobs.get(*id).is_some_and(|job| !job.is_finished()));
+                        if let Ok(reloaded) = self.load_properties(name, is_pool) {
+                            properties = reloaded;
+                            position = position.min(properties.len().saturating_sub(1));
+                        }
+                    }
+                },
+                Screen::KEY_ESC => { delwin(dialog); return },
+                KEY_UP          => { position = position.saturating_sub(1); },
+                KEY_DOWN        => { position = (position + 1).min(properties.len().saturating_sub(1)); },
+                KEY_PPAGE       => { position = position.saturating_sub(visible); },
+                KEY_NPAGE       => { position = (position + visible).min(properties.len().saturating_sub(1)); },
+                KEY_HOME        => { position = 0; },
+                KEY_END         => { position = properties.len().saturating_sub(1); },
+                Screen::KEY_ENTER => {
+                    if let Some(property) = properties.get(position) {
+                        if let Some(id) = self.input_property_set(name, property, is_pool) {
+                            pending.push(id);
+                        }
+                    }
+                },
+                KEY_INHERIT | KEY_DC if !is_pool => {
+                    if let Some(property) = properties.get(position) {
+                        if let Some(id) = self.confirm_property_inherit(name, property) {
+                            pending.push(id);
+                        }
+                    }
+                },
+                _ => {},
+            }
+        }
+    }
+
+    fn input_property_set(&self, name: &str, property: &Property, is_pool: bool) -> Option<usize> {
+
+        if property.is_readonly() {
+            self.message_dialog(" Set Property: ", format!("'{}' is a read-only property.", property.name).as_str());
+            return None;
+        }
+
+        let prompt = format!("Enter the new value of '{}'", property.name);
+        let value = self.input_dialog(" Set Property: ", prompt.as_str(), property.value.as_str()).ok()?;
+
+        if let Err(error) = property::validate(property.name.as_str(), value.as_str(), is_pool) {
+            self.message_dialog(" Set Property: ", error.as_str());
+            return None;
+        }
+
+        let name = name.to_string();
+        let property = property.name.to_owned();
+        let description = format!("set {}={} on {}", property, value, name);
+
+        Some(self.jobs.submit(command::pool_name(&name).to_string(), description, move |zfs| {
+            if is_pool {
+                vec![zfs.zpool_set(&name, &property, &value)]
+            } else {
+                vec![zfs.zfs_set(&name, &property, &value)]
+            }
+        }))
+    }
+
+    fn confirm_property_inherit(&self, name: &str, property: &Property) -> Option<usize> {
+
+        if property.is_readonly() {
+            self.message_dialog(" Inherit Property: ", format!("'{}' is a read-only property.", property.name).as_str());
+            return None;
+        }
+
+        let prompt = format!("'{}' will be reset to the inherited or default value on: ", property.name);
+        self.confirm_dialog(" Confirm Inherit: ", prompt.as_str(), name).ok()?;
+
+        let name = name.to_string();
+        let property = property.name.to_owned();
+        let description = format!("inherit {} on {}", property, name);
+
+        Some(self.jobs.submit(command::pool_name(&name).to_string(), description, move |zfs| {
+            vec![zfs.zfs_inherit(&name, &property)]
+        }))
+    }
+
+    fn key_f4(&mut self) { 
+
+        let selected_elements = self.selected_elements();
+        if selected_elements.is_empty() { return; }
+
+        match self.content_type() {
+            ContentType::Pools =>     { },
+            ContentType::Datasets =>  { },
+            ContentType::Volumes =>   { },
+            ContentType::Snapshots => { self.input_snapshot_send(selected_elements); },
+            ContentType::Bookmarks => { },
+        };
+    }
+
+    fn input_snapshot_send(&mut self, selected_elements: Vec<String>) {
+
+        if selected_elements.len() > 1 {
+            self.message_dialog(" Send Snapshot: ", "Only a single snapshot can be sent at a time.");
+            return;
+        }
+
+        let snapshot = self.seleted_string(&selected_elements);
+
+        let (base, intermediate) = match self.input_send_base(snapshot.as_str()) {
+            Ok(base) => base,
+            Err(_) => return,
+        };
+
+        let target = match self.input_send_target(snapshot.as_str()) {
+            Ok(target) => target,
+            Err(_) => return,
+        };
+
+        let mut send = SendOptions::new(snapshot.as_str(), target);
+        send.base = base;
+        send.intermediate = intermediate;
+
+        if self.input_send_flags(&mut send).is_err() {
+            return;
+        }
+
+        let estimate = match self.backend.zfs_send_estimate(&send) {
+            Ok(estimate) => estimate,
+            Err(output) => {
+                self.message_dialog(" Send Snapshot: ", output.error_message().as_str());
+                return;
+            },
+        };
+
+        // Writing the stream truncates a file already at the path.
+        let overwrite = match &send.target {
+            Target::File(path) => self.file_exists(path),
+            Target::Dataset(_) => false,
+        };
+
+        let (title, prompt) = if overwrite {
+            (" Confirm Overwrite: ", format!("Estimated stream size {}, it will overwrite the existing file:", column::format_size(estimate)))
+        } else {
+            (" Confirm Send: ", format!("Estimated stream size {}, it will be written to:", column::format_size(estimate)))
+        };
+        if self.confirm_dialog(title, prompt.as_str(), send.target.name()).is_err() {
+            return;
+        }
+
+        let queue = match &send.target {
+            Target::Dataset(target) => command::pool_name(target).to_string(),
+            Target::File(_) => command::pool_name(&send.snapshot).to_string(),
+        };
+
+        let progress = Progress::new(estimate);
+        let job_progress = progress.clone();
+
+        self.selected_content_mut().selected_elements.clear();
+        self.show_jobs = true;
+
+        self.jobs.submit_with_progress(queue, send.description(), Some(progress), move |zfs| {
+            vec![zfs.zfs_send(&send, &job_progress)]
+        });
+    }
+
+    fn file_exists(&self, path: &str) -> bool {
+
+        let (directory, name) = match path.rfind('/') {
+            Some(0) => ("/", &path[1..]),
+            Some(index) => (&path[..index], &path[index + 1..]),
+            None => (".", path),
+        };
+
+        self.backend.list_directory(directory).is_ok_and(|entries| entries.iter().any(|entry| entry.name == name))
+    }
+
+    // Offers the earlier snapshots and bookmarks of the same dataset as
+    // incremental source. A bookmark only works for a -i stream.
+    fn input_send_base(&self, snapshot: &str) -> Result<(Option<String>, bool),()> {
+
+        let content = self.selected_content();
+        let dataset = snapshot.split('@').next().unwrap_or(snapshot);
+        let bookmarks = self.backend.zfs_bookmarks().unwrap_or_default();
+
+        let creation = |result: &command::CommandResult| result.property("creation").parse::<u64>().unwrap_or(0);
+        let snapshot_creation = content.all_results.iter().find(|result| result.name == snapshot).map_or(0, creation);
+
+        let mut bases: Vec<&command::CommandResult> = content.all_results.iter()
+            .chain(bookmarks.iter())
+            .filter(|result| result.name.split(['@', '#']).next() == Some(dataset))
+            .filter(|result| creation(result) < snapshot_creation)
+            .collect();
+        bases.sort_by_key(|result| std::cmp::Reverse(creation(result)));
+
+        if bases.is_empty() {
+            return Ok((None, false));
+        }
+
+        let mut items = vec!["Full stream".to_string()];
+        items.extend(bases.iter().map(|result| format!("from {}", &result.name[dataset.len()..])));
+
+        let position = self.menu_dialog(" Incremental Source: ", &items, 0)?;
+        if position == 0 {
+            return Ok((None, false));
+        }
+
+        let base = bases[position - 1].name.to_owned();
+        if base.contains('#') {
+            return Ok((Some(base), false));
+        }
+
+        let items = vec!["-i  changes since the base".to_string(), "-I  with intermediate snapshots".to_string()];
+        let intermediate = self.menu_dialog(" Incremental Stream: ", &items, 0)? == 1;
+
+        Ok((Some(base), intermediate))
+    }
+
+    // Suggests a dataset under the one focused in the other panel.
+    // Asks whether the stream goes to a dataset or a file, then for its name.
+    fn input_send_target(&self, snapshot: &str) -> Result<Target,()> {
+
+        let items = vec!["Dataset (zfs receive)".to_string(), "Stream file".to_string()];
+        let file = self.menu_dialog(" Send To: ", &items, 0)? == 1;
+
+        let (prompt, default) = if file {
+            ("Enter the path of the stream file", String::new())
+        } else {
+            ("Enter the target dataset", self.send_target(snapshot))
+        };
+
+        let target = self.input_dialog(" Send Snapshot: ", prompt, default.as_str())?;
+        if target.is_empty() { return Err(()) }
+
+        let pools: Vec<String> = self.backend.zfs_pools().unwrap_or_default().into_iter().map(|pool| pool.name).collect();
+
+        Target::parse(target.as_str(), file, &pools).map_err(|error| {
+            self.message_dialog(" Send Snapshot: ", error.as_str());
+        })
+    }
+
+    fn send_target(&self, snapshot: &str) -> String {
+
+        let other = if self.left_content.is_selected { &self.right_content } else { &self.left_content };
+        let dataset = snapshot.split('@').next().unwrap_or(snapshot);
+        let dataset_name = dataset.rsplit('/').next().unwrap_or(dataset);
+
+        match (&other.c_type, other.focused()) {
+            (ContentType::Datasets, Some(result)) => format!("{}/{}", result.name, dataset_name),
+            _ => String::new(),
+        }
+    }
+
+    fn input_send_flags(&self, send: &mut SendOptions) -> Result<(),()> {
+
+        let items = [
+            "Raw stream (-w)", "Compressed (-c)", "Properties (-p)", "Recursive (-R)",
+            "Resumable receive (-s)", "Force receive (-F)",
+        ].iter().map(|item| item.to_string()).collect::<Vec<String>>();
+
+        let mut checked = vec![send.raw, send.compressed, send.properties, send.recursive, send.resumable, send.force];
+
+        self.check_dialog(" Send Options: ", &items, &mut checked)?;
+
+        send.raw        = checked[0];
+        send.compressed = checked[1];
+        send.properties = checked[2];
+        send.recursive  = checked[3];
+        send.resumable  = checked[4];
+        send.force      = checked[5];
+
+        Ok(())
+    }
+
+    fn key_f5(&mut self) { 
+
+        let selected_elements = self.selected_elements();
+        if selected_elements.is_empty() { return; }
+
+        match self.content_type() {
+            ContentType::Pools =>     { },
+            ContentType::Datasets =>  { self.input_snapshot_dataset(selected_elements); },
+            ContentType::Volumes =>   { self.input_snapshot_dataset(selected_elements); },
+            ContentType::Snapshots => { self.input_snapshot_clone(selected_elements);   },
+            ContentType::Bookmarks => { },
+        };
+    }
+
+    fn input_snapshot_clone(&self, selected_elements: Vec<String>) {
+
+        if selected_elements.len() > 1 {
+            self.message_dialog(" Clone Snapshot: ", "Only a single snapshot can be cloned at a time.");
+            return;
+        }
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        if let Ok(dataset_name) = self.input_dialog(" Clone Snapshot: ", "Enter the name of the new Snapshot", "") {
+            let description = format!("clone {} to {}", selected_string, dataset_name);
+            self.jobs.submit(command::pool_name(&dataset_name).to_string(), description, move |zfs| {
+                vec![zfs.zfs_clone(&selected_string, &dataset_name)]
+            });
+        }
+
+        wrefresh(stdscr());
+    }
+
+    fn input_snapshot_dataset(&mut self, selected_elements: Vec<String>) {
+
+        if selected_elements.len() > 1 {
+            self.input_snapshot_datasets(selected_elements);
+            return;
+        }
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        let snapshot = format!("{}@", selected_string);
+
+        if let Ok(dataset_name) = self.input_dialog(" Snapshot Dataset: ", "Enter the name of the new Snapshot", snapshot.as_str()) {
+            let description = format!("snapshot {}", dataset_name);
+            self.jobs.submit(command::pool_name(&dataset_name).to_string(), description, move |zfs| {
+                vec![zfs.zfs_snapshot(&dataset_name)]
+            });
+        }
+
+        wrefresh(stdscr());
+    }
+
+    fn input_snapshot_datasets(&mut self, selected_elements: Vec<String>) {
+
+        let prompt = format!("Enter the name of the new Snapshot for {} elements", selected_elements.len());
+
+        if let Ok(snapshot_name) = self.input_dialog(" Snapshot Datasets: ", prompt.as_str(), "") {
+            self.selected_content_mut().selected_elements.clear();
+
+            for element in selected_elements {
+                let dataset_name = format!("{}@{}", element, snapshot_name);
+                let description = format!("snapshot {}", dataset_name);
+                self.jobs.submit(command::pool_name(&dataset_name).to_string(), description, move |zfs| {
+                    vec![zfs.zfs_snapshot(&dataset_name)]
+                });
+            }
+        }
+
+        wrefresh(stdscr());
+    }
+
+    fn key_f6(&self) { 
+
+        let selected_elements = self.selected_elements();
+        if selected_elements.is_empty() { return; }
+
+        match self.content_type() {
+            ContentType::Pools =>     { },
+            ContentType::Datasets =>  { self.input_dataset_rename(selected_elements); },
+            ContentType::Volumes =>   { self.input_dataset_rename(selected_elements); },
+            ContentType::Snapshots => { self.input_dataset_rename(selected_elements); },
+            ContentType::Bookmarks => { },
+        };
+    }
+
+    fn input_dataset_rename(&self, selected_elements: Vec<String>) {
+
+        if selected_elements.len() > 1 {
+            self.message_dialog(" Rename Dataset: ", "Only a single element can be renamed at a time.");
+            return;
+        }
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        if let Ok(new_dataset_name) = self.input_dialog(" Rename Dataset: ", "Enter the new name for the Dataset", selected_string.as_str()) {
+            let description = format!("rename {} to {}", selected_string, new_dataset_name);
+            self.jobs.submit(command::pool_name(&selected_string).to_string(), description, move |zfs| {
+                vec![zfs.zfs_rename(&selected_string, &new_dataset_name)]
+            });
+        }
+
+        wrefresh(stdscr());
+    }
+
+    fn key_f7(&mut self) {
+
+        let selected_elements = self.selected_elements();
+        if selected_elements.is_empty() { return; }
+
+        match self.content_type() {
+            ContentType::Pools =>     { self.confirm_pool_scrub(selected_elements); },
+            ContentType::Datasets =>  { },
+            ContentType::Volumes =>   { },
+            ContentType::Snapshots => { self.confirm_snapshot_rollback(selected_elements); },
+            ContentType::Bookmarks => { },
+        };
+    }
+
+    fn confirm_pool_scrub(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "scrub", Control::Start);
+    }
+
+    fn confirm_scrub_pause(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "scrub", Control::Pause);
+    }
+
+    fn confirm_scrub_cancel(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "scrub", Control::Cancel);
+    }
+
+    fn confirm_trim_start(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "trim", Control::Start);
+    }
+
+    fn confirm_trim_suspend(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "trim", Control::Pause);
+    }
+
+    fn confirm_trim_cancel(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "trim", Control::Cancel);
+    }
+
+    fn confirm_initialize_start(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "initialize", Control::Start);
+    }
+
+    fn confirm_initialize_suspend(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "initialize", Control::Pause);
+    }
+
+    fn confirm_initialize_cancel(&mut self, selected_elements: Vec<String>) {
+        self.confirm_pool_control(selected_elements, "initialize", Control::Cancel);
+    }
+
+    // Starts, pauses or cancels a scrub, trim or initialize of the pools.
+    fn confirm_pool_control(&mut self, selected_elements: Vec<String>, operation: &'static str, control: Control) {
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        let title = match operation {
+            "scrub" => " Confirm Scrub: ",
+            "trim" => " Confirm Trim: ",
+            _ => " Confirm Initialize: ",
+        };
+
+        let prompt = match control {
+            Control::Start if operation == "scrub" => "The following pools(s) will be scrubbed: ".to_string(),
+            Control::Start => format!("A {} will be started or resumed on the following pool(s): ", operation),
+            Control::Pause if operation == "scrub" => "The scrub of the following pool(s) will be paused: ".to_string(),
+            Control::Pause => format!("The {} of the following pool(s) will be suspended: ", operation),
+            Control::Cancel => format!("The {} of the following pool(s) will be canceled: ", operation),
+        };
+
+        if self.confirm_dialog(title, prompt.as_str(), selected_string.as_str()).is_err() {
+            return;
+        }
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for element in selected_elements {
+            self.submit_pool_job(element.as_str(), control.description(operation, element.as_str()), move |zfs, pool_name| {
+                match operation {
+                    "scrub" => zfs.zpool_scrub(pool_name, control),
+                    "trim" => zfs.zpool_trim(pool_name, control),
+                    _ => zfs.zpool_initialize(pool_name, control),
+                }
+            });
+        }
+    }
+
+    fn confirm_snapshot_rollback(&mut self, selected_elements: Vec<String>) {
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        let title = " Confirm Rollback: ";
+        let prompt = "The Dataset(s) will be rolled back to the following snapshot(s): ";
+
+        if self.confirm_dialog(title, prompt, selected_string.as_str()).is_err() {
+            return;
+        }
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for element in selected_elements {
+            let description = format!("rollback {}", element);
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                vec![zfs.zfs_rollback(&element)]
+            });
+        }
+    }
+
+    fn key_f8(&mut self) {
+
+        let mut selected_elements = self.selected_elements();
+        if selected_elements.is_empty() { return; }
+
+        // zfs refuses to destroy a snapshot with user holds or clones; say so
+        // up front instead of letting the job fail.
+        let blocked: Vec<(String, String)> = self.selected_content().all_results.iter()
+            .filter(|result| selected_elements.contains(&result.name))
+            .filter_map(|result| {
+                if hold::is_held(result.property("userrefs")) {
+                    Some((result.name.to_owned(), format!("held: {}", result.property(column::HOLDS))))
+                } else if dependency::has_clones(result.property("clones")) {
+                    Some((result.name.to_owned(), format!("clones: {}", result.property("clones"))))
+                } else {
+                    None
+                }
+            })
+            .collect();
+
+        if !blocked.is_empty() {
+            let mut text = "Snapshots with user holds or clones cannot be destroyed.\nRelease the holds, or promote or destroy the clones, first:\n".to_string();
+            for (name, reason) in blocked.iter() {
+                text.push_str(format!("  {} ({})\n", name, reason).as_str());
+            }
+
+            self.message_dialog(" Destroy: ", text.as_str());
+
+            selected_elements.retain(|element| !blocked.iter().any(|(name, _)| name == element));
+            if selected_elements.is_empty() { return; }
+        }
+
+        let selected_string = self.seleted_string(&selected_elements);
+
+        let title = " Confirm Destroy: ";
+        let prompt = "The following element(s) will be destroyed: ";
+
+        if self.confirm_dialog(title, prompt, selected_string.as_str()).is_err() {
+            return;
+        }
+
+        let is_pool = matches!(self.content_type(), ContentType::Pools);
+        self.selected_content_mut().selected_elements.clear();
+
+        self.destroy_elements(selected_elements, is_pool);
+    }
+
+    // One job per element, queued by pool.
+    fn destroy_elements(&mut self, elements: Vec<String>, is_pool: bool) {
+
+        for element in elements {
+            let description = format!("destroy {}", element);
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                if is_pool {
+                    vec![zfs.zpool_destroy(&element)]
+                } else {
+                    vec![zfs.zfs_destroy(&element)]
+                }
+            });
+        }
+    }
+
+    // Operations without a function key of their own, offered for the
+    // elements they apply to.
+    fn key_f9(&mut self) { 
+
+        let selected_elements = self.selected_elements();
+        let is_pool = matches!(self.content_type(), ContentType::Pools);
+
+        // Pools can be imported with nothing selected.
+        if selected_elements.is_empty() && !is_pool { return; }
+
+        let content = self.selected_content();
+        let has_resume_token = selected_elements.iter().any(|element| content.has_resume_token(element));
+
+        let mut actions: Vec<(&str, Action)> = Vec::new();
+
+        match self.content_type() {
+            ContentType::Pools => {
+                let activities: Vec<ScanProgress> = selected_elements.iter()
+                    .filter_map(|pool_name| self.backend.zpool_status(pool_name).ok())
+                    .flat_map(|status| status.activities())
+                    .collect();
+                let running = |operation: &str| activities.iter().any(|activity| activity.operation == operation && !activity.paused);
+                let paused = |operation: &str| activities.iter().any(|activity| activity.operation == operation && activity.paused);
+
+                if running("scrub") { actions.push(("Pause scrub", Screen::confirm_scrub_pause)); }
+                if paused("scrub") { actions.push(("Resume scrub", Screen::confirm_pool_scrub)); }
+                if running("scrub") || paused("scrub") { actions.push(("Cancel scrub", Screen::confirm_scrub_cancel)); }
+
+                if !selected_elements.is_empty() {
+                    actions.push((if paused("trim") { "Resume trim" } else { "Start trim" }, Screen::confirm_trim_start));
+                }
+                if running("trim") { actions.push(("Suspend trim", Screen::confirm_trim_suspend)); }
+                if running("trim") || paused("trim") { actions.push(("Cancel trim", Screen::confirm_trim_cancel)); }
+
+                if !selected_elements.is_empty() {
+                    actions.push((if paused("initialize") { "Resume initialize" } else { "Start initialize" }, Screen::confirm_initialize_start));
+                }
+                if running("initialize") { actions.push(("Suspend initialize", Screen::confirm_initialize_suspend)); }
+                if running("initialize") || paused("initialize") { actions.push(("Cancel initialize", Screen::confirm_initialize_cancel)); }
+
+                actions.push(("Import pools...", Screen::input_pool_import));
+                if !selected_elements.is_empty() {
+                    actions.push(("Export", Screen::confirm_pool_export));
+                }
+            },
+            ContentType::Datasets | ContentType::Volumes => {
+                if has_resume_token {
+                    actions.push(("Resume receive", Screen::resume_receive));
+                    actions.push(("Abort receive", Screen::confirm_receive_abort));
+                }
+                actions.push(("Snapshot policy...", Screen::input_snapshot_policy));
+                actions.push(("Automatic snapshots (com.sun:auto-snapshot)...", Screen::input_auto_snapshot));
+                actions.push(("Show dependencies...", Screen::show_dependencies));
+                if selected_elements.iter().any(|element| content.has_origin(element)) {
+                    actions.push(("Promote clone", Screen::confirm_dataset_promote));
+                }
+            },
+            ContentType::Snapshots => {
+                actions.push(("Show differences...", Screen::show_snapshot_diff));
+                actions.push(("Browse and restore files...", Screen::browse_snapshot));
+                actions.push(("Bookmark...", Screen::input_snapshot_bookmark));
+                actions.push(("Hold...", Screen::input_snapshot_hold));
+                if selected_elements.iter().any(|element| content.is_held(element)) {
+                    actions.push(("Release holds...", Screen::input_snapshot_release));
+                }
+                actions.push(("Show dependencies...", Screen::show_dependencies));
+            },
+            ContentType::Bookmarks => { },
+        };
+
+        if actions.is_empty() {
+            self.message_dialog(" Actions: ", "No further actions for the selected element(s).");
+            return;
+        }
+
+        let items: Vec<String> = actions.iter().map(|(label, _)| label.to_string()).collect();
+
+        if let Ok(position) = self.menu_dialog(" Actions: ", &items, 0) {
+            (actions[position].1)(self, selected_elements);
+        }
+    }
+
+    fn input_pool_import(&mut self, _selected_elements: Vec<String>) {
+
+        let directories = match self.input_dialog(" Import Pools: ", "Directories to search (-d), empty for /dev", "") {
+            Ok(directories) => directories.split_whitespace().map(|directory| directory.to_string()).collect::<Vec<String>>(),
+            Err(_) => return,
+        };
+
+        let items = vec!["Exported pools".to_string(), "Destroyed pools (-D)".to_string()];
+        let destroyed = match self.menu_dialog(" Import Pools: ", &items, 0) {
+            Ok(position) => position == 1,
+            Err(_) => return,
+        };
+
+        let pools = match self.backend.zpool_importable(&directories, destroyed) {
+            Ok(pools) => pools,
+            Err(output) => {
+                self.message_dialog(" Import Pools: ", output.error_message().as_str());
+                return;
+            },
+        };
+
+        if pools.is_empty() {
+            self.message_dialog(" Import Pools: ", "No pools available to import.");
+            return;
+        }
+
+        let items: Vec<String> = pools.iter()
+            .map(|status| format!("{:<16} {:>20}  {}", status.name, status.id, status.state))
+            .collect();
+
+        let status = match self.menu_dialog(" Import Pools: ", &items, 0) {
+            Ok(position) => &pools[position],
+            Err(_) => return,
+        };
+
+        // Pools sharing a name can only be told apart by their id.
+        let ambiguous = pools.iter().filter(|other| other.name == status.name).count() > 1;
+        let mut import = ImportOptions::new(if ambiguous { status.id.as_str() } else { status.name.as_str() });
+        import.directories = directories;
+        import.destroyed = destroyed;
+
+        let items = vec![
+            "Read-only (-o readonly=on)".to_string(),
+            "Force (-f)".to_string(),
+            "Rename on import".to_string(),
+            "Alternate root (-R)".to_string(),
+        ];
+        let mut checked = vec![false, status.is_destroyed(), false, false];
+
+        if self.check_dialog(" Import Options: ", &items, &mut checked).is_err() {
+            return;
+        }
+
+        import.readonly = checked[0];
+        import.force = checked[1];
+
+        if checked[2] {
+            match self.input_dialog(" Import Pools: ", "Enter the new name of the pool", status.name.as_str()) {
+                Ok(new_name) if !new_name.trim().is_empty() => import.new_name = Some(new_name.trim().to_string()),
+                _ => return,
+            }
+        }
+
+        if checked[3] {
+            match self.input_dialog(" Import Pools: ", "Enter the alternate root directory", "/mnt") {
+                Ok(altroot) if !altroot.trim().is_empty() => import.altroot = Some(altroot.trim().to_string()),
+                _ => return,
+            }
+        }
+
+        let mut text = format!("zpool {}\n\n", import.arguments().join(" "));
+        for vdev in status.config.iter() {
+            let name = if vdev.is_device() { device_label(vdev.name.as_str()) } else { vdev.name.as_str() };
+            text.push_str(format!("  {}{}  {}\n", "  ".repeat(vdev.depth), name, vdev.state).as_str());
+        }
+
+        if self.confirm_text_dialog(" Confirm Import: ", text.trim_end()).is_err() {
+            return;
+        }
+
+        self.jobs.submit(import.imported_name().to_string(), import.description(), move |zfs| {
+            vec![zfs.zpool_import(&import)]
+        });
+    }
+
+    fn confirm_pool_export(&mut self, selected_elements: Vec<String>) {
+
+        let selected_string = self.seleted_string(&selected_elements);
+        let prompt = "The following pool(s) will be exported: ";
+
+        if self.confirm_dialog(" Confirm Export: ", prompt, selected_string.as_str()).is_err() {
+            return;
+        }
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for pool_name in selected_elements {
+            self.submit_pool_job(pool_name.as_str(), format!("export {}", pool_name), |zfs, pool_name| zfs.zpool_export(pool_name));
+        }
+    }
+
+    fn resume_receive(&mut self, selected_elements: Vec<String>) {
+
+        let content = self.selected_content();
+        let resumable: Vec<String> = selected_elements.into_iter()
+            .filter(|element| content.has_resume_token(element))
+            .collect();
+
+        if resumable.len() > 1 {
+            self.message_dialog(" Resume Receive: ", "Only a single receive can be resumed at a time.");
+            return;
+        }
+
+        let dataset_name = self.seleted_string(&resumable);
+        let token = match content.all_results.iter().find(|result| result.name == dataset_name) {
+            Some(result) => result.property(column::RESUME_TOKEN).to_string(),
+            None => return,
+        };
+
+        let send = SendOptions::resume(token.as_str(), dataset_name.as_str());
+
+        let estimate = match self.backend.zfs_send_estimate(&send) {
+            Ok(estimate) => estimate,
+            Err(output) => {
+                self.message_dialog(" Resume Receive: ", output.error_message().as_str());
+                return;
+            },
+        };
+
+        let prompt = format!("{} left to send, the interrupted receive will resume on:", column::format_size(estimate));
+        if self.confirm_dialog(" Confirm Resume: ", prompt.as_str(), dataset_name.as_str()).is_err() {
+            return;
+        }
+
+        let progress = Progress::new(estimate);
+        let job_progress = progress.clone();
+
+        self.selected_content_mut().selected_elements.clear();
+        self.show_jobs = true;
+
+        self.jobs.submit_with_progress(command::pool_name(&dataset_name).to_string(), send.description(), Some(progress), move |zfs| {
+            vec![zfs.zfs_send(&send, &job_progress)]
+        });
+    }
+
+    fn confirm_receive_abort(&mut self, selected_elements: Vec<String>) {
+
+        let content = self.selected_content();
+        let resumable: Vec<String> = selected_elements.into_iter()
+            .filter(|element| content.has_resume_token(element))
+            .collect();
+
+        let selected_string = self.seleted_string(&resumable);
+
+        let title = " Confirm Abort: ";
+        let prompt = "The partially received state will be discarded on: ";
+
+        if self.confirm_dialog(title, prompt, selected_string.as_str()).is_err() {
+            return;
+        }
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for element in resumable {
+            let description = format!("abort receive into {}", element);
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                vec![zfs.zfs_receive_abort(&element)]
+            });
+        }
+    }
+
+    fn confirm_dataset_promote(&mut self, selected_elements: Vec<String>) {
+
+        let content = self.selected_content();
+        let clones: Vec<String> = selected_elements.into_iter()
+            .filter(|element| content.has_origin(element))
+            .collect();
+
+        if !self.promote_clones(clones).is_empty() {
+            self.selected_content_mut().selected_elements.clear();
+        }
+    }
+
+    // Confirms and queues `zfs promote` of clones, returning the jobs.
+    fn promote_clones(&mut self, clones: Vec<String>) -> Vec<usize> {
+
+        let selected_string = self.seleted_string(&clones);
+
+        let title = " Confirm Promote: ";
+        let prompt = "The following clone(s) will take over their origin snapshots: ";
+
+        if self.confirm_dialog(title, prompt, selected_string.as_str()).is_err() {
+            return Vec::new();
+        }
+
+        clones.into_iter().map(|element| {
+            let description = format!("promote {}", element);
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                vec![zfs.zfs_promote(&element)]
+            })
+        }).collect()
+    }
+
+    fn show_dependencies(&mut self, selected_elements: Vec<String>) {
+
+        if let Some(element) = selected_elements.first() {
+            let dataset_name = element.split('@').next().unwrap_or("").to_string();
+            self.dependency_view(dataset_name.as_str());
+        }
+    }
+
+    // The origin snapshots a dataset was cloned from, and the clones of its
+    // own snapshots down to their clones in turn. Clones can be promoted
+    // from here, which reverses their dependency on the origin.
+    fn dependency_view(&mut self, dataset_name: &str) {
+
+        const KEY_PROMOTE: i32 = 0x70;
+
+        let load = |backend: &dyn Backend| backend.zfs_list_properties("filesystem,volume", &["origin".to_string()]);
+
+        let mut datasets = match load(self.backend.as_ref()) {
+            Ok(datasets) => datasets,
+            Err(output) => {
+                self.message_dialog(" Dependencies: ", output.error_message().as_str());
+                return;
+            },
+        };
+
+        let height = self.max_y - 1;
+        let width = self.max_x;
+        let text_width = width as usize - 4;
+
+        let title = format!(" Dependencies: {} ", dataset_name);
+        let window = Screen::draw_window(height, width, 0, 0, title.as_str());
+
+        let footnote = "p Promote clone     ESC Close";
+        let visible = (height - 4) as usize;
+
+        let mut position = 0;
+        let mut start_from = 0;
+        let mut pending: Vec<usize> = Vec::new();
+
+        loop {
+            // Each line, with the clone it shows when it can be promoted.
+            let mut lines: Vec<(String, attr_t, Option<String>)> = Vec::new();
+            let clone = |name: &str| dependency::origin(&datasets, name).map(|_| name.to_string());
+
+            lines.push(("Origin chain:".to_string(), A_BOLD(), None));
+            lines.push((format!("  {}", dataset_name), A_NORMAL(), clone(dataset_name)));
+            let chain = dependency::origin_chain(&datasets, dataset_name);
+            for (depth, origin) in chain.iter().enumerate() {
+                lines.push((format!("  {:indent$}cloned from {}", "", origin, indent = 2 * depth + 2), A_NORMAL(), None));
+            }
+            if chain.is_empty() {
+                lines.push(("    not a clone".to_string(), A_DIM(), None));
+            }
+
+            lines.push((String::new(), A_NORMAL(), None));
+            lines.push(("Dependents:".to_string(), A_BOLD(), None));
+            let dependents = dependency::dependents(&datasets, dataset_name);
+            for (depth, name) in dependents.iter() {
+                lines.push((format!("  {:indent$}{}", "", name, indent = 2 * depth), A_NORMAL(), clone(name)));
+            }
+            if dependents.is_empty() {
+                lines.push(("  no snapshot has clones".to_string(), A_DIM(), None));
+            }
+
+            let clone_lines: Vec<usize> = lines.iter().enumerate()
+                .filter(|(_, (_, _, clone))| clone.is_some())
+                .map(|(i, _)| i)
+                .collect();
+
+            position = position.min(clone_lines.len().saturating_sub(1));
+            let current = clone_lines.get(position).copied().unwrap_or(usize::MAX);
+
+            if current != usize::MAX {
+                if current < start_from { start_from = current; }
+                if current >= start_from + visible { start_from = current + 1 - visible; }
+            }
+            start_from = start_from.min(lines.len().saturating_sub(visible));
+
+            for row in 0..visible {
+                let (text, attributes) = match lines.get(start_from + row) {
+                    Some((text, attributes, _)) => (text.as_str(), *attributes),
+                    None => ("", A_NORMAL()),
+                };
+
+                let attributes = if start_from + row == current { attributes | A_REVERSE() } else { attributes };
+
+                wattron(window, attributes);
+                mvwaddstr(window, 1 + row as i32, 2, format!("{:<width$}", text.chars().take(text_width).collect::<String>(), width = text_width).as_str());
+                wattroff(window, attributes);
+            }
+
+            let progress = if pending.is_empty() { String::new() } else { format!(" promoting {} clone(s)... ", pending.len()) };
+            mvwaddstr(window, height-3, 2, format!("{:-<width$}", progress, width = text_width).as_str());
+            mvwaddstr(window, height-2, width/2 - footnote.len() as i32/2, footnote);
+            touchwin(window);
+            wrefresh(window);
+
+            let selected = lines.get(current).and_then(|(_, _, clone)| clone.to_owned());
+
+            match getch() {
+                ERR => {
+                    let finished = pending.iter().any(|id| self.jobs.get(*id).is_none_or(|job| job.is_finished()));
+                    if finished {
+                        pending.retain(|id| self.jobs.get(*id).is_some_and(|job| !job.is_finished()));
+                        self.check_jobs();
+
+                        if let Ok(refreshed) = load(self.backend.as_ref()) {
+                            datasets = refreshed;
+                        }
+                    }
+                },
+                Screen::KEY_ESC => { delwin(window); return },
+                KEY_PROMOTE => {
+                    if let Some(clone) = selected {
+                        pending.extend(self.promote_clones(vec![clone]));
+                    }
+                },
+                KEY_UP          => { position = position.saturating_sub(1); },
+                KEY_DOWN        => { position += 1; },
+                KEY_HOME        => { position = 0; },
+                KEY_END         => { position = usize::MAX; },
+                _               => {},
+            }
+        }
+    }
+
+    // Compares the focused (or first tagged) snapshot with the next snapshot of
+    // the same filesystem, or with the filesystem itself.
+    fn show_snapshot_diff(&mut self, selected_elements: Vec<String>) {
+
+        let snapshot_name = match selected_elements.first() {
+            Some(snapshot_name) => snapshot_name.to_owned(),
+            None => return,
+        };
+
+        let dataset_name = snapshot_name.split('@').next().unwrap_or("").to_string();
+        let creation = |result: &command::CommandResult| result.property("creation").parse::<i64>().unwrap_or(0);
+
+        let mut snapshots: Vec<&command::CommandResult> = self.selected_content().all_results.iter()
+            .filter(|result| result.name.split('@').next() == Some(dataset_name.as_str()))
+            .collect();
+        snapshots.sort_by_key(|result| creation(result));
+
+        let next = snapshots.iter()
+            .position(|result| result.name == snapshot_name)
+            .and_then(|position| snapshots.get(position + 1))
+            .map(|result| result.name.to_owned());
+
+        let target = match next {
+            Some(next) => {
+                let items = vec![format!("Next snapshot: {}", next), format!("Live filesystem: {}", dataset_name)];
+                match self.menu_dialog(" Compare With: ", &items, 0) {
+                    Ok(0) => Some(next),
+                    Ok(_) => None,
+                    Err(_) => return,
+                }
+            },
+            None => None,
+        };
+
+        match self.backend.zfs_diff(&snapshot_name, target.as_deref()) {
+            Ok(changes) => {
+                let title = format!(" Diff: {} -> {} ", snapshot_name, target.as_deref().unwrap_or(dataset_name.as_str()));
+                self.diff_view(title.as_str(), &changes);
+            },
+            Err(output) => self.message_dialog(" Differences: ", output.error_message().as_str()),
+        }
+    }
+
+    fn browse_snapshot(&mut self, selected_elements: Vec<String>) {
+
+        let snapshot_name = match selected_elements.first() {
+            Some(snapshot_name) => snapshot_name.to_owned(),
+            None => return,
+        };
+
+        let dataset_name = snapshot_name.split('@').next().unwrap_or("");
+
+        let mountpoint = match self.backend.zfs_get_all(dataset_name) {
+            Ok(properties) => properties.into_iter()
+                .find(|property| property.name == "mountpoint")
+                .map(|property| property.value)
+                .filter(|mountpoint| mountpoint.starts_with('/')),
+            Err(output) => {
+                self.message_dialog(" Browse Snapshot: ", output.error_message().as_str());
+                return;
+            },
+        };
+
+        match mountpoint {
+            Some(mountpoint) => self.restore_view(snapshot_name.as_str(), mountpoint.as_str()),
+            None => self.message_dialog(" Browse Snapshot: ", format!("{} is not a mounted filesystem.", dataset_name).as_str()),
+        }
+    }
+
+    fn input_snapshot_policy(&mut self, selected_elements: Vec<String>) {
+
+        let dataset_name = match selected_elements.first() {
+            Some(dataset_name) => dataset_name.to_owned(),
+            None => return,
+        };
+
+        let mut schedule = match Schedule::load(self.schedule_path.as_str()) {
+            Ok(schedule) => schedule,
+            Err(error) => {
+                self.message_dialog(" Snapshot Policy: ", error.as_str());
+                return;
+            },
+        };
+
+        let policy = schedule.policies.iter().find(|policy| policy.dataset == dataset_name).cloned()
+            .unwrap_or_else(|| Policy::new(dataset_name.as_str()));
+
+        if let Some(policy) = self.input_policy(policy) {
+            self.save_policy(&mut schedule, policy, None);
+        }
+    }
+
+    // Opts the tagged datasets in or out of the policies covering them.
+    fn input_auto_snapshot(&mut self, selected_elements: Vec<String>) {
+
+        let items = vec![
+            "Opt in (true)".to_string(),
+            "Opt out (false)".to_string(),
+            "Inherit from the parent".to_string(),
+        ];
+
+        let title = format!(" {}: ", schedule::PROPERTY);
+        let value = match self.menu_dialog(title.as_str(), &items, 0) {
+            Ok(0) => Some("true"),
+            Ok(1) => Some("false"),
+            Ok(_) => None,
+            Err(_) => return,
+        };
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for element in selected_elements {
+            let description = match value {
+                Some(value) => format!("set {}={} on {}", schedule::PROPERTY, value, element),
+                None => format!("inherit {} on {}", schedule::PROPERTY, element),
+            };
+
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                match value {
+                    Some(value) => vec![zfs.zfs_set(&element, schedule::PROPERTY, value)],
+                    None => vec![zfs.zfs_inherit(&element, schedule::PROPERTY)],
+                }
+            });
+        }
+    }
+
+    // A single snapshot gets the name given, several are each bookmarked
+    // under their own name.
+    fn input_snapshot_bookmark(&mut self, selected_elements: Vec<String>) {
+
+        let title = " Bookmark Snapshots: ";
+        let bookmark_name = |snapshot: &str| snapshot.replacen('@', "#", 1);
+
+        let bookmarks: Vec<(String, String)> = if selected_elements.len() == 1 {
+            let snapshot = selected_elements[0].to_owned();
+            match self.input_dialog(title, "Enter the name of the new bookmark", bookmark_name(snapshot.as_str()).as_str()) {
+                Ok(bookmark) if !bookmark.trim().is_empty() => vec![(snapshot, bookmark.trim().to_string())],
+                _ => return,
+            }
+        } else {
+            let selected_string = self.seleted_string(&selected_elements);
+            if self.confirm_dialog(title, "Bookmark each snapshot under its own name:", selected_string.as_str()).is_err() {
+                return;
+            }
+            selected_elements.iter().map(|snapshot| (snapshot.to_owned(), bookmark_name(snapshot))).collect()
+        };
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for (snapshot, bookmark) in bookmarks {
+            let description = format!("bookmark {} as {}", snapshot, bookmark);
+            self.jobs.submit(command::pool_name(&snapshot).to_string(), description, move |zfs| {
+                vec![zfs.zfs_bookmark(&snapshot, &bookmark)]
+            });
+        }
+    }
+
+    // Places a hold with the same tag on every selected snapshot.
+    fn input_snapshot_hold(&mut self, selected_elements: Vec<String>) {
+
+        let tag = match self.input_dialog(" Hold Snapshots: ", "Tag of the hold", "keep") {
+            Ok(tag) if !tag.trim().is_empty() => tag.trim().to_string(),
+            _ => return,
+        };
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for element in selected_elements {
+            let tag = tag.to_owned();
+            let description = format!("hold {} {}", tag, element);
+            self.jobs.submit(command::pool_name(&element).to_string(), description, move |zfs| {
+                vec![zfs.zfs_hold(&tag, &element)]
+            });
+        }
+    }
+
+    // Offers the tags found on the selected snapshots and releases the
+    // checked ones wherever they are placed.
+    fn input_snapshot_release(&mut self, selected_elements: Vec<String>) {
+
+        let title = " Release Holds: ";
+
+        let held: Vec<String> = selected_elements.into_iter().filter(|element| self.selected_content().is_held(element)).collect();
+        let holds = match self.backend.zfs_holds(&held) {
+            Ok(holds) => holds,
+            Err(output) => {
+                self.message_dialog(title, output.error_message().as_str());
+                return;
+            },
+        };
+
+        let mut tags: Vec<String> = Vec::new();
+        for hold in holds.iter() {
+            if !tags.contains(&hold.tag) { tags.push(hold.tag.to_owned()); }
+        }
+
+        // The holds may have been released since the list was refreshed.
+        if tags.is_empty() {
+            self.message_dialog(title, "No holds found on the selected snapshot(s).");
+            return;
+        }
+
+        let items: Vec<String> = tags.iter().map(|tag| {
+            let count = holds.iter().filter(|hold| hold.tag == *tag).count();
+            format!("{} ({} snapshot(s))", tag, count)
+        }).collect();
+
+        let mut checked = vec![tags.len() == 1; tags.len()];
+        if self.check_dialog(title, &items, &mut checked).is_err() { return; }
+
+        self.selected_content_mut().selected_elements.clear();
+
+        for hold in holds.into_iter().filter(|hold| tags.iter().zip(checked.iter()).any(|(tag, checked)| *checked && *tag == hold.tag)) {
+            let description = format!("release {} {}", hold.tag, hold.snapshot);
+            self.jobs.submit(command::pool_name(&hold.snapshot).to_string(), description, move |zfs| {
+                vec![zfs.zfs_release(&hold.tag, &hold.snapshot)]
+            });
+        }
+    }
+
+    fn key_f11(&self) { 
+        // TODO
+    }
+
+    fn switch_window(&mut self) {
+
+        if self.left_content.is_selected {
+            self.left_content.is_selected = false;
+            self.right_content.is_selected = true;
+        } else {
+            self.left_content.is_selected = true;
+            self.right_content.is_selected = false;
+        }
+    }
+
+    fn switch_mode(&mut self) {
+
+        if self.left_content.is_selected {
+            self.left_content = Content::new(true, self.left_content.next());
+
+        } else {
+            self.right_content = Content::new(true, self.right_content.next());
+        }
+    }
+
+    // In linked mode the Snapshots (or Bookmarks) panel only lists the
+    // snapshots of the dataset or volume under the cursor in the other panel.
+    fn follow_link(&mut self) {
+
+        let (source, target) = match (&self.left_content.c_type, &self.right_content.c_type) {
+            (ContentType::Datasets, ContentType::Snapshots | ContentType::Bookmarks) |
+            (ContentType::Volumes, ContentType::Snapshots | ContentType::Bookmarks) => (&self.left_content, &mut self.right_content),
+            (ContentType::Snapshots | ContentType::Bookmarks, ContentType::Datasets) |
+            (ContentType::Snapshots | ContentType::Bookmarks, ContentType::Volumes) => (&self.right_content, &mut self.left_content),
+            _ => return,
+        };
+
+        let link = match (self.link, source.focused()) {
+            (Link::Dataset, Some(result)) => Some((result.name.to_owned(), false)),
+            (Link::Recursive, Some(result)) => Some((result.name.to_owned(), true)),
+            _ => None,
+        };
+
+        target.set_link(link);
+    }
+
+    fn scroll_window(content: &mut Content, height: i32) {
+
+        if content.position < content.start_from {
+            content.start_from = content.position
+
+        } else if content.position - content.start_from > (height as usize - BOTTOM_BORDER_SIZE - HEADER_SIZE - 1) {
+            content.start_from = content.position - (height as usize - BOTTOM_BORDER_SIZE - HEADER_SIZE - 1);
+        }
+
+        content.move_by(0);
+    }
+
+    fn test_windows(&self) {
+
+        let s = format!("  Left position: {} len: {}  ", self.left_content.position, self.left_content.command_result.len());
+        mvwaddstr(stdscr(), 1, 1, s.as_str());
+
+        let s = format!(" Right position: {} len: {}  ", self.right_content.position, self.right_content.command_result.len());
+        mvwaddstr(stdscr(), 2, 1, s.as_str());
+
+        let key = Screen::wait_key();
+        let s = format!(" Keystroke: 0x{:x}     ", key);
+        mvwaddstr(stdscr(), 3, 1, s.as_str());
+
+        Screen::wait_key();
+    }
+
+    fn wait_key() -> i32 {
+
+        loop {
+            let key = getch();
+            if key != ERR { return key }
+        }
+    }
+
+    fn draw(&mut self) {
+
+        getmaxyx(stdscr(), &mut self.max_y, &mut self.max_x);
+
+        self.draw_menu();
+        self.draw_content();
+    }
+
+    fn draw_content(&mut self) {
+
+        let panel_height = if self.show_jobs { self.max_y - JOBS_PANEL_HEIGHT } else { self.max_y };
+
+        let left_start_y = 0;
+        let left_start_x = 0;
+        let left_height = panel_height;
+        let left_width  = self.max_x/2;
+        let left_title = self.left_content.title();
+
+        let left_window = Screen::draw_window(left_height-1, left_width, left_start_y, left_start_x, left_title.as_str());
+        // self.left_content.update();
+        Screen::scroll_window(&mut self.left_content, left_height);
+        Screen::write_content(&self.left_content, left_window, left_height, left_width);
+
+        let right_start_x = left_width;
+        let right_start_y = 0;
+        let right_height = panel_height;
+        let right_width  = self.max_x - right_start_x;
+        let right_title = self.right_content.title();
+
+        let right_window = Screen::draw_window(right_height-1, right_width, right_start_y, right_start_x, right_title.as_str());
+        // self.right_content.update();
+        Screen::scroll_window(&mut self.right_content, right_height);
+        Screen::write_content(&self.right_content, right_window, right_height, right_width);
+
+        wrefresh(stdscr());
+        wrefresh(left_window);
+        wrefresh(right_window);
+
+        delwin(left_window);
+        delwin(right_window);
+
+        if self.show_jobs {
+            self.draw_jobs(panel_height - 1);
+        }
+    }
+
+    fn draw_jobs(&self, start_y: i32) {
+
+        let jobs = self.jobs.list();
+        let title = format!(" Jobs: {} active, {} total ", self.jobs.active(), jobs.len());
+
+        let window = Screen::draw_window(JOBS_PANEL_HEIGHT, self.max_x, start_y, 0, title.as_str());
+
+        for (i, job) in jobs.iter().rev().take(JOBS_PANEL_HEIGHT as usize - 2).enumerate() {
+
+            let seconds = job.duration().as_secs();
+            let mut line = format!("{:>4}  {:<7}  {:>3}:{:02}  {}", job.id, job.state.text(), seconds / 60, seconds % 60, job.description);
+
+            if let (JobState::Running, Some(progress)) = (job.state, &job.progress) {
+                let percent = progress.percent().map_or(String::new(), |percent| format!("{}% ", percent));
+                line.push_str(format!("  {}{} of {}", percent, column::format_size(progress.done()), column::format_size(progress.total())).as_str());
+            }
+
+            if let Some(failure) = job.failures().first() {
+                line.push_str(format!(": {}", failure.error_message().lines().next().unwrap_or("")).as_str());
+            }
+
+            if job.state == JobState::Failed { wattron(window, A_BOLD()); }
+            let text = Screen::fit_to_window(line.as_str(), self.max_x as usize);
+            mvwaddstr(window, i as i32 + 1, 1, text.as_str());
+            wattroff(window, A_BOLD());
+        }
+
+        wrefresh(window);
+        delwin(window);
+    }
+
+    fn draw_window(height: i32, width: i32, start_y: i32, start_x: i32, title: &str) -> WINDOW {
+
+        let win = newwin(height, width, start_y, start_x);
+
+        box_(win, 0, 0);
+        wmove(win, 0, 1);
+        waddstr(win, title);
+
+        win
+    }
+
+    fn draw_menu(&mut self) {
+
+        let pools_menu     = " 1 _____ 2 Creat 3 Props 4 _____ 5 _____ 6 _____ 7 Scrub 8 Destr 9 More  10 Exit ";
+        let datasets_menu  = " 1 _____ 2 Creat 3 Props 4 _____ 5 Snaps 6 Renam 7 _____ 8 Destr 9 More  10 Exit ";
+        let volumes_menu   = " 1 _____ 2 _____ 3 Props 4 _____ 5 Snaps 6 Renam 7 _____ 8 Destr 9 More  10 Exit ";
+        let snapshots_menu = " 1 _____ 2 _____ 3 Props 4 Send  5 Clone 6 Renam 7 RollB 8 Destr 9 More  10 Exit ";
+        let bookmarks_menu = " 1 _____ 2 _____ 3 Props 4 _____ 5 _____ 6 _____ 7 _____ 8 Destr 9 _____ 10 Exit ";
+
+        let mut selected_menu = match self.content_type() {
+            ContentType::Pools =>     { pools_menu.to_string() },
+            ContentType::Datasets =>  { datasets_menu.to_string() },
+            ContentType::Volumes =>   { volumes_menu.to_string() },
+            ContentType::Snapshots => { snapshots_menu.to_string() },
+            ContentType::Bookmarks => { bookmarks_menu.to_string() },
+        };
+
+        let active_jobs = self.jobs.active();
+        let jobs_status = if active_jobs > 0 { format!(" {} job(s) running ", active_jobs) } else { String::new() };
 
-        for _ in selected_menu.len()..self.max_x as usize {
-            selected_menu.push_str(" ");
+        for _ in selected_menu.len()..(self.max_x as usize).saturating_sub(jobs_status.len()) {
+            selected_menu.push(' ');
         }
+        selected_menu.push_str(jobs_status.as_str());
 
         wattron(stdscr(), A_BOLD());
-        mvwprintw(stdscr(), self.max_y-1, 0, selected_menu.as_str());
+        mvwaddstr(stdscr(), self.max_y-1, 0, selected_menu.as_str());
         wattroff(stdscr(), A_BOLD());
     }
 
@@ -502,145 +3093,447 @@ impl Screen {
         const TOP_CONTENT_Y: i32 = 1;
         const TOP_CONTENT_X: i32 = 1;
 
+        if let Some(error) = &content.error {
+            let text = Screen::fit_to_window(error.as_str(), width as usize);
+            mvwaddstr(window, TOP_CONTENT_Y, TOP_CONTENT_X, text.as_str());
+            return;
+        }
+
+        let (columns, name_width) = Screen::fit_columns(content.columns(), width as usize - 2);
+
+        let mut header = format!(" {}", Screen::fit_name("NAME", name_width));
+        for (column, column_width) in columns.iter() {
+            header.push_str(format!(" {}", Screen::align(column.header, column, *column_width)).as_str());
+        }
+
+        wattron(window, A_BOLD());
+        mvwaddstr(window, TOP_CONTENT_Y, TOP_CONTENT_X, header.as_str());
+        wattroff(window, A_BOLD());
+
         for (i, result_line) in content.command_result.iter().enumerate() {
 
             if i < content.start_from { continue }
-            if i >= height as usize + content.start_from - BOTTOM_BORDER_SIZE { break }
+            if i >= height as usize + content.start_from - BOTTOM_BORDER_SIZE - HEADER_SIZE { break }
             if i == content.position && content.is_selected { wattron(window, A_REVERSE()); }
 
-            let text = Screen::fit_to_window(result_line.name.as_str(), width as usize);
+            let is_tagged = content.is_tagged(result_line.name.as_str());
+            if is_tagged { wattron(window, A_BOLD()); }
+
+            let marker = if is_tagged { '*' }
+                else if content.has_resume_token(result_line.name.as_str()) { '!' }
+                else if hold::is_held(result_line.property("userrefs")) { 'H' }
+                else { ' ' };
+            let label = Screen::label_with_activity(content.labels[i].as_str(), result_line.property(column::ACTIVITY), name_width);
+            let mut line = format!("{}{}", marker, label);
+
+            for (column, column_width) in columns.iter() {
+                let value = column::format_value(result_line.property(column.property), column.format);
+                line.push_str(format!(" {}", Screen::align(value.as_str(), column, *column_width)).as_str());
+            }
 
-            let content_position = i as i32 - content.start_from as i32 + TOP_CONTENT_Y;
+            let content_position = i as i32 - content.start_from as i32 + TOP_CONTENT_Y + HEADER_SIZE as i32;
 
-            mvwprintw(window, content_position, TOP_CONTENT_X, text.as_str());
+            mvwaddstr(window, content_position, TOP_CONTENT_X, line.as_str());
             wattroff(window, A_REVERSE());
+            wattroff(window, A_BOLD());
+        }
+
+        if !content.selected_elements.is_empty() {
+            let summary = format!(" {}/{} tagged, {} ", content.selected_elements.len(), content.command_result.len(),
+                column::format_size(content.tagged_size()));
+            mvwaddstr(window, height - 2, 2, summary.as_str());
+        }
+    }
+
+    fn fit_columns(columns: Vec<&'static Column>, width: usize) -> (Vec<(&'static Column, usize)>, usize) {
+
+        const MIN_TEXT_WIDTH: usize = 8;
+
+        let mut columns: Vec<(&'static Column, usize)> = columns.into_iter().map(|column| (column, column.width)).collect();
+
+        loop {
+            let columns_width: usize = columns.iter().map(|(_, width)| width + 1).sum();
+
+            if columns.is_empty() || columns_width + MIN_NAME_WIDTH < width {
+                return (columns, width.saturating_sub(columns_width + 1));
+            }
+
+            let excess = columns_width + MIN_NAME_WIDTH + 1 - width;
+            let text_column = columns.iter_mut()
+                .find(|(column, width)| column.format == Format::Text && *width > MIN_TEXT_WIDTH);
+
+            match text_column {
+                Some((_, width)) => { *width -= excess.min(*width - MIN_TEXT_WIDTH); },
+                None => { columns.pop(); },
+            }
+        }
+    }
+
+    // A pool name with its running scrub or trim right-aligned in the name
+    // column. When they don't all fit, only the first one is shown, without
+    // the time left and the rate if need be.
+    fn label_with_activity(label: &str, activity: &str, width: usize) -> String {
+
+        if activity == "-" {
+            return Screen::fit_name(label, width);
+        }
+
+        let mut candidates = vec![activity.to_string()];
+        let mut words: Vec<&str> = activity.split(", ").next().unwrap_or(activity).split(' ').collect();
+
+        while words.len() >= 2 {
+            candidates.push(words.join(" "));
+            words.pop();
+        }
+
+        for activity in candidates {
+            let used = label.chars().count() + activity.chars().count() + 2;
+
+            if used <= width {
+                return format!("{}{}{} ", label, " ".repeat(width - used + 1), activity);
+            }
+        }
+
+        Screen::fit_name(label, width)
+    }
+
+    fn fit_name(name: &str, width: usize) -> String {
+
+        let chars: Vec<char> = name.chars().collect();
+
+        if chars.len() <= width {
+            return format!("{:<width$}", name, width = width);
+        }
+
+        if width < 3 {
+            return chars[..width].iter().collect();
+        }
+
+        let head = (width - 1) / 2;
+        let tail = width - 1 - head;
+
+        let mut fitted: String = chars[..head].iter().collect();
+        fitted.push('~');
+        fitted.extend(chars[chars.len() - tail..].iter());
+
+        fitted
+    }
+
+    fn align(value: &str, column: &Column, width: usize) -> String {
+
+        if column.format == Format::Text {
+            format!("{:<width$}", Screen::fit_name(value, width), width = width)
+        } else {
+            let value: String = value.chars().take(width).collect();
+            format!("{:>width$}", value, width = width)
         }
     }
 
     fn update_content(&mut self) {
-        self.left_content.update();
-        self.right_content.update();
+        self.left_content.update(self.backend.as_ref());
+        self.right_content.update(self.backend.as_ref());
     }
 
     fn fit_to_window(result_name: &str, width: usize) -> String {
 
-        let mut name = result_name.to_string();
+        let width = width.saturating_sub(2);
+        format!("{:<width$}", result_name.chars().take(width).collect::<String>(), width = width)
+    }
+
+    fn selected_content(&self) -> &Content {
+
+        if self.left_content.is_selected {
+            &self.left_content
+        } else {
+            &self.right_content
+        }
+    }
+
+    fn selected_content_mut(&mut self) -> &mut Content {
+
+        if self.left_content.is_selected {
+            &mut self.left_content
+        } else {
+            &mut self.right_content
+        }
+    }
+
+    fn selected_elements(&self) -> Vec<String> {
+
+        let content = self.selected_content();
+
+        if !content.selected_elements.is_empty() {
+            content.selected_elements.to_owned()
+        } else if let Some(result) = content.command_result.get(content.position) {
+            vec![result.name.to_owned()]
+        } else {
+            Vec::new()
+        }
+    }
+
+    fn input_dialog(&self, title: &str, prompt: &str, info: &str) -> Result<String,()> {
+
+        let dialog_height = 8;
+        let dialog_width = 70;
+
+        let start_y = self.max_y/2 - dialog_height/2;
+        let start_x = self.max_x/2 - dialog_width/2;
+
+        let footnote = "ESC Cancel     ENTER Confirm";
+
+        let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title);
+        mvwaddstr(dialog, 2, 3, prompt);
+        wattroff(dialog, A_REVERSE());
+
+        mvwaddstr(dialog, 5, 3, "----------------------------------------------------------------");
+        let foot_x = dialog_width/2 - footnote.len() as i32/2;
+        mvwaddstr(dialog, 6, foot_x, footnote);
+
+        wattron(dialog, A_REVERSE());
+        mvwaddstr(dialog, 3, 3, "                                                                ");
+        mvwaddstr(dialog, 3, 3, info);
+
+        wrefresh(dialog);
+
+        let mut input = String::from(info);
+        let mut _input_scr = String::new();
+
+        loop {
+            let key = getch();
+
+            match key {
+                ERR               => { continue },
+                Screen::KEY_ENTER => { delwin(dialog); return Ok(input.to_owned()) },
+                Screen::KEY_ESC   => { delwin(dialog); return Err(())   },
+                0x20..=0x7f       => { input.push(char::from_u32(key as u32).unwrap()); },
+                KEY_BACKSPACE     => { input.pop(); }
+                _                 => {},
+            }
+
+            let input_size = input.len();
+            _input_scr = input.clone();
+            for _ in input_size..64 {
+                _input_scr.push(' ');
+            }
+
+            mvwaddstr(dialog, 3, 3, _input_scr.as_str());
+            wrefresh(dialog);
+        }
+    }
+    
+    fn confirm_dialog(&self, title: &str, prompt: &str, info: &str) -> Result<(),()> {
+
+        let dialog_height = 8;
+        let dialog_width = 70;
+
+        let start_y = self.max_y/2 - dialog_height/2;
+        let start_x = self.max_x/2 - dialog_width/2;
+
+        let footnote = "ESC Cancel     ENTER Confirm";
+
+        let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title);
+        mvwaddstr(dialog, 2, 3, prompt);
+        mvwaddstr(dialog, 3, 3, info);
+
+        mvwaddstr(dialog, 5, 3, "----------------------------------------------------------------");
+        let foot_x = dialog_width/2 - footnote.len() as i32/2;
+        mvwaddstr(dialog, 6, foot_x, footnote);
+
+        wrefresh(dialog);
+
+        loop {
+            let key = getch();
+
+            if key == Screen::KEY_ENTER {
+                delwin(dialog);
+                return Ok(())
+            }
 
-        if name.len() > width-2 {
-            name = name.get(0..width-2).unwrap().to_string();
+            if key == Screen::KEY_ESC {
+                delwin(dialog);
+                return Err(())
+            }
         }
+    }
+
+    fn menu_dialog(&self, title: &str, items: &[String], current: usize) -> Result<usize,()> {
+
+        let visible = (self.max_y - 8).max(1) as usize;
+        let longest = items.iter().map(|item| item.len()).max().unwrap_or(0) as i32;
+
+        let dialog_height = items.len().min(visible) as i32 + 6;
+        let dialog_width = (longest + 8).clamp(40, (self.max_x - 4).max(40));
+        let item_width = dialog_width as usize - 8;
+
+        let start_y = self.max_y/2 - dialog_height/2;
+        let start_x = self.max_x/2 - dialog_width/2;
+
+        let footnote = "ESC Cancel     ENTER Select";
+
+        let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title);
+
+        mvwaddstr(dialog, dialog_height-3, 3, "-".repeat(dialog_width as usize - 6).as_str());
+        let foot_x = dialog_width/2 - footnote.len() as i32/2;
+        mvwaddstr(dialog, dialog_height-2, foot_x, footnote);
 
-        for _ in name.len()..width-2 {
-            name.push_str(" ");
-        }
+        let mut position = current.min(items.len().saturating_sub(1));
+        let mut start_from = 0;
 
-        format!("{}", name)
-    }
+        loop {
+            if position < start_from { start_from = position; }
+            if position >= start_from + visible { start_from = position + 1 - visible; }
 
-    fn selected_elements(&self) -> Vec<String> {
+            for (i, item) in items.iter().enumerate().skip(start_from).take(visible) {
+                let line = format!(" {:<width$}", Screen::fit_name(item, item_width), width = item_width);
 
-        if self.left_content.is_selected {
-            if self.left_content.selected_elements.len() > 0 {
-                self.left_content.selected_elements.to_owned()
-            } else {
-                vec![self.left_content.command_result[self.left_content.position].name.to_owned()]
+                if i == position { wattron(dialog, A_REVERSE()); }
+                mvwaddstr(dialog, 2 + (i - start_from) as i32, 2, line.as_str());
+                wattroff(dialog, A_REVERSE());
             }
 
-        } else {
-            if self.right_content.selected_elements.len() > 0 {
-                self.right_content.selected_elements.to_owned()
-            } else {
-                vec![self.right_content.command_result[self.right_content.position].name.to_owned()]
+            wrefresh(dialog);
+
+            match getch() {
+                Screen::KEY_ENTER => { delwin(dialog); return Ok(position) },
+                Screen::KEY_ESC   => { delwin(dialog); return Err(()) },
+                KEY_UP            => { position = position.saturating_sub(1); },
+                KEY_DOWN          => { position = (position + 1).min(items.len().saturating_sub(1)); },
+                KEY_HOME          => { position = 0; },
+                KEY_END           => { position = items.len().saturating_sub(1); },
+                _                 => {},
             }
         }
     }
 
-    fn input_dialog(&self, title: &str, prompt: &str, info: &str) -> Result<String,()> {
+    fn check_dialog(&self, title: &str, items: &[String], checked: &mut Vec<bool>) -> Result<(),()> {
 
-        let dialog_height = 8;
-        let dialog_width = 70;
+        if items.is_empty() { return Err(()) }
+
+        let visible = (self.max_y - 8).max(1) as usize;
+        let longest = items.iter().map(|item| item.len()).max().unwrap_or(0) as i32;
+
+        let dialog_height = items.len().min(visible) as i32 + 6;
+        let dialog_width = (longest + 10).clamp(40, (self.max_x - 4).max(40));
+        let item_width = dialog_width as usize - 10;
 
         let start_y = self.max_y/2 - dialog_height/2;
         let start_x = self.max_x/2 - dialog_width/2;
 
-        let footnote = "ESC Cancel     ENTER Confirm";
+        let footnote = "SPACE Toggle  ESC Cancel  ENTER Ok";
 
         let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title);
-        mvwprintw(dialog, 2, 3, prompt);
-        wattroff(dialog, A_REVERSE());
 
-        mvwprintw(dialog, 5, 3, "----------------------------------------------------------------");
+        mvwaddstr(dialog, dialog_height-3, 3, "-".repeat(dialog_width as usize - 6).as_str());
         let foot_x = dialog_width/2 - footnote.len() as i32/2;
-        mvwprintw(dialog, 6, foot_x, footnote);
+        mvwaddstr(dialog, dialog_height-2, foot_x, footnote);
 
-        wattron(dialog, A_REVERSE());
-        mvwprintw(dialog, 3, 3, "                                                                ");
-        mvwprintw(dialog, 3, 3, info);
+        let mut position = 0;
+        let mut start_from = 0;
+        let original = checked.clone();
 
-        wrefresh(dialog);
+        loop {
+            if position < start_from { start_from = position; }
+            if position >= start_from + visible { start_from = position + 1 - visible; }
 
-        let mut input = String::from(info);
-        let mut _input_scr = String::new();
+            for (i, item) in items.iter().enumerate().skip(start_from).take(visible) {
+                let mark = if checked[i] { 'x' } else { ' ' };
+                let line = format!("[{}] {}", mark, Screen::fit_name(item, item_width));
 
-        loop {
-            let key = getch();
+                if i == position { wattron(dialog, A_REVERSE()); }
+                mvwaddstr(dialog, 2 + (i - start_from) as i32, 3, line.as_str());
+                wattroff(dialog, A_REVERSE());
+            }
 
-            match key {
-                Screen::KEY_ENTER => { return Ok(input.to_owned()) },
-                Screen::KEY_ESC   => { return Err(())   },
-                0x20..=0x7f       => { input.push(char::from_u32(key as u32).unwrap()); },
-                KEY_BACKSPACE     => { input.pop(); }
+            wrefresh(dialog);
+
+            match getch() {
+                Screen::KEY_ENTER => { delwin(dialog); return Ok(()) },
+                Screen::KEY_ESC   => { *checked = original; delwin(dialog); return Err(()) },
+                KEY_UP            => { position = position.saturating_sub(1); },
+                KEY_DOWN          => { position = (position + 1).min(items.len().saturating_sub(1)); },
+                0x20              => { if let Some(checked) = checked.get_mut(position) { *checked = !*checked; } },
                 _                 => {},
             }
+        }
+    }
 
-            let input_size = input.len();
-            _input_scr = input.clone();
-            for _ in input_size..64 {
-                _input_scr.push(' ');
-            }
+    fn message_dialog(&self, title: &str, text: &str) {
+        self.text_dialog(title, text, "ENTER Close");
+    }
 
-            mvwprintw(dialog, 3, 3, _input_scr.as_str());
-            wrefresh(dialog);
+    fn confirm_text_dialog(&self, title: &str, text: &str) -> Result<(),()> {
+
+        match self.text_dialog(title, text, "ESC Cancel     ENTER Confirm") {
+            Screen::KEY_ENTER => Ok(()),
+            _ => Err(()),
         }
     }
-    
-    fn confirm_dialog(&self, title: &str, prompt: &str, info: &str) -> Result<(),()> {
 
-        let dialog_height = 8;
+    fn text_dialog(&self, title: &str, text: &str, footnote: &str) -> i32 {
+
         let dialog_width = 70;
+        let text_width = dialog_width as usize - 6;
+
+        let mut lines = Vec::new();
+        for line in text.lines() {
+            let mut line = line.to_string();
+            while let Some((split, _)) = line.char_indices().nth(text_width) {
+                let rest = line.split_off(split);
+                lines.push(line);
+                line = format!("  {}", rest);
+            }
+            lines.push(line);
+        }
+
+        // Text longer than the screen scrolls, so nothing a confirmation
+        // applies to is hidden.
+        let visible = (self.max_y - 7).max(1) as usize;
+        let last_start = lines.len().saturating_sub(visible);
+
+        let dialog_height = lines.len().min(visible) as i32 + 6;
 
         let start_y = self.max_y/2 - dialog_height/2;
         let start_x = self.max_x/2 - dialog_width/2;
 
-        let footnote = "ESC Cancel     ENTER Confirm";
-
         let dialog = Screen::draw_window(dialog_height, dialog_width, start_y, start_x, title);
-        mvwprintw(dialog, 2, 3, prompt);
-        mvwprintw(dialog, 3, 3, info);
 
-        mvwprintw(dialog, 5, 3, "----------------------------------------------------------------");
         let foot_x = dialog_width/2 - footnote.len() as i32/2;
-        mvwprintw(dialog, 6, foot_x, footnote);
+        mvwaddstr(dialog, dialog_height-2, foot_x, footnote);
 
-        wrefresh(dialog);
+        let mut start_from = 0;
 
         loop {
-            let key = getch();
-
-            if key == Screen::KEY_ENTER {
-                return Ok(())
+            for row in 0..lines.len().min(visible) {
+                let line = lines.get(start_from + row).map_or("", |line| line.as_str());
+                mvwaddstr(dialog, 2 + row as i32, 3, format!("{:<width$}", line, width = text_width).as_str());
             }
 
-            if key == Screen::KEY_ESC {
-                return Err(())
+            let position = if last_start == 0 { String::new() } else {
+                format!(" lines {}-{} of {} ", start_from + 1, (start_from + visible).min(lines.len()), lines.len())
+            };
+            mvwaddstr(dialog, dialog_height-3, 3, format!("{:-<width$}", position, width = text_width).as_str());
+
+            wrefresh(dialog);
+
+            match getch() {
+                key @ (Screen::KEY_ENTER | Screen::KEY_ESC) => { delwin(dialog); return key },
+                KEY_UP          => { start_from = start_from.saturating_sub(1); },
+                KEY_DOWN        => { start_from = (start_from + 1).min(last_start); },
+                KEY_PPAGE       => { start_from = start_from.saturating_sub(visible); },
+                KEY_NPAGE       => { start_from = (start_from + visible).min(last_start); },
+                KEY_HOME        => { start_from = 0; },
+                KEY_END         => { start_from = last_start; },
+                _               => {},
             }
         }
     }
 
-    fn seleted_string(&self, selected_elements: &Vec<String>) -> String {
+    fn seleted_string(&self, selected_elements: &[String]) -> String {
 
-        if selected_elements.len() == 1 {
-            selected_elements.get(0).unwrap().to_string()
+        if selected_elements.is_empty() {
+            String::new()
+        } else if selected_elements.len() == 1 {
+            selected_elements[0].to_string()
         } else {
             format!("{} elements", selected_elements.len())
         }
@@ -655,12 +3548,31 @@ impl Drop for Screen {
     }
 }
 
+#[derive(Clone, Copy)]
+enum Link {
+    Off,
+    Dataset,
+    Recursive,
+}
+
+impl Link {
+
+    pub fn next(&self) -> Link {
+        match self {
+            Link::Off       => Link::Dataset,
+            Link::Dataset   => Link::Recursive,
+            Link::Recursive => Link::Off,
+        }
+    }
+}
+
 #[allow(dead_code)]
 enum ContentType {
     Datasets,
     Pools,
     Volumes,
     Snapshots,
+    Bookmarks,
 }
 
 impl ContentType {
@@ -671,6 +3583,24 @@ impl ContentType {
             ContentType::Datasets => " Datasets: ".to_string(),
             ContentType::Volumes => " Volumes: ".to_string(),
             ContentType::Snapshots => " Snapshots: ".to_string(),
+            ContentType::Bookmarks => " Bookmarks: ".to_string(),
+        }
+    }
+
+    pub fn columns(&self) -> &'static [Column] {
+        match self {
+            ContentType::Pools => column::POOL_COLUMNS,
+            ContentType::Datasets => column::DATASET_COLUMNS,
+            ContentType::Volumes => column::VOLUME_COLUMNS,
+            ContentType::Snapshots => column::SNAPSHOT_COLUMNS,
+            ContentType::Bookmarks => column::BOOKMARK_COLUMNS,
+        }
+    }
+
+    pub fn size_property(&self) -> &'static str {
+        match self {
+            ContentType::Pools => "size",
+            _ => "used",
         }
     }
 }
@@ -681,33 +3611,478 @@ struct Content {
     start_from: usize,
     position: usize,
     c_type: ContentType,
+    all_results: Vec<command::CommandResult>,
     command_result: Vec<command::CommandResult>,
+    labels: Vec<String>,
     selected_elements: Vec<String>,
+    filter: Option<(String, Filter)>,
+    tree_mode: bool,
+    collapsed: HashSet<String>,
+    link: Option<(String, bool)>,
+    visible_columns: Vec<&'static str>,
+    sort_by: Option<&'static str>,
+    sort_descending: bool,
+    error: Option<String>,
 }
 
 impl Content {
 
     pub fn new(is_selected: bool, c_type: ContentType) -> Content {
 
+        let visible_columns = c_type.columns().iter()
+            .filter(|column| column.visible)
+            .map(|column| column.property)
+            .collect();
+
         Content {
-            is_selected: is_selected,
+            is_selected,
             start_from: 0,
             position: 0,
-            c_type: c_type,
+            c_type,
+            all_results: Vec::new(),
             command_result: Vec::new(),
+            labels: Vec::new(),
             selected_elements: Vec::new(),
+            filter: None,
+            tree_mode: false,
+            collapsed: HashSet::new(),
+            link: None,
+            visible_columns,
+            sort_by: None,
+            sort_descending: false,
+            error: None,
         }
     }
 
-    pub fn update(&mut self) {
+    pub fn update(&mut self, backend: &dyn Backend) {
 
-        match self.c_type {
-            ContentType::Pools     => { self.command_result = command::zfs_pools(); },
-            ContentType::Datasets  => { self.command_result = command::zfs_dataset(); },
-            ContentType::Volumes   => { self.command_result = command::zfs_volumes(); },
-            ContentType::Snapshots => { self.command_result = command::zfs_snapshots(); },
+        let result = match self.c_type {
+            ContentType::Pools     => { backend.zfs_pools() },
+            ContentType::Datasets  => { backend.zfs_dataset() },
+            ContentType::Volumes   => { backend.zfs_volumes() },
+            ContentType::Snapshots => { backend.zfs_snapshots() },
+            ContentType::Bookmarks => { backend.zfs_bookmarks() },
+        };
+
+        match result {
+            Ok(mut all_results) => {
+                if let ContentType::Pools = self.c_type {
+                    Content::add_activities(&mut all_results, backend);
+                }
+                if let ContentType::Snapshots = self.c_type {
+                    Content::add_holds(&mut all_results, backend);
+                }
+                self.all_results = all_results;
+                self.error = None;
+            },
+            Err(output) => {
+                self.all_results = Vec::new();
+                self.error = Some(output.error_message());
+            },
+        }
+
+        self.apply_view();
+    }
+
+    // The running scrubs, resilvers, trims and initializes, shown next to
+    // the pool names.
+    fn add_activities(pools: &mut [command::CommandResult], backend: &dyn Backend) {
+
+        let statuses = match backend.zpool_status_all() {
+            Ok(statuses) => statuses,
+            Err(_) => return,
+        };
+
+        for pool in pools.iter_mut() {
+            let summaries: Vec<String> = statuses.iter()
+                .filter(|status| status.name == pool.name)
+                .flat_map(|status| status.activities())
+                .map(|activity| activity.summary())
+                .collect();
+
+            if !summaries.is_empty() {
+                pool.properties.insert(column::ACTIVITY.to_string(), summaries.join(", "));
+            }
+        }
+    }
+
+    // The tags of the user holds, for the snapshots that have any.
+    fn add_holds(snapshots: &mut [command::CommandResult], backend: &dyn Backend) {
+
+        let held: Vec<String> = snapshots.iter()
+            .filter(|snapshot| hold::is_held(snapshot.property("userrefs")))
+            .map(|snapshot| snapshot.name.to_owned())
+            .collect();
+
+        if held.is_empty() { return }
+
+        let holds = match backend.zfs_holds(&held) {
+            Ok(holds) => holds,
+            Err(_) => return,
+        };
+
+        for snapshot in snapshots.iter_mut().filter(|snapshot| held.contains(&snapshot.name)) {
+            let tags = hold::tags(&holds, snapshot.name.as_str());
+            snapshot.properties.insert(column::HOLDS.to_string(), tags.join(","));
+        }
+    }
+
+    pub fn apply_view(&mut self) {
+
+        let current = self.command_result.get(self.position).map(|result| result.name.to_owned());
+
+        self.command_result = self.all_results.iter()
+            .filter(|result| self.is_linked(result.name.as_str()))
+            .filter(|result| match &self.filter {
+                Some((_, filter)) => filter.matches(result.name.as_str()),
+                None => true,
+            })
+            .cloned()
+            .collect();
+
+        self.sort();
+
+        if self.tree_mode {
+            self.build_tree();
+        } else {
+            self.labels = self.command_result.iter().map(|result| result.name.to_owned()).collect();
+        }
+
+        let command_result = &self.command_result;
+        self.selected_elements.retain(|element| command_result.iter().any(|result| result.name == *element));
+
+        if let Some(name) = current {
+            self.move_to_name(name.as_str());
+        }
+
+        self.move_by(0);
+    }
+
+    fn build_tree(&mut self) {
+
+        let names: HashSet<&str> = self.command_result.iter().map(|result| result.name.as_str()).collect();
+
+        let mut children: HashMap<&str, Vec<usize>> = HashMap::new();
+        let mut roots = Vec::new();
+
+        for (i, result) in self.command_result.iter().enumerate() {
+            match parent_name(result.name.as_str()) {
+                Some(parent) if names.contains(parent) => { children.entry(parent).or_default().push(i); },
+                _ => { roots.push(i); },
+            }
+        }
+
+        let mut order: Vec<(usize, usize)> = Vec::new();
+        let mut stack: Vec<(usize, usize)> = roots.into_iter().rev().map(|i| (i, 0)).collect();
+
+        while let Some((i, depth)) = stack.pop() {
+            order.push((i, depth));
+
+            let name = self.command_result[i].name.as_str();
+            if self.collapsed.contains(name) { continue }
+
+            if let Some(nodes) = children.get(name) {
+                stack.extend(nodes.iter().rev().map(|child| (*child, depth + 1)));
+            }
+        }
+
+        let mut subtree: HashMap<String, u64> = HashMap::new();
+        for result in self.all_results.iter() {
+            let referenced = result.property("referenced").parse::<u64>().unwrap_or(0);
+
+            let mut name = result.name.as_str();
+            loop {
+                *subtree.entry(name.to_string()).or_insert(0) += referenced;
+                match parent_name(name) {
+                    Some(parent) => name = parent,
+                    None => break,
+                }
+            }
+        }
+
+        let mut rows = Vec::new();
+        let mut labels = Vec::new();
+
+        for (i, depth) in order {
+            let mut result = self.command_result[i].clone();
+
+            let name = result.name.as_str();
+            let marker = match children.get(name) {
+                Some(_) if self.collapsed.contains(name) => "+ ",
+                Some(_) => "- ",
+                None => "  ",
+            };
+            let short_name = if depth == 0 { name } else { name.rsplit('/').next().unwrap_or(name) };
+            labels.push(format!("{}{}{}", "  ".repeat(depth), marker, short_name));
+
+            if let Some(total) = subtree.get(name) {
+                result.properties.insert(column::SUBTREE.to_string(), total.to_string());
+            }
+            rows.push(result);
+        }
+
+        self.command_result = rows;
+        self.labels = labels;
+    }
+
+    pub fn toggle_tree(&mut self) {
+
+        if let ContentType::Datasets = self.c_type {
+            self.tree_mode = !self.tree_mode;
+            self.apply_view();
+        }
+    }
+
+    pub fn toggle_collapse(&mut self) {
+
+        if !self.tree_mode { return }
+
+        let name = match self.focused() {
+            Some(result) => result.name.to_owned(),
+            None => return,
+        };
+
+        if !self.collapsed.remove(&name) {
+            let prefix = format!("{}/", name);
+            if self.all_results.iter().any(|result| result.name.starts_with(&prefix)) {
+                self.collapsed.insert(name);
+            }
+        }
+
+        self.apply_view();
+    }
+
+    pub fn focused(&self) -> Option<&command::CommandResult> {
+        self.command_result.get(self.position)
+    }
+
+    fn is_linked(&self, name: &str) -> bool {
+
+        let (dataset, recursive) = match &self.link {
+            Some(link) => link,
+            None => return true,
+        };
+
+        let snapshot_dataset = name.split(['@', '#']).next().unwrap_or(name);
+        if snapshot_dataset == dataset {
+            return true;
+        }
+
+        *recursive && snapshot_dataset.starts_with(dataset.as_str()) && snapshot_dataset[dataset.len()..].starts_with('/')
+    }
+
+    pub fn set_link(&mut self, link: Option<(String, bool)>) {
+
+        if self.link != link {
+            self.link = link;
+            self.start_from = 0;
+            self.position = 0;
+            self.apply_view();
+        }
+    }
+
+    pub fn set_filter(&mut self, expression: &str) -> Result<(), String> {
+
+        self.filter = if expression.is_empty() {
+            None
+        } else {
+            Some((expression.to_string(), Filter::parse(expression)?))
+        };
+
+        self.apply_view();
+        Ok(())
+    }
+
+    pub fn search(&mut self, text: &str, skip_current: bool, forward: bool) -> bool {
+
+        let text = text.to_lowercase();
+        let len = self.command_result.len();
+
+        if len == 0 { return false; }
+
+        let start = match (skip_current, forward) {
+            (false, _)    => self.position,
+            (true, true)  => self.position + 1,
+            (true, false) => self.position + len - 1,
+        };
+
+        for i in 0..len {
+            let position = if forward { (start + i) % len } else { (start + len - i % len) % len };
+
+            if self.command_result[position].name.to_lowercase().contains(text.as_str()) {
+                self.position = position;
+                return true;
+            }
+        }
+
+        false
+    }
+
+    pub fn title(&self) -> String {
+
+        let mut title = self.c_type.text();
+
+        if self.tree_mode {
+            title = format!("{}tree ", title);
+        }
+
+        if let Some((dataset, recursive)) = &self.link {
+            title = format!("{}of {}{} ", title, dataset, if *recursive { " -r" } else { "" });
+        }
+
+        if let Some((expression, _)) = &self.filter {
+            title = format!("{}[{}] ", title, expression);
+        }
+
+        match self.sort_by {
+            Some(property) => {
+                let header = self.c_type.columns().iter()
+                    .find(|column| column.property == property)
+                    .map(|column| column.header)
+                    .unwrap_or("NAME");
+                let order = if self.sort_descending { "desc" } else { "asc" };
+
+                format!("{}by {} {} ", title, header, order)
+            },
+            None => title,
+        }
+    }
+
+    fn sort(&mut self) {
+
+        let property = match self.sort_by {
+            Some(property) => property,
+            None => return,
+        };
+
+        let format = self.c_type.columns().iter()
+            .find(|column| column.property == property)
+            .map(|column| column.format)
+            .unwrap_or(Format::Text);
+        let descending = self.sort_descending;
+
+        self.command_result.sort_by(|a, b| {
+            let ordering = if property == "name" {
+                a.name.cmp(&b.name)
+            } else if format == Format::Text {
+                a.property(property).cmp(b.property(property))
+            } else {
+                compare_numbers(a.property(property), b.property(property))
+            };
+
+            if descending { ordering.reverse() } else { ordering }
+        });
+    }
+
+    pub fn reverse_sort(&mut self) {
+
+        if self.sort_by.is_none() {
+            self.sort_by = Some("name");
+        } else {
+            self.sort_descending = !self.sort_descending;
+        }
+
+        self.apply_view();
+    }
+
+    pub fn move_to_name(&mut self, name: &str) {
+
+        if let Some(position) = self.command_result.iter().position(|result| result.name == name) {
+            self.position = position;
+        }
+    }
+
+    pub fn columns(&self) -> Vec<&'static Column> {
+
+        self.c_type.columns().iter()
+            .filter(|column| self.visible_columns.contains(&column.property))
+            .filter(|column| self.tree_mode || column.property != column::SUBTREE)
+            .collect()
+    }
+
+    pub fn is_tagged(&self, name: &str) -> bool {
+        self.selected_elements.iter().any(|element| element == name)
+    }
+
+    pub fn has_resume_token(&self, name: &str) -> bool {
+        self.all_results.iter()
+            .find(|result| result.name == name)
+            .is_some_and(|result| result.property(column::RESUME_TOKEN) != "-")
+    }
+
+    pub fn is_held(&self, name: &str) -> bool {
+        self.all_results.iter()
+            .find(|result| result.name == name)
+            .is_some_and(|result| hold::is_held(result.property("userrefs")))
+    }
+
+    pub fn has_origin(&self, name: &str) -> bool {
+        dependency::origin(&self.all_results, name).is_some()
+    }
+
+    pub fn toggle_tag(&mut self) {
+
+        let name = match self.command_result.get(self.position) {
+            Some(result) => result.name.to_owned(),
+            None => return,
+        };
+
+        if self.is_tagged(name.as_str()) {
+            self.selected_elements.retain(|element| *element != name);
+        } else {
+            self.selected_elements.push(name);
+        }
+
+        self.move_by(1);
+    }
+
+    pub fn tag_matching(&mut self, pattern: &str, tag: bool) {
+
+        let matching: Vec<String> = self.command_result.iter()
+            .filter(|result| pattern::glob_match(pattern, result.name.as_str()))
+            .map(|result| result.name.to_owned())
+            .collect();
+
+        if tag {
+            for name in matching {
+                if !self.is_tagged(name.as_str()) {
+                    self.selected_elements.push(name);
+                }
+            }
+        } else {
+            self.selected_elements.retain(|element| !matching.contains(element));
         }
-    } 
+    }
+
+    pub fn invert_tags(&mut self) {
+
+        let inverted: Vec<String> = self.command_result.iter()
+            .filter(|result| !self.is_tagged(result.name.as_str()))
+            .map(|result| result.name.to_owned())
+            .collect();
+
+        self.selected_elements = inverted;
+    }
+
+    pub fn tagged_size(&self) -> u64 {
+
+        self.command_result.iter()
+            .filter(|result| self.is_tagged(result.name.as_str()))
+            .map(|result| result.property(self.c_type.size_property()).parse::<u64>().unwrap_or(0))
+            .sum()
+    }
+
+    pub fn move_to(&mut self, position: usize) {
+
+        let last = self.command_result.len().saturating_sub(1);
+        self.position = position.min(last);
+    }
+
+    pub fn move_by(&mut self, offset: isize) {
+
+        let position = self.position as isize + offset;
+        self.move_to(position.max(0) as usize);
+    }
 
     pub fn next(&mut self) -> ContentType {
 
@@ -715,8 +4090,28 @@ impl Content {
             ContentType::Pools     => { ContentType::Datasets },
             ContentType::Datasets  => { ContentType::Volumes },
             ContentType::Volumes   => { ContentType::Snapshots },
-            ContentType::Snapshots => { ContentType::Pools },
+            ContentType::Snapshots => { ContentType::Bookmarks },
+            ContentType::Bookmarks => { ContentType::Pools },
         }
 
     }
-}
\ No newline at end of file
+}
+
+fn compare_numbers(a: &str, b: &str) -> Ordering {
+
+    match (a.parse::<f64>(), b.parse::<f64>()) {
+        (Ok(a), Ok(b)) => a.partial_cmp(&b).unwrap_or(Ordering::Equal),
+        (Ok(_), Err(_)) => Ordering::Greater,
+        (Err(_), Ok(_)) => Ordering::Less,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+fn parent_name(name: &str) -> Option<&str> {
+    name.rfind('/').map(|i| &name[..i])
+}
+
+// The last component of a device path, e.g. "ata-WDC_WD40EFRX-68N32N0_WD-WCC7K3NX4C1D-part1".
+fn device_label(path: &str) -> &str {
+    path.rsplit('/').next().unwrap_or(path)
+}
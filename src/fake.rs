@@ -0,0 +1,3097 @@
+
+use std::collections::BTreeMap;
+use std::sync::Mutex;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use crate::backend::{Backend, ListResult, PropertyResult, StatusResult};
+use crate::column;
+use crate::command::{pool_name, CommandOutput, CommandResult};
+use crate::diff::{self, Change};
+use crate::files::{self, Entry};
+use crate::hold::Hold;
+use crate::iostat::{self, Monitor};
+use crate::job::Progress;
+use crate::pool::{self, BlockDevice, Control, CreateOptions, ImportOptions};
+use crate::property::{self, Property};
+use crate::replication::{SendOptions, Target};
+use crate::retention;
+use crate::status;
+
+const KB: u64 = 1024;
+const MB: u64 = 1024 * KB;
+const GB: u64 = 1024 * MB;
+const TB: u64 = 1024 * GB;
+
+const LATENCY: Duration = Duration::from_millis(400);
+
+const SCRUB_DURATION: u64 = 180;
+const TRIM_DURATION: u64 = 120;
+const INITIALIZE_DURATION: u64 = 300;
+
+const TRANSFER_STEPS: u64 = 40;
+const TRANSFER_STEP: Duration = Duration::from_millis(150);
+
+const HOUR: u64 = 3600;
+const DAY: u64 = 24 * HOUR;
+
+// The made-up contents of every filesystem and snapshot, relative to the
+// mountpoint; which files each of them holds depends on its name.
+const DIRECTORIES: &[&str] = &["", "documents", "documents/taxes", "photos/2024", "projects/zc/src", ".config/zc", "music/Live Sessions"];
+const FILES: &[&str] = &["notes.txt", "report.pdf", "IMG_0412.jpg", "main.rs", "settings.toml", "backup.tar.gz", "track 01.flac", ".cache"];
+
+const POOL_DEFAULTS: &[(&str, &str)] = &[
+    ("ashift", "0"), ("autoexpand", "off"), ("autoreplace", "off"), ("autotrim", "off"), ("cachefile", "-"),
+    ("comment", "-"), ("delegation", "on"), ("failmode", "wait"), ("listsnapshots", "off"), ("multihost", "off"),
+];
+
+const FILESYSTEM_DEFAULTS: &[(&str, &str)] = &[
+    ("aclinherit", "restricted"), ("acltype", "off"), ("atime", "on"), ("canmount", "on"), ("checksum", "on"),
+    ("compression", "off"), ("copies", "1"), ("dedup", "off"), ("devices", "on"), ("exec", "on"),
+    ("logbias", "latency"), ("mountpoint", ""), ("primarycache", "all"), ("quota", "0"), ("readonly", "off"),
+    ("recordsize", "131072"), ("refquota", "0"), ("refreservation", "0"), ("relatime", "off"), ("reservation", "0"),
+    ("secondarycache", "all"), ("setuid", "on"), ("snapdir", "hidden"), ("sync", "standard"), ("xattr", "on"),
+];
+
+const VOLUME_DEFAULTS: &[(&str, &str)] = &[
+    ("checksum", "on"), ("compression", "off"), ("copies", "1"), ("dedup", "off"), ("logbias", "latency"),
+    ("primarycache", "all"), ("readonly", "off"), ("refreservation", "0"), ("reservation", "0"),
+    ("secondarycache", "all"), ("snapdev", "hidden"), ("sync", "standard"), ("volmode", "default"), ("volsize", ""),
+];
+
+const RESERVED_POOL_NAMES: &[&str] = &["mirror", "raidz", "draid", "spare"];
+
+const MIN_DEVICE_SIZE: u64 = 64 * MB;
+
+const NOT_INHERITED: &[&str] = &["canmount", "quota", "refquota", "refreservation", "reservation", "volsize"];
+
+#[derive(Clone, Copy, PartialEq)]
+enum Kind {
+    Filesystem,
+    Volume,
+    Snapshot,
+}
+
+impl Kind {
+
+    // The type as `zfs list -t` takes it.
+    fn name(self) -> &'static str {
+
+        match self {
+            Kind::Filesystem => "filesystem",
+            Kind::Volume => "volume",
+            Kind::Snapshot => "snapshot",
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq)]
+enum Class {
+    Data,
+    Log,
+    Cache,
+    Spare,
+}
+
+// A scrub, trim or initialize. Time spent paused does not count, so resuming
+// moves the start forward by the length of the pause.
+#[derive(Clone, Copy)]
+struct Task {
+    started: u64,
+    paused: Option<u64>,
+    canceled: Option<u64>,
+}
+
+impl Task {
+
+    fn start() -> Task {
+        Task { started: now(), paused: None, canceled: None }
+    }
+
+    fn elapsed(&self) -> u64 {
+        self.canceled.or(self.paused).unwrap_or_else(now).saturating_sub(self.started)
+    }
+
+    fn is_active(&self, duration: u64) -> bool {
+        self.canceled.is_none() && self.elapsed() < duration
+    }
+
+    fn resume(&mut self) {
+        if let Some(paused) = self.paused.take() {
+            self.started += now().saturating_sub(paused);
+        }
+    }
+}
+
+#[derive(Clone)]
+struct Device {
+    path: String,
+    state: String,
+    read: u64,
+    write: u64,
+    cksum: u64,
+    note: String,
+    trim: Option<Task>,
+    initialize: Option<Task>,
+}
+
+impl Device {
+
+    fn task_mut(&mut self, operation: &str) -> &mut Option<Task> {
+        if operation == "trim" { &mut self.trim } else { &mut self.initialize }
+    }
+}
+
+#[derive(Clone)]
+struct Vdev {
+    class: Class,
+    kind: String,
+    devices: Vec<Device>,
+}
+
+#[derive(Clone)]
+struct Pool {
+    id: u64,
+    name: String,
+    size: u64,
+    scrub: Option<Task>,
+    properties: BTreeMap<String, String>,
+    vdevs: Vec<Vdev>,
+    error_files: Vec<String>,
+}
+
+impl Pool {
+
+    fn new(name: &str, size: u64, vdevs: Vec<Vdev>) -> Pool {
+
+        // The guid only has to tell apart pools sharing a name.
+        let nanos = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_nanos()).unwrap_or(0);
+
+        Pool {
+            id: hash(format!("{}:{}", name, nanos).as_str()),
+            name: name.to_string(),
+            size,
+            scrub: None,
+            properties: BTreeMap::new(),
+            vdevs,
+            error_files: Vec::new(),
+        }
+    }
+}
+
+struct Dataset {
+    kind: Kind,
+    used: u64,
+    creation: u64,
+    origin: Option<String>,
+    properties: BTreeMap<String, String>,
+    resume: Option<(String, String)>,
+    holds: BTreeMap<String, u64>,
+}
+
+// A bookmark keeps the creation time and guid of the snapshot it was made
+// from, which is all an incremental send needs from its source.
+struct Bookmark {
+    snapshot: String,
+    creation: u64,
+    guid: u64,
+}
+
+// A pool that is no longer imported, with its datasets, as `zpool import`
+// (or `zpool import -D` once destroyed) still finds it on its devices.
+struct Exported {
+    pool: Pool,
+    datasets: Vec<(String, Dataset)>,
+    destroyed: bool,
+}
+
+struct State {
+    pools: Vec<Pool>,
+    exported: Vec<Exported>,
+    datasets: BTreeMap<String, Dataset>,
+    bookmarks: BTreeMap<String, Bookmark>,
+    devices: BTreeMap<String, u64>,
+    restored: BTreeMap<String, Entry>,
+}
+
+pub struct Fake {
+    state: Mutex<State>,
+}
+
+impl Fake {
+
+    pub fn new() -> Fake {
+
+        let now = now();
+
+        let mut state = State {
+            pools: Vec::new(),
+            exported: Vec::new(),
+            datasets: BTreeMap::new(),
+            bookmarks: BTreeMap::new(),
+            devices: BTreeMap::new(),
+            restored: BTreeMap::new(),
+        };
+
+        for serial in ["WSD0A1B2", "WSD0C3D4", "WSD0E5F6", "WSD0G7H8"].iter() {
+            state.devices.insert(format!("/dev/disk/by-id/ata-ST8000VN004-2M2101_{}", serial), 8 * TB);
+        }
+        for serial in ["23124K800123", "23124K800456"].iter() {
+            state.devices.insert(format!("/dev/disk/by-id/nvme-WD_BLACK_SN850X_1000GB_{}", serial), 1000 * GB);
+        }
+
+        state.add_pool("tank", 4 * TB, now - 90 * DAY, vec![
+            vdev(Class::Data, "mirror", &[disk("WCC7K1AL2P3V"), disk("WCC7K5HJ9E7A")]),
+            vdev(Class::Data, "mirror", &[disk("WCC7K3NX4C1D"), disk("WCC7K0TR8Z6Q")]),
+            vdev(Class::Log, "mirror", &[nvme("S4EWNX0N401237", 1), nvme("S4EWNX0N401882", 1)]),
+            vdev(Class::Cache, "disk", &[nvme("S4EWNX0N401237", 2)]),
+            vdev(Class::Spare, "disk", &[disk("WCC7K6DF2M8S")]),
+        ]);
+        state.add_pool("backup", 2 * TB, now - 60 * DAY, vec![
+            vdev(Class::Data, "raidz1", &[disk("WX21DA0KL7H2"), disk("WX21DA0KN3F5"), disk("WX21DA0KP9C8")]),
+        ]);
+
+        let faulted = &mut state.pools[0].vdevs[1].devices[0];
+        faulted.state = "FAULTED".to_string();
+        faulted.read = 12;
+        faulted.cksum = 45;
+        faulted.note = "too many errors".to_string();
+
+        state.pools[1].vdevs[0].devices[1].cksum = 2;
+        state.pools[1].error_files.push("/backup/tank/photos/2019/IMG_0042.jpg".to_string());
+        state.pools[1].scrub = Some(Task { started: now - SCRUB_DURATION / 4, paused: None, canceled: None });
+
+        state.add_dataset("tank/home", Kind::Filesystem, 210 * GB, now - 90 * DAY);
+        state.add_dataset("tank/home/alice", Kind::Filesystem, 120 * GB, now - 80 * DAY);
+        state.add_dataset("tank/home/bob", Kind::Filesystem, 85 * GB, now - 80 * DAY);
+        state.add_dataset("tank/media", Kind::Filesystem, 1700 * GB, now - 70 * DAY);
+        state.add_dataset("tank/vm", Kind::Filesystem, 300 * GB, now - 50 * DAY);
+        state.add_dataset("tank/vm/debian", Kind::Volume, 40 * GB, now - 50 * DAY);
+        state.add_dataset("tank/vm/windows", Kind::Volume, 120 * GB, now - 45 * DAY);
+        state.add_dataset("backup/tank", Kind::Filesystem, 900 * GB, now - 60 * DAY);
+
+        for dataset in ["tank/home", "tank/home/alice", "tank/home/bob", "tank/media"].iter() {
+            for day in 1..=7 {
+                let name = format!("{}@daily-{}", dataset, day);
+                let used = (day * 37 + dataset.len() as u64 * 13) % 97 * 11 * MB;
+                state.add_dataset(name.as_str(), Kind::Snapshot, used, now - (8 - day) * DAY);
+            }
+        }
+
+        for hour in 1..=12 {
+            let name = format!("tank/home/alice@hourly-{}", hour);
+            state.add_dataset(name.as_str(), Kind::Snapshot, hour * 3 * MB, now - (13 - hour) * HOUR);
+        }
+
+        state.add_dataset("tank/vm/debian@installed", Kind::Snapshot, 2 * GB, now - 49 * DAY);
+        state.add_dataset("tank/vm/windows@installed", Kind::Snapshot, 6 * GB, now - 44 * DAY);
+        state.datasets.get_mut("tank/vm/debian@installed").unwrap().holds.insert("keep".to_string(), now - 49 * DAY);
+        state.datasets.get_mut("tank/media@daily-7").unwrap().holds.insert("backup".to_string(), now - DAY);
+        let _ = state.bookmark("tank/media@daily-1", "tank/media#daily-1");
+        let _ = state.bookmark("tank/home@daily-1", "tank/home#daily-1");
+        state.datasets.remove("tank/media@daily-1");
+
+        state.set_local("tank", "compression", "lz4");
+        state.set_local("tank/home/alice", "quota", (200 * GB).to_string().as_str());
+        state.set_local("tank/media", "recordsize", MB.to_string().as_str());
+        state.set_local("tank/media", "atime", "off");
+        state.set_local("backup/tank", "readonly", "on");
+        state.pools[0].properties.insert("autotrim".to_string(), "on".to_string());
+
+        state.add_pool("archive", 2 * TB, now - 400 * DAY, vec![
+            vdev(Class::Data, "mirror", &[disk("WMAZA1234567"), disk("WMAZA7654321")]),
+        ]);
+        state.add_dataset("archive/2019", Kind::Filesystem, 640 * GB, now - 400 * DAY);
+        state.add_dataset("archive/2020", Kind::Filesystem, 710 * GB, now - 300 * DAY);
+        state.add_dataset("archive/2020@final", Kind::Snapshot, 0, now - 200 * DAY);
+        let _ = state.pool_export("archive");
+
+        state.add_pool("scratch", 1000 * GB, now - 20 * DAY, vec![
+            vdev(Class::Data, "disk", &["/dev/disk/by-id/nvme-WD_BLACK_SN850X_1000GB_23124K800456".to_string()]),
+        ]);
+        state.add_dataset("scratch/build", Kind::Filesystem, 85 * GB, now - 20 * DAY);
+        let _ = state.pool_destroy("scratch");
+
+        state.add_dataset("backup/media", Kind::Filesystem, 420 * GB, now - 2 * HOUR);
+        state.datasets.get_mut("backup/media").unwrap().resume = Some((resume_token("tank/media@daily-7"), "tank/media@daily-7".to_string()));
+
+        Fake {
+            state: Mutex::new(state),
+        }
+    }
+
+    fn list(&self, kind: Option<Kind>) -> ListResult {
+
+        let state = self.state.lock().unwrap();
+
+        let mut result = Vec::new();
+
+        let mut datasets: Vec<(&String, &Dataset)> = state.datasets.iter()
+            .filter(|(_, dataset)| match kind {
+                Some(kind) => dataset.kind == kind,
+                None => dataset.kind != Kind::Snapshot,
+            })
+            .collect();
+
+        datasets.sort_by(|(a, a_dataset), (b, b_dataset)| {
+            dataset_part(a).cmp(dataset_part(b)).then(a_dataset.creation.cmp(&b_dataset.creation))
+        });
+
+        for (name, dataset) in datasets {
+            result.push(state.dataset_properties(name, dataset));
+        }
+
+        Ok(result)
+    }
+}
+
+impl Backend for Fake {
+
+    fn zfs_pools(&self) -> ListResult {
+
+        let state = self.state.lock().unwrap();
+
+        let result = state.pools.iter().map(|pool| state.pool_properties(pool)).collect();
+
+        Ok(result)
+    }
+
+    fn zfs_dataset(&self) -> ListResult {
+        self.list(None)
+    }
+
+    fn zfs_volumes(&self) -> ListResult {
+        self.list(Some(Kind::Volume))
+    }
+
+    fn zfs_snapshots(&self) -> ListResult {
+        self.list(Some(Kind::Snapshot))
+    }
+
+    fn zfs_bookmarks(&self) -> ListResult {
+
+        let state = self.state.lock().unwrap();
+
+        let result = state.bookmarks.iter()
+            .filter(|(name, _)| state.datasets.contains_key(bookmark_dataset(name)))
+            .map(|(name, bookmark)| state.bookmark_properties(name, bookmark))
+            .collect();
+
+        Ok(result)
+    }
+
+    fn zfs_list_properties(&self, types: &str, properties: &[String]) -> ListResult {
+
+        let state = self.state.lock().unwrap();
+        let types: Vec<&str> = types.split(',').collect();
+
+        let result = state.datasets.iter()
+            .filter(|(_, dataset)| types.contains(&dataset.kind.name()))
+            .filter_map(|(name, _)| {
+                let all = state.get_all(name).ok()?;
+                let mut result = CommandResult::new(name.to_owned());
+                for property in properties {
+                    let value = all.iter().find(|known| known.name == *property).map_or("-", |known| known.value.as_str());
+                    result.properties.insert(property.to_owned(), value.to_string());
+                }
+                Some(result)
+            })
+            .collect();
+
+        Ok(result)
+    }
+
+    fn zfs_get_all(&self, dataset_name: &str) -> PropertyResult {
+
+        let result = self.state.lock().unwrap().get_all(dataset_name);
+        result.map_err(|error| CommandOutput::simulated("zfs", &["get", "all", "-H", "-p", dataset_name], Err(error)))
+    }
+
+    fn zfs_diff(&self, snapshot_name: &str, target: Option<&str>) -> Result<Vec<Change>, CommandOutput> {
+
+        thread::sleep(LATENCY);
+
+        let arguments = diff::arguments(snapshot_name, target);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        match self.state.lock().unwrap().diff_text(snapshot_name, target) {
+            Ok(text) => Ok(diff::parse_diff(text.as_str())),
+            Err(error) => Err(CommandOutput::simulated("zfs", &arguments, Err(error))),
+        }
+    }
+
+    fn zfs_holds(&self, snapshot_names: &[String]) -> Result<Vec<Hold>, CommandOutput> {
+
+        let mut arguments = vec!["holds", "-H", "-p"];
+        arguments.extend(snapshot_names.iter().map(|name| name.as_str()));
+
+        let result = self.state.lock().unwrap().holds(snapshot_names);
+        result.map_err(|error| CommandOutput::simulated("zfs", &arguments, Err(error)))
+    }
+
+    fn list_directory(&self, path: &str) -> Result<Vec<Entry>, CommandOutput> {
+
+        let result = self.state.lock().unwrap().directory(path);
+        result.map_err(|error| CommandOutput::simulated("ls", &[path], Err(error)))
+    }
+
+    fn zpool_status(&self, pool_name: &str) -> StatusResult {
+
+        let result = self.state.lock().unwrap().pool_status(pool_name);
+        match result {
+            Ok(text) => Ok(status::parse_status(text.as_str())),
+            Err(error) => Err(CommandOutput::simulated("zpool", &["status", "-P", "-t", "-i", pool_name], Err(error))),
+        }
+    }
+
+    fn zpool_status_all(&self) -> Result<Vec<status::PoolStatus>, CommandOutput> {
+
+        let state = self.state.lock().unwrap();
+        let text: Vec<String> = state.pools.iter().filter_map(|pool| state.pool_status(pool.name.as_str()).ok()).collect();
+        Ok(status::parse_pools(text.join("\n").as_str()))
+    }
+
+    fn zpool_get_all(&self, pool_name: &str) -> PropertyResult {
+
+        let result = self.state.lock().unwrap().pool_get_all(pool_name);
+        result.map_err(|error| CommandOutput::simulated("zpool", &["get", "all", "-H", "-p", pool_name], Err(error)))
+    }
+
+    fn zpool_devices(&self) -> Result<Vec<BlockDevice>, CommandOutput> {
+        Ok(self.state.lock().unwrap().free_devices())
+    }
+
+    fn zpool_iostat(&self, pool_names: &[String], monitor: &Monitor) -> CommandOutput {
+
+        let mut arguments = vec!["iostat", "-Hpvy", "-l", "-q"];
+        arguments.extend(pool_names.iter().map(|pool_name| pool_name.as_str()));
+
+        let mut sample = 0;
+
+        while !monitor.is_stopped() {
+            let result = self.state.lock().unwrap().iostat_text(pool_names, sample);
+
+            match result {
+                Ok(text) => {
+                    let mut pool = String::new();
+                    for line in text.lines() {
+                        if let Some(stat) = iostat::parse_line(line, &mut pool) { monitor.update(stat) }
+                    }
+                },
+                Err(error) => return CommandOutput::simulated("zpool", &arguments, Err(error)),
+            }
+
+            for _ in 0..iostat::INTERVAL * 10 {
+                if monitor.is_stopped() { break }
+                thread::sleep(Duration::from_millis(100));
+            }
+
+            sample += 1;
+        }
+
+        CommandOutput::simulated("zpool", &arguments, Ok(String::new()))
+    }
+
+    fn zpool_importable(&self, directories: &[String], destroyed: bool) -> Result<Vec<status::PoolStatus>, CommandOutput> {
+
+        thread::sleep(LATENCY);
+        let text = self.state.lock().unwrap().import_text(directories, destroyed);
+        Ok(status::parse_pools(text.as_str()))
+    }
+
+    fn zfs_create(&self, dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().create(dataset_name);
+        CommandOutput::simulated("zfs", &["create", dataset_name], result)
+    }
+
+    fn zfs_rename(&self, old_dataset_name: &str, new_dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().rename(old_dataset_name, new_dataset_name);
+        CommandOutput::simulated("zfs", &["rename", old_dataset_name, new_dataset_name], result)
+    }
+
+    fn zfs_clone(&self, snapshot_name: &str, new_dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().clone(snapshot_name, new_dataset_name);
+        CommandOutput::simulated("zfs", &["clone", snapshot_name, new_dataset_name], result)
+    }
+
+    fn zfs_snapshot(&self, snapshot_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().snapshot(snapshot_name);
+        CommandOutput::simulated("zfs", &["snapshot", snapshot_name], result)
+    }
+
+    fn zfs_snapshot_all(&self, snapshot_names: &[String]) -> CommandOutput {
+
+        let mut arguments = vec!["snapshot"];
+        arguments.extend(snapshot_names.iter().map(|name| name.as_str()));
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().snapshot_all(snapshot_names);
+        CommandOutput::simulated("zfs", &arguments, result)
+    }
+
+    fn zfs_bookmark(&self, snapshot_name: &str, bookmark_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().bookmark(snapshot_name, bookmark_name);
+        CommandOutput::simulated("zfs", &["bookmark", snapshot_name, bookmark_name], result)
+    }
+
+    fn zfs_destroy(&self, dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().destroy(dataset_name);
+        CommandOutput::simulated("zfs", &["destroy", dataset_name], result)
+    }
+
+    fn zfs_destroy_estimate(&self, snapshot_names: &[String]) -> Result<u64, CommandOutput> {
+
+        let arguments = retention::estimate_arguments(snapshot_names);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        let result = self.state.lock().unwrap().destroy_size(snapshot_names);
+        result.map_err(|error| CommandOutput::simulated("zfs", &arguments, Err(error)))
+    }
+
+    fn zfs_rollback(&self, snapshot_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().rollback(snapshot_name);
+        CommandOutput::simulated("zfs", &["rollback", "-rf", snapshot_name], result)
+    }
+
+    fn zfs_hold(&self, tag: &str, snapshot_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().hold(tag, snapshot_name);
+        CommandOutput::simulated("zfs", &["hold", tag, snapshot_name], result)
+    }
+
+    fn zfs_release(&self, tag: &str, snapshot_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().release(tag, snapshot_name);
+        CommandOutput::simulated("zfs", &["release", tag, snapshot_name], result)
+    }
+
+    fn zfs_promote(&self, dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().promote(dataset_name);
+        CommandOutput::simulated("zfs", &["promote", dataset_name], result)
+    }
+
+    fn zfs_set(&self, dataset_name: &str, property: &str, value: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().set(dataset_name, property, value);
+        let assignment = format!("{}={}", property, value);
+        CommandOutput::simulated("zfs", &["set", assignment.as_str(), dataset_name], result)
+    }
+
+    fn zfs_inherit(&self, dataset_name: &str, property: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().inherit(dataset_name, property);
+        CommandOutput::simulated("zfs", &["inherit", property, dataset_name], result)
+    }
+
+    fn zfs_send_estimate(&self, send: &SendOptions) -> Result<u64, CommandOutput> {
+
+        let arguments = send.estimate_arguments();
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        let result = self.state.lock().unwrap().stream_size(send);
+        result.map_err(|error| CommandOutput::simulated("zfs", &arguments, Err(error)))
+    }
+
+    fn zfs_send(&self, send: &SendOptions, progress: &Progress) -> CommandOutput {
+
+        let command = match &send.target {
+            Target::Dataset(_) => format!("{} | zfs {}", send.send_arguments().join(" "), send.receive_arguments().join(" ")),
+            Target::File(path) => format!("{} > {}", send.send_arguments().join(" "), path),
+        };
+
+        thread::sleep(LATENCY);
+        let size = self.state.lock().unwrap().receive(send, false);
+
+        let result = match size {
+            Ok(size) => {
+                for _ in 0..TRANSFER_STEPS {
+                    thread::sleep(TRANSFER_STEP);
+                    progress.add(size / TRANSFER_STEPS);
+                }
+                self.state.lock().unwrap().receive(send, true).map(|_| String::new())
+            },
+            Err(error) => Err(error),
+        };
+
+        CommandOutput::simulated("zfs", &[command.as_str()], result)
+    }
+
+    fn zfs_receive_abort(&self, dataset_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().receive_abort(dataset_name);
+        CommandOutput::simulated("zfs", &["receive", "-A", dataset_name], result)
+    }
+
+    fn restore_files(&self, sources: &[String], directory: &str) -> CommandOutput {
+
+        let arguments = files::restore_arguments(sources, directory);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().restore(sources, directory);
+        CommandOutput::simulated("cp", &arguments, result)
+    }
+
+    fn zpool_create(&self, create: &CreateOptions, dry_run: bool) -> CommandOutput {
+
+        if !dry_run { thread::sleep(LATENCY); }
+        let result = self.state.lock().unwrap().pool_create(create, dry_run);
+
+        let arguments = create.arguments(dry_run);
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+        CommandOutput::simulated("zpool", &arguments, result)
+    }
+
+    fn zpool_file_vdev(&self, path: &str, size: u64) -> CommandOutput {
+
+        let result = self.state.lock().unwrap().file_vdev(path, size);
+        CommandOutput::simulated("truncate", &["-s", size.to_string().as_str(), path], result)
+    }
+
+    fn zpool_import(&self, import: &ImportOptions) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_import(import);
+
+        let arguments = import.arguments();
+        let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+        CommandOutput::simulated("zpool", &arguments, result)
+    }
+
+    fn zpool_export(&self, pool_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_export(pool_name);
+        CommandOutput::simulated("zpool", &["export", pool_name], result)
+    }
+
+    fn zpool_destroy(&self, pool_name: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_destroy(pool_name);
+        CommandOutput::simulated("zpool", &["destroy", pool_name], result)
+    }
+
+    fn zpool_scrub(&self, pool_name: &str, control: Control) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_scrub(pool_name, control);
+        simulated_control("scrub", pool_name, control, result)
+    }
+
+    fn zpool_trim(&self, pool_name: &str, control: Control) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_device_task(pool_name, "trim", control);
+        simulated_control("trim", pool_name, control, result)
+    }
+
+    fn zpool_initialize(&self, pool_name: &str, control: Control) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_device_task(pool_name, "initialize", control);
+        simulated_control("initialize", pool_name, control, result)
+    }
+
+    fn zpool_set(&self, pool_name: &str, property: &str, value: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_set(pool_name, property, value);
+        let assignment = format!("{}={}", property, value);
+        CommandOutput::simulated("zpool", &["set", assignment.as_str(), pool_name], result)
+    }
+
+    fn zpool_offline(&self, pool_name: &str, device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_offline(pool_name, device);
+        CommandOutput::simulated("zpool", &["offline", pool_name, device], result)
+    }
+
+    fn zpool_online(&self, pool_name: &str, device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_online(pool_name, device);
+        CommandOutput::simulated("zpool", &["online", pool_name, device], result)
+    }
+
+    fn zpool_attach(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_attach(pool_name, device, new_device);
+        CommandOutput::simulated("zpool", &["attach", pool_name, device, new_device], result)
+    }
+
+    fn zpool_detach(&self, pool_name: &str, device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_detach(pool_name, device);
+        CommandOutput::simulated("zpool", &["detach", pool_name, device], result)
+    }
+
+    fn zpool_replace(&self, pool_name: &str, device: &str, new_device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_replace(pool_name, device, new_device);
+        CommandOutput::simulated("zpool", &["replace", pool_name, device, new_device], result)
+    }
+
+    fn zpool_add(&self, pool_name: &str, vdevs: &[String], dry_run: bool) -> CommandOutput {
+
+        if !dry_run { thread::sleep(LATENCY); }
+        let result = self.state.lock().unwrap().pool_add(pool_name, vdevs, dry_run);
+
+        let mut arguments = vec!["add"];
+        if dry_run { arguments.push("-n"); }
+        arguments.push(pool_name);
+        arguments.extend(vdevs.iter().map(|vdev| vdev.as_str()));
+
+        CommandOutput::simulated("zpool", &arguments, result)
+    }
+
+    fn zpool_remove(&self, pool_name: &str, device: &str) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_remove(pool_name, device);
+        CommandOutput::simulated("zpool", &["remove", pool_name, device], result)
+    }
+
+    fn zpool_clear(&self, pool_name: &str, device: Option<&str>) -> CommandOutput {
+
+        thread::sleep(LATENCY);
+        let result = self.state.lock().unwrap().pool_clear(pool_name, device);
+
+        match device {
+            Some(device) => CommandOutput::simulated("zpool", &["clear", pool_name, device], result),
+            None => CommandOutput::simulated("zpool", &["clear", pool_name], result),
+        }
+    }
+}
+
+impl State {
+
+    fn add_pool(&mut self, name: &str, size: u64, creation: u64, vdevs: Vec<Vdev>) {
+
+        self.pools.push(Pool::new(name, size, vdevs));
+        self.add_dataset(name, Kind::Filesystem, 96 * KB, creation);
+    }
+
+    fn add_dataset(&mut self, name: &str, kind: Kind, used: u64, creation: u64) {
+
+        self.datasets.insert(name.to_string(), Dataset {
+            kind,
+            used,
+            creation,
+            origin: None,
+            properties: BTreeMap::new(),
+            resume: None,
+            holds: BTreeMap::new(),
+        });
+    }
+
+    fn set_local(&mut self, name: &str, property: &str, value: &str) {
+
+        if let Some(dataset) = self.datasets.get_mut(name) {
+            dataset.properties.insert(property.to_string(), value.to_string());
+        }
+    }
+
+    fn allocated(&self, pool_name: &str) -> u64 {
+
+        let prefix = format!("{}/", pool_name);
+        let snapshots = format!("{}@", pool_name);
+
+        self.datasets.iter()
+            .filter(|(name, _)| *name == pool_name || name.starts_with(&prefix) || name.starts_with(&snapshots))
+            .map(|(_, dataset)| dataset.used)
+            .sum()
+    }
+
+    fn pool_properties(&self, pool: &Pool) -> CommandResult {
+
+        let allocated = self.allocated(pool.name.as_str()).min(pool.size);
+
+        let mut result = CommandResult::new(pool.name.to_owned());
+        let properties = &mut result.properties;
+
+        properties.insert("size".to_string(), pool.size.to_string());
+        properties.insert("allocated".to_string(), allocated.to_string());
+        properties.insert("free".to_string(), (pool.size - allocated).to_string());
+        properties.insert("fragmentation".to_string(), (allocated * 30 / pool.size).to_string());
+        properties.insert("capacity".to_string(), (allocated * 100 / pool.size).to_string());
+        properties.insert("dedupratio".to_string(), "1.00".to_string());
+        properties.insert("health".to_string(), pool_state(pool).to_string());
+
+        result
+    }
+
+    fn dataset_properties(&self, name: &str, dataset: &Dataset) -> CommandResult {
+
+        let pool_name = pool_name(name);
+        let available = match self.pools.iter().find(|pool| pool.name == pool_name) {
+            Some(pool) => pool.size.saturating_sub(self.allocated(pool_name)),
+            None => 0,
+        };
+
+        let referenced = match dataset.kind {
+            Kind::Snapshot => dataset.used * 4 + 64 * MB,
+            _ => dataset.used * 3 / 4,
+        };
+
+        let ratio = 100 + name.bytes().map(|b| b as u64).sum::<u64>() % 120;
+
+        let mut result = CommandResult::new(name.to_string());
+        let properties = &mut result.properties;
+
+        properties.insert("used".to_string(), dataset.used.to_string());
+        properties.insert("referenced".to_string(), referenced.to_string());
+        properties.insert("compressratio".to_string(), format!("{}.{:02}", ratio / 100, ratio % 100));
+        properties.insert("creation".to_string(), dataset.creation.to_string());
+
+        match dataset.kind {
+            Kind::Filesystem => {
+                properties.insert("available".to_string(), available.to_string());
+                properties.insert("mountpoint".to_string(), format!("/{}", name));
+                properties.insert("volsize".to_string(), "-".to_string());
+            },
+            Kind::Volume => {
+                properties.insert("available".to_string(), available.to_string());
+                properties.insert("mountpoint".to_string(), "-".to_string());
+                properties.insert("volsize".to_string(), (dataset.used / GB * GB + 8 * GB).to_string());
+            },
+            Kind::Snapshot => {
+                let clones = self.clones_of(name);
+                properties.insert("written".to_string(), (dataset.used * 2).to_string());
+                properties.insert("userrefs".to_string(), dataset.holds.len().to_string());
+                properties.insert("guid".to_string(), snapshot_guid(name).to_string());
+                properties.insert("clones".to_string(), if clones.is_empty() { "-".to_string() } else { clones.join(",") });
+            },
+        }
+
+        if dataset.kind != Kind::Snapshot {
+            let token = dataset.resume.as_ref().map_or("-", |(token, _)| token.as_str());
+            properties.insert("receive_resume_token".to_string(), token.to_string());
+            properties.insert("origin".to_string(), dataset.origin.as_deref().unwrap_or("-").to_string());
+        }
+
+        result
+    }
+
+    fn defaults(kind: Kind) -> &'static [(&'static str, &'static str)] {
+        match kind {
+            Kind::Filesystem => FILESYSTEM_DEFAULTS,
+            Kind::Volume => VOLUME_DEFAULTS,
+            Kind::Snapshot => &[],
+        }
+    }
+
+    // Resolves a settable property the way zfs does: a local value, else the
+    // closest ancestor's local value, else the default.
+    fn resolve(&self, name: &str, property: &str, default: &str) -> (String, String) {
+
+        let dataset = &self.datasets[name];
+        if let Some(value) = dataset.properties.get(property) {
+            return (value.to_owned(), "local".to_string());
+        }
+
+        if !NOT_INHERITED.contains(&property) {
+            let mut ancestor = parent_name(name);
+            while let Some(parent) = ancestor {
+                if let Some(value) = self.datasets.get(parent).and_then(|dataset| dataset.properties.get(property)) {
+                    let value = if property == "mountpoint" { format!("{}{}", value, &name[parent.len()..]) } else { value.to_owned() };
+                    return (value, format!("inherited from {}", parent));
+                }
+                ancestor = parent_name(parent);
+            }
+        }
+
+        match property {
+            "mountpoint" => (format!("/{}", name), "default".to_string()),
+            "volsize" => (self.dataset_properties(name, dataset).property("volsize").to_string(), "local".to_string()),
+            _ => (default.to_string(), "default".to_string()),
+        }
+    }
+
+    fn get_all(&self, dataset_name: &str) -> Result<Vec<Property>, String> {
+
+        if let Some(bookmark) = self.bookmarks.get(dataset_name) {
+            let mut properties = vec![Property::new("type", "bookmark", "-")];
+            let mut readonly: Vec<(String, String)> = self.bookmark_properties(dataset_name, bookmark).properties.into_iter().collect();
+            readonly.sort();
+            properties.extend(readonly.iter().map(|(property, value)| Property::new(property, value, "-")));
+            return Ok(properties);
+        }
+
+        let dataset = match self.datasets.get(dataset_name) {
+            Some(dataset) => dataset,
+            None => return Err(format!("cannot open '{}': dataset does not exist", dataset_name)),
+        };
+
+        let defaults = State::defaults(dataset.kind);
+        let mut properties = vec![Property::new("type", dataset.kind.name(), "-")];
+
+        let mut readonly: Vec<(String, String)> = self.dataset_properties(dataset_name, dataset).properties.into_iter()
+            .filter(|(property, value)| value != "-" && !defaults.iter().any(|(name, _)| name == property))
+            .collect();
+        readonly.sort();
+        properties.extend(readonly.iter().map(|(property, value)| Property::new(property, value, "-")));
+
+        for (property, default) in defaults {
+            let (value, source) = self.resolve(dataset_name, property, default);
+            properties.push(Property::new(property, value.as_str(), source.as_str()));
+        }
+
+        let mut user_properties: Vec<&String> = Vec::new();
+        let mut ancestor = Some(dataset_name);
+        while let Some(name) = ancestor {
+            if let Some(dataset) = self.datasets.get(name) {
+                user_properties.extend(dataset.properties.keys().filter(|property| property.contains(':')));
+            }
+            ancestor = parent_name(name);
+        }
+        user_properties.sort();
+        user_properties.dedup();
+
+        for property in user_properties {
+            let (value, source) = self.resolve(dataset_name, property, "-");
+            properties.push(Property::new(property, value.as_str(), source.as_str()));
+        }
+
+        Ok(properties)
+    }
+
+    fn check_property(&self, name: &str, property: &str, value: Option<&str>) -> Result<Kind, String> {
+
+        let action = if value.is_some() { "cannot set property for" } else { "cannot inherit property for" };
+
+        let kind = match self.datasets.get(name) {
+            Some(dataset) => dataset.kind,
+            None => return Err(format!("cannot open '{}': dataset does not exist", name)),
+        };
+
+        if property.contains(':') {
+            return Ok(kind);
+        }
+
+        if !State::defaults(kind).iter().any(|(settable, _)| *settable == property) {
+            let readonly = self.get_all(name)?.iter().any(|known| known.name == property);
+            if readonly {
+                return Err(format!("{} '{}': '{}' is readonly", action, name, property));
+            }
+            return Err(format!("{} '{}': invalid property '{}'", action, name, property));
+        }
+
+        if let Some(value) = value {
+            property::validate(property, value, false).map_err(|error| format!("{} '{}': {}", action, name, error))?;
+        }
+
+        Ok(kind)
+    }
+
+    fn set(&mut self, dataset_name: &str, property: &str, value: &str) -> Result<String, String> {
+
+        self.check_property(dataset_name, property, Some(value))?;
+
+        let value = match property::parse_size(value) {
+            Some(size) if value.chars().any(|c| c.is_ascii_alphabetic()) => size.to_string(),
+            _ => value.to_string(),
+        };
+
+        self.set_local(dataset_name, property, value.as_str());
+        Ok(String::new())
+    }
+
+    fn inherit(&mut self, dataset_name: &str, property: &str) -> Result<String, String> {
+
+        self.check_property(dataset_name, property, None)?;
+
+        if NOT_INHERITED.contains(&property) {
+            return Err(format!("'{}' property cannot be inherited", property));
+        }
+
+        if let Some(dataset) = self.datasets.get_mut(dataset_name) {
+            dataset.properties.remove(property);
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_get_all(&self, pool_name: &str) -> Result<Vec<Property>, String> {
+
+        let pool = match self.pools.iter().find(|pool| pool.name == pool_name) {
+            Some(pool) => pool,
+            None => return Err(format!("cannot open '{}': no such pool", pool_name)),
+        };
+
+        let mut readonly: Vec<(String, String)> = self.pool_properties(pool).properties.into_iter().collect();
+        readonly.sort();
+
+        let mut properties: Vec<Property> = readonly.iter().map(|(property, value)| Property::new(property, value, "-")).collect();
+
+        for (property, default) in POOL_DEFAULTS {
+            match pool.properties.get(*property) {
+                Some(value) => properties.push(Property::new(property, value, "local")),
+                None => properties.push(Property::new(property, default, "default")),
+            }
+        }
+
+        // Only set when importing.
+        for (property, default) in [("altroot", "-"), ("readonly", "off")].iter() {
+            match pool.properties.get(*property) {
+                Some(value) => properties.push(Property::new(property, value, "local")),
+                None => properties.push(Property::new(property, default, "default")),
+            }
+        }
+
+        Ok(properties)
+    }
+
+    fn pool_set(&mut self, pool_name: &str, property: &str, value: &str) -> Result<String, String> {
+
+        if !self.has_pool(pool_name) {
+            return Err(format!("cannot open '{}': no such pool", pool_name));
+        }
+
+        if !POOL_DEFAULTS.iter().any(|(settable, _)| *settable == property) {
+            let readonly = self.pool_get_all(pool_name)?.iter().any(|known| known.name == property);
+            if readonly {
+                return Err(format!("cannot set property for '{}': property '{}' is readonly", pool_name, property));
+            }
+            return Err(format!("cannot set property for '{}': invalid property '{}'", pool_name, property));
+        }
+
+        property::validate(property, value, true).map_err(|error| format!("cannot set property for '{}': {}", pool_name, error))?;
+
+        let pool = self.pools.iter_mut().find(|pool| pool.name == pool_name).unwrap();
+        pool.properties.insert(property.to_string(), value.to_string());
+
+        Ok(String::new())
+    }
+
+    fn has_pool(&self, name: &str) -> bool {
+        self.pools.iter().any(|pool| pool.name == name)
+    }
+
+    fn children(&self, name: &str) -> Vec<String> {
+
+        let prefix = format!("{}/", name);
+        let snapshots = format!("{}@", name);
+
+        self.datasets.keys()
+            .filter(|child| child.starts_with(&prefix) || child.starts_with(&snapshots))
+            .cloned()
+            .collect()
+    }
+
+    fn clones_of(&self, snapshot_name: &str) -> Vec<String> {
+
+        self.datasets.iter()
+            .filter(|(_, dataset)| dataset.origin.as_deref() == Some(snapshot_name))
+            .map(|(name, _)| name.to_owned())
+            .collect()
+    }
+
+    fn check_new_name(&self, name: &str, action: &str) -> Result<(), String> {
+
+        if let Some(c) = name.chars().find(|c| !c.is_ascii_alphanumeric() && !"_-.:/@ ".contains(*c)) {
+            return Err(format!("cannot {} '{}': invalid character '{}' in name", action, name, c));
+        }
+
+        if name.contains(' ') || name.contains("//") || name.ends_with('/') || name.ends_with('@') {
+            return Err(format!("cannot {} '{}': invalid dataset name", action, name));
+        }
+
+        if self.datasets.contains_key(name) {
+            return Err(format!("cannot {} '{}': dataset already exists", action, name));
+        }
+
+        let pool = pool_name(name);
+        if !self.has_pool(pool) {
+            return Err(format!("cannot {} '{}': no such pool '{}'", action, name, pool));
+        }
+
+        Ok(())
+    }
+
+    fn create(&mut self, dataset_name: &str) -> Result<String, String> {
+
+        if dataset_name.contains('@') {
+            return Err(format!("cannot create '{}': snapshot delimiter '@' is not expected here", dataset_name));
+        }
+
+        let parent = match parent_name(dataset_name) {
+            Some(parent) => parent,
+            None => return Err(format!("cannot create '{}': missing dataset name", dataset_name)),
+        };
+
+        self.check_new_name(dataset_name, "create")?;
+
+        match self.datasets.get(parent) {
+            Some(dataset) if dataset.kind == Kind::Filesystem => {},
+            Some(_) => return Err(format!("cannot create '{}': parent is not a filesystem", dataset_name)),
+            None => return Err(format!("cannot create '{}': parent does not exist", dataset_name)),
+        }
+
+        self.add_dataset(dataset_name, Kind::Filesystem, 96 * KB, now());
+        Ok(String::new())
+    }
+
+    fn rename(&mut self, old_dataset_name: &str, new_dataset_name: &str) -> Result<String, String> {
+
+        if !self.datasets.contains_key(old_dataset_name) {
+            return Err(format!("cannot open '{}': dataset does not exist", old_dataset_name));
+        }
+
+        if old_dataset_name == new_dataset_name {
+            return Ok(String::new());
+        }
+
+        if pool_name(old_dataset_name) != pool_name(new_dataset_name) {
+            return Err(format!("cannot rename to '{}': datasets must be within same pool", new_dataset_name));
+        }
+
+        let old_is_snapshot = old_dataset_name.contains('@');
+        if old_is_snapshot != new_dataset_name.contains('@') ||
+           (old_is_snapshot && dataset_part(old_dataset_name) != dataset_part(new_dataset_name)) {
+            return Err(format!("cannot rename to '{}': snapshots must be part of same dataset", new_dataset_name));
+        }
+
+        self.check_new_name(new_dataset_name, "rename to")?;
+
+        if !old_is_snapshot {
+            match parent_name(new_dataset_name) {
+                Some(parent) if self.datasets.contains_key(parent) => {},
+                _ => return Err(format!("cannot rename to '{}': parent does not exist", new_dataset_name)),
+            }
+
+            if new_dataset_name.starts_with(format!("{}/", old_dataset_name).as_str()) {
+                return Err(format!("cannot rename to '{}': New dataset name cannot be a descendant of current dataset name", new_dataset_name));
+            }
+        }
+
+        let mut renamed = vec![old_dataset_name.to_string()];
+        if !old_is_snapshot {
+            renamed.extend(self.children(old_dataset_name));
+        }
+
+        for name in renamed {
+            let new_name = format!("{}{}", new_dataset_name, &name[old_dataset_name.len()..]);
+            let dataset = self.datasets.remove(&name).unwrap();
+            self.datasets.insert(new_name.to_owned(), dataset);
+
+            for dataset in self.datasets.values_mut() {
+                if dataset.origin.as_deref() == Some(name.as_str()) {
+                    dataset.origin = Some(new_name.to_owned());
+                }
+            }
+
+            let bookmarks: Vec<String> = self.bookmarks.keys().filter(|bookmark| bookmark_dataset(bookmark) == name).cloned().collect();
+            for bookmark in bookmarks {
+                let new_bookmark = format!("{}{}", new_name, &bookmark[name.len()..]);
+                let value = self.bookmarks.remove(&bookmark).unwrap();
+                self.bookmarks.insert(new_bookmark, value);
+            }
+        }
+
+        Ok(String::new())
+    }
+
+    // Swaps a clone with the filesystem it was cloned from: the origin
+    // snapshot and the ones before it move to the clone, and the former
+    // origin filesystem becomes a clone of it.
+    fn promote(&mut self, dataset_name: &str) -> Result<String, String> {
+
+        let origin = match self.datasets.get(dataset_name) {
+            Some(dataset) if dataset.kind == Kind::Snapshot =>
+                return Err(format!("cannot promote '{}': operation only applies to filesystems and volumes", dataset_name)),
+            Some(dataset) => match &dataset.origin {
+                Some(origin) => origin.to_owned(),
+                None => return Err(format!("cannot promote '{}': not a cloned filesystem", dataset_name)),
+            },
+            None => return Err(format!("cannot open '{}': dataset does not exist", dataset_name)),
+        };
+
+        let parent = dataset_part(origin.as_str()).to_string();
+        let creation = self.datasets[origin.as_str()].creation;
+
+        let moved: Vec<String> = self.snapshots_of(parent.as_str()).into_iter()
+            .filter(|(_, snapshot_creation)| *snapshot_creation <= creation)
+            .map(|(name, _)| name)
+            .collect();
+
+        if let Some(name) = moved.iter().find(|name| self.datasets.contains_key(format!("{}@{}", dataset_name, name).as_str())) {
+            return Err(format!("cannot promote '{0}': snapshot name '{1}' from origin\nconflicts with '{0}@{1}' from target", dataset_name, name));
+        }
+
+        let parent_origin = self.datasets[parent.as_str()].origin.to_owned();
+
+        for name in moved {
+            let old_name = format!("{}@{}", parent, name);
+            let new_name = format!("{}@{}", dataset_name, name);
+
+            let snapshot = self.datasets.remove(old_name.as_str()).unwrap();
+            self.datasets.insert(new_name.to_owned(), snapshot);
+
+            for dataset in self.datasets.values_mut() {
+                if dataset.origin.as_deref() == Some(old_name.as_str()) {
+                    dataset.origin = Some(new_name.to_owned());
+                }
+            }
+        }
+
+        let new_origin = format!("{}{}", dataset_name, &origin[parent.len()..]);
+        self.datasets.get_mut(dataset_name).unwrap().origin = parent_origin;
+        self.datasets.get_mut(parent.as_str()).unwrap().origin = Some(new_origin);
+
+        Ok(String::new())
+    }
+
+    fn clone(&mut self, snapshot_name: &str, new_dataset_name: &str) -> Result<String, String> {
+
+        let (kind, used) = match self.datasets.get(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => {
+                let origin_kind = self.datasets.get(dataset_part(snapshot_name)).map(|d| d.kind);
+                (origin_kind.unwrap_or(Kind::Filesystem), snapshot.used)
+            },
+            Some(_) => return Err(format!("cannot open '{}': operation only applies to snapshots", snapshot_name)),
+            None => return Err(format!("cannot open '{}': dataset does not exist", snapshot_name)),
+        };
+
+        if new_dataset_name.contains('@') {
+            return Err(format!("cannot create '{}': snapshot delimiter '@' is not expected here", new_dataset_name));
+        }
+
+        if pool_name(snapshot_name) != pool_name(new_dataset_name) {
+            return Err(format!("cannot create '{}': source and target pools differ", new_dataset_name));
+        }
+
+        self.check_new_name(new_dataset_name, "create")?;
+
+        match parent_name(new_dataset_name) {
+            Some(parent) if self.datasets.contains_key(parent) => {},
+            _ => return Err(format!("cannot create '{}': parent does not exist", new_dataset_name)),
+        }
+
+        self.add_dataset(new_dataset_name, kind, used / 8 + 8 * KB, now());
+        self.datasets.get_mut(new_dataset_name).unwrap().origin = Some(snapshot_name.to_string());
+
+        Ok(String::new())
+    }
+
+    // All or none, as a single `zfs snapshot` with several names.
+    fn snapshot_all(&mut self, snapshot_names: &[String]) -> Result<String, String> {
+
+        for name in snapshot_names {
+            if self.datasets.contains_key(name.as_str()) {
+                return Err(format!("cannot create snapshot '{}': dataset already exists\nno snapshots were created", name));
+            }
+            if !self.datasets.contains_key(dataset_part(name)) {
+                return Err(format!("cannot open '{}': dataset does not exist\nno snapshots were created", dataset_part(name)));
+            }
+        }
+
+        for name in snapshot_names {
+            self.snapshot(name)?;
+        }
+
+        Ok(String::new())
+    }
+
+    fn snapshot(&mut self, snapshot_name: &str) -> Result<String, String> {
+
+        if !snapshot_name.contains('@') {
+            return Err(format!("cannot create snapshot '{}': missing '@' delimiter in snapshot name", snapshot_name));
+        }
+
+        let dataset_name = dataset_part(snapshot_name);
+        match self.datasets.get(dataset_name) {
+            Some(dataset) if dataset.kind != Kind::Snapshot => {},
+            _ => return Err(format!("cannot open '{}': dataset does not exist", dataset_name)),
+        }
+
+        if self.datasets.contains_key(snapshot_name) {
+            return Err(format!("cannot create snapshot '{}': dataset already exists", snapshot_name));
+        }
+
+        self.check_new_name(snapshot_name, "create snapshot")?;
+
+        self.add_dataset(snapshot_name, Kind::Snapshot, 0, now());
+        Ok(String::new())
+    }
+
+    fn destroy(&mut self, dataset_name: &str) -> Result<String, String> {
+
+        if dataset_name.contains('#') {
+            return match self.bookmarks.remove(dataset_name) {
+                Some(_) => Ok(String::new()),
+                None => Err(format!("cannot destroy '{}': bookmark does not exist", dataset_name)),
+            };
+        }
+
+        let kind = match self.datasets.get(dataset_name) {
+            Some(dataset) => dataset.kind,
+            None => return Err(format!("cannot open '{}': dataset does not exist", dataset_name)),
+        };
+
+        if kind == Kind::Snapshot {
+            if !self.datasets[dataset_name].holds.is_empty() {
+                return Err(format!("cannot destroy snapshot {}: dataset is busy", dataset_name));
+            }
+
+            let clones = self.clones_of(dataset_name);
+            if !clones.is_empty() {
+                return Err(format!("cannot destroy '{}': snapshot has dependent clones\nuse '-R' to destroy the following datasets:\n{}",
+                    dataset_name, clones.join("\n")));
+            }
+
+        } else {
+            if !dataset_name.contains('/') {
+                return Err(format!("cannot destroy '{0}': operation does not apply to pools\nuse 'zfs destroy -r {0}' to destroy all datasets in the pool\nuse 'zpool destroy {0}' to destroy the pool itself", dataset_name));
+            }
+
+            let children = self.children(dataset_name);
+            if !children.is_empty() {
+                let kind_name = if kind == Kind::Volume { "volume" } else { "filesystem" };
+                return Err(format!("cannot destroy '{}': {} has children\nuse '-r' to destroy the following datasets:\n{}",
+                    dataset_name, kind_name, children.join("\n")));
+            }
+        }
+
+        self.datasets.remove(dataset_name);
+        self.bookmarks.retain(|name, _| bookmark_dataset(name) != dataset_name);
+        Ok(String::new())
+    }
+
+    // What destroying the snapshots would free: their own space, as the fake
+    // does not track the space shared between neighbouring snapshots.
+    fn destroy_size(&self, snapshot_names: &[String]) -> Result<u64, String> {
+
+        let mut size = 0;
+
+        for name in snapshot_names {
+            match self.datasets.get(name.as_str()) {
+                Some(snapshot) if snapshot.kind == Kind::Snapshot => size += snapshot.used,
+                _ => return Err("could not find any snapshots to destroy; check snapshot names.".to_string()),
+            }
+
+            if !self.clones_of(name).is_empty() {
+                return Err(format!("cannot destroy '{}': snapshot has dependent clones", name));
+            }
+        }
+
+        Ok(size)
+    }
+
+    fn bookmark_properties(&self, name: &str, bookmark: &Bookmark) -> CommandResult {
+
+        let mut result = CommandResult::new(name.to_string());
+        result.properties.insert("creation".to_string(), bookmark.creation.to_string());
+        result.properties.insert("guid".to_string(), bookmark.guid.to_string());
+        result
+    }
+
+    // "#name" is short for a bookmark of the snapshot's own dataset.
+    fn bookmark(&mut self, snapshot_name: &str, bookmark_name: &str) -> Result<String, String> {
+
+        let creation = match self.datasets.get(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot.creation,
+            _ => return Err(format!("cannot bookmark '{}': dataset does not exist", snapshot_name)),
+        };
+
+        let bookmark_name = match bookmark_name.strip_prefix('#') {
+            Some(name) => format!("{}#{}", dataset_part(snapshot_name), name),
+            None => bookmark_name.to_string(),
+        };
+
+        match bookmark_name.split_once('#') {
+            Some((dataset, name)) if dataset == dataset_part(snapshot_name) && !name.is_empty() && !name.contains(['#', '@', '/']) => {},
+            Some((dataset, _)) if dataset != dataset_part(snapshot_name) =>
+                return Err(format!("cannot create bookmark '{}': source snapshot must be in the same dataset", bookmark_name)),
+            _ => return Err(format!("cannot create bookmark '{}': invalid bookmark name", bookmark_name)),
+        }
+
+        if self.bookmarks.contains_key(bookmark_name.as_str()) {
+            return Err(format!("cannot create bookmark '{}': bookmark exists", bookmark_name));
+        }
+
+        let snapshot = snapshot_name[dataset_part(snapshot_name).len() + 1..].to_string();
+        self.bookmarks.insert(bookmark_name, Bookmark { snapshot, creation, guid: snapshot_guid(snapshot_name) });
+        Ok(String::new())
+    }
+
+    fn holds(&self, snapshot_names: &[String]) -> Result<Vec<Hold>, String> {
+
+        let mut result = Vec::new();
+
+        for name in snapshot_names {
+            let snapshot = match self.datasets.get(name.as_str()) {
+                Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot,
+                Some(_) => return Err(format!("'{}' is not a snapshot", name)),
+                None => return Err(format!("cannot open '{}': dataset does not exist", name)),
+            };
+
+            result.extend(snapshot.holds.iter().map(|(tag, time)| Hold { snapshot: name.to_owned(), tag: tag.to_owned(), time: *time as i64 }));
+        }
+
+        Ok(result)
+    }
+
+    fn hold(&mut self, tag: &str, snapshot_name: &str) -> Result<String, String> {
+
+        let snapshot = match self.datasets.get_mut(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot,
+            Some(_) => return Err(format!("'{}' is not a snapshot", snapshot_name)),
+            None => return Err(format!("cannot hold snapshot '{}': dataset does not exist", snapshot_name)),
+        };
+
+        if snapshot.holds.contains_key(tag) {
+            return Err(format!("cannot hold snapshot '{}': tag already exists on this dataset", snapshot_name));
+        }
+
+        snapshot.holds.insert(tag.to_string(), now());
+        Ok(String::new())
+    }
+
+    fn release(&mut self, tag: &str, snapshot_name: &str) -> Result<String, String> {
+
+        let snapshot = match self.datasets.get_mut(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot,
+            Some(_) => return Err(format!("'{}' is not a snapshot", snapshot_name)),
+            None => return Err(format!("cannot release hold from snapshot '{}': dataset does not exist", snapshot_name)),
+        };
+
+        if snapshot.holds.remove(tag).is_none() {
+            return Err(format!("cannot release hold from snapshot '{}': no such tag on this dataset", snapshot_name));
+        }
+
+        Ok(String::new())
+    }
+
+    fn rollback(&mut self, snapshot_name: &str) -> Result<String, String> {
+
+        let creation = match self.datasets.get(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot.creation,
+            Some(_) => return Err(format!("cannot rollback '{}': operation only applies to snapshots", snapshot_name)),
+            None => return Err(format!("cannot open '{}': dataset does not exist", snapshot_name)),
+        };
+
+        let prefix = format!("{}@", dataset_part(snapshot_name));
+        let later: Vec<String> = self.datasets.iter()
+            .filter(|(name, dataset)| name.starts_with(&prefix) && dataset.creation > creation)
+            .map(|(name, _)| name.to_owned())
+            .collect();
+
+        for name in later.iter() {
+            if !self.clones_of(name).is_empty() {
+                return Err(format!("cannot rollback to '{}': clones of previous snapshots exist\nuse '-R' to force deletion of the following clones and dependents:\n{}",
+                    snapshot_name, self.clones_of(name).join("\n")));
+            }
+            if !self.datasets[name].holds.is_empty() {
+                return Err(format!("cannot destroy snapshot {}: dataset is busy", name));
+            }
+        }
+
+        for name in later {
+            self.datasets.remove(&name);
+        }
+
+        Ok(String::new())
+    }
+
+    // Made-up changes between a snapshot and a later snapshot or the live
+    // filesystem, in the `zfs diff -FHt` format. The same pair always yields
+    // the same files.
+    fn diff_text(&self, snapshot_name: &str, target: Option<&str>) -> Result<String, String> {
+
+        let dataset_name = dataset_part(snapshot_name);
+
+        let start = match self.datasets.get(snapshot_name) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => snapshot.creation,
+            Some(_) => return Err(format!("Badly formed snapshot name {}", snapshot_name)),
+            None => return Err(format!("cannot open '{}': dataset does not exist", snapshot_name)),
+        };
+
+        if self.datasets.get(dataset_name).is_some_and(|dataset| dataset.kind != Kind::Filesystem) {
+            return Err(format!("Unable to obtain diffs:\n   {} is not a ZFS filesystem", dataset_name));
+        }
+
+        let end = match target {
+            None => now(),
+            Some(target) if target == dataset_name => now(),
+            Some(target) => match self.datasets.get(target) {
+                Some(snapshot) if dataset_part(target) == dataset_name && snapshot.creation >= start => snapshot.creation,
+                Some(_) => return Err("Unable to obtain diffs:\n   Not an earlier snapshot from the same fs".to_string()),
+                None => return Err(format!("cannot open '{}': dataset does not exist", target)),
+            },
+        };
+
+        let mountpoint = self.get_all(dataset_name)?.into_iter()
+            .find(|property| property.name == "mountpoint")
+            .map(|property| property.value)
+            .unwrap_or_else(|| format!("/{}", dataset_name));
+
+        let seed = format!("{}{}", snapshot_name, target.unwrap_or(""));
+        let noise = |salt: usize, range: u64| hash(format!("{}{}", seed, salt).as_str()) % range.max(1);
+        let path = |salt: usize| {
+            let directory = DIRECTORIES[noise(salt, DIRECTORIES.len() as u64) as usize];
+            let file = FILES[noise(salt + 1000, FILES.len() as u64) as usize];
+            let directory = if directory.is_empty() { mountpoint.to_owned() } else { format!("{}/{}", mountpoint, directory) };
+            (directory.to_owned(), format!("{}/{}", directory, file))
+        };
+
+        let mut lines = Vec::new();
+
+        for i in 0..(6 + noise(0, 30) as usize) {
+            let time = start + noise(i + 2000, end.saturating_sub(start).max(1));
+            let nanoseconds = noise(i + 3000, 1_000_000_000);
+            let (directory, file) = path(i);
+
+            let (kind, file_type, paths) = match noise(i + 4000, 10) {
+                0..=2 => ("+", "F", vec![file]),
+                3 => ("-", "F", vec![file]),
+                4..=6 => ("M", "F", vec![file]),
+                7 => ("R", "F", vec![file.to_owned(), format!("{}.old", file)]),
+                8 => ("+", "@", vec![format!("{}/latest", directory)]),
+                _ => ("+", "/", vec![format!("{}/new folder {}", directory, i)]),
+            };
+
+            let paths: Vec<String> = paths.iter().map(|path| diff::escape(path)).collect();
+            lines.push(format!("{}.{:09}\t{}\t{}\t{}", time, nanoseconds, kind, file_type, paths.join("\t")));
+
+            // Adding or removing a file modifies its directory too.
+            if kind == "+" || kind == "-" {
+                lines.push(format!("{}.{:09}\tM\t/\t{}", time, nanoseconds, diff::escape(directory.as_str())));
+            }
+        }
+
+        lines.sort();
+        lines.dedup_by(|a, b| a.split('\t').skip(1).eq(b.split('\t').skip(1)));
+
+        Ok(lines.join("\n"))
+    }
+
+    fn mountpoint(&self, dataset_name: &str) -> Option<String> {
+
+        self.get_all(dataset_name).ok()?.into_iter()
+            .find(|property| property.name == "mountpoint")
+            .map(|property| property.value)
+            .filter(|mountpoint| mountpoint.starts_with('/'))
+    }
+
+    // The filesystem or snapshot a path belongs to, with the path inside it
+    // and the time its files were last changed.
+    fn resolve_path(&self, path: &str) -> Option<(String, String, u64)> {
+
+        let path = path.trim_end_matches('/');
+
+        let (dataset_name, rest) = self.datasets.iter()
+            .filter(|(_, dataset)| dataset.kind == Kind::Filesystem)
+            .filter_map(|(name, _)| {
+                let mountpoint = self.mountpoint(name)?;
+                let rest = path.strip_prefix(mountpoint.trim_end_matches('/'))?;
+                (rest.is_empty() || rest.starts_with('/')).then(|| (name.to_owned(), rest.to_string()))
+            })
+            .min_by_key(|(_, rest)| rest.len())?;
+
+        match rest.strip_prefix("/.zfs/snapshot/") {
+            Some(rest) => {
+                let (snapshot, relative) = rest.split_at(rest.find('/').unwrap_or(rest.len()));
+                let snapshot_name = format!("{}@{}", dataset_name, snapshot);
+                let creation = self.datasets.get(snapshot_name.as_str())?.creation;
+                Some((snapshot_name, relative.to_string(), creation))
+            },
+            None => Some((dataset_name, rest, now())),
+        }
+    }
+
+    fn directory(&self, path: &str) -> Result<Vec<Entry>, String> {
+
+        let missing = || "No such file or directory (os error 2)".to_string();
+
+        let (name, relative, time) = self.resolve_path(path).ok_or_else(missing)?;
+        let relative = relative.trim_start_matches('/');
+
+        if !DIRECTORIES.iter().any(|directory| *directory == relative || directory.starts_with(format!("{}/", relative).as_str()) || relative.is_empty()) {
+            return Err(missing());
+        }
+
+        let noise = |item: &str, range: u64| hash(format!("{}/{}/{}", name, relative, item).as_str()) % range.max(1);
+        let age = |item: &str| time.saturating_sub(noise(item, 30 * DAY)) as i64;
+
+        let mut entries: Vec<Entry> = Vec::new();
+
+        for directory in DIRECTORIES {
+            let child = match directory.strip_prefix(relative) {
+                Some(rest) if relative.is_empty() => rest,
+                Some(rest) if rest.starts_with('/') => &rest[1..],
+                _ => continue,
+            };
+
+            let child = child.split('/').next().unwrap_or("");
+            if !child.is_empty() && !entries.iter().any(|entry| entry.name == child) {
+                entries.push(Entry { name: child.to_string(), file_type: '/', size: 4096, mode: 0o755, modified: age(child) });
+            }
+        }
+
+        for file in FILES.iter().filter(|file| noise(file, 3) != 0) {
+            let mode = if file.starts_with('.') { 0o600 } else { 0o644 };
+            entries.push(Entry { name: file.to_string(), file_type: 'F', size: noise(file, 50 * MB), mode, modified: age(file) });
+        }
+
+        if !name.contains('@') {
+            // Filesystems mounted below, and whatever was restored here.
+            let children = self.datasets.keys()
+                .filter(|child| parent_name(child) == Some(name.as_str()) && !child.contains('@'))
+                .filter_map(|child| self.mountpoint(child))
+                .filter(|mountpoint| parent_name(mountpoint) == Some(path.trim_end_matches('/')));
+
+            for mountpoint in children {
+                let child = mountpoint.rsplit('/').next().unwrap_or("").to_string();
+                entries.retain(|entry| entry.name != child);
+                entries.push(Entry { name: child, file_type: '/', size: 4096, mode: 0o755, modified: time as i64 });
+            }
+
+            for (restored, entry) in self.restored.iter() {
+                if parent_name(restored) == Some(path.trim_end_matches('/')) {
+                    entries.retain(|existing| existing.name != entry.name);
+                    entries.push(entry.clone());
+                }
+            }
+        }
+
+        files::sort(&mut entries);
+        Ok(entries)
+    }
+
+    // Copies files or directories of a snapshot into a directory of the live
+    // filesystem, keeping their mode and times.
+    fn restore(&mut self, sources: &[String], directory: &str) -> Result<String, String> {
+
+        let target = match self.resolve_path(directory) {
+            Some((name, _, _)) if !name.contains('@') && self.directory(directory).is_ok() => name,
+            _ => return Err(format!("cp: target '{}': No such file or directory", directory)),
+        };
+
+        let readonly = self.get_all(target.as_str())?.into_iter()
+            .any(|property| property.name == "readonly" && property.value == "on");
+
+        let mut copies = Vec::new();
+
+        for source in sources {
+            let source = source.trim_end_matches('/');
+            let entry = parent_name(source)
+                .and_then(|parent| self.directory(parent).ok())
+                .and_then(|entries| entries.into_iter().find(|entry| Some(entry.name.as_str()) == source.rsplit('/').next()));
+
+            let entry = match entry {
+                Some(entry) => entry,
+                None => return Err(format!("cp: cannot stat '{}': No such file or directory", source)),
+            };
+
+            let destination = files::join(directory, entry.name.as_str());
+            if readonly {
+                return Err(format!("cp: cannot create {} '{}': Read-only file system",
+                    if entry.is_directory() { "directory" } else { "regular file" }, destination));
+            }
+
+            self.copy_tree(source, &entry, destination.as_str(), &mut copies);
+        }
+
+        self.restored.extend(copies);
+        Ok(String::new())
+    }
+
+    fn copy_tree(&self, source: &str, entry: &Entry, destination: &str, copies: &mut Vec<(String, Entry)>) {
+
+        copies.push((destination.to_string(), entry.clone()));
+
+        if entry.is_directory() {
+            for child in self.directory(source).unwrap_or_default() {
+                let name = child.name.to_owned();
+                self.copy_tree(files::join(source, name.as_str()).as_str(), &child, files::join(destination, name.as_str()).as_str(), copies);
+            }
+        }
+    }
+
+    fn snapshots_of(&self, dataset_name: &str) -> Vec<(String, u64)> {
+
+        let prefix = format!("{}@", dataset_name);
+
+        let mut snapshots: Vec<(String, u64)> = self.datasets.iter()
+            .filter(|(name, _)| name.starts_with(&prefix))
+            .map(|(name, dataset)| (name[prefix.len()..].to_string(), dataset.creation))
+            .collect();
+
+        snapshots.sort_by_key(|(_, creation)| *creation);
+        snapshots
+    }
+
+    // The (source, relative name) pairs sent by a stream: the dataset itself
+    // and, with -R, every descendant holding a snapshot of the same name.
+    fn stream_datasets(&self, send: &SendOptions) -> Vec<(String, String)> {
+
+        let dataset_name = dataset_part(send.snapshot.as_str());
+        let snapshot_name = &send.snapshot[dataset_name.len()..];
+
+        let mut datasets = vec![(dataset_name.to_string(), String::new())];
+
+        if send.recursive {
+            let prefix = format!("{}/", dataset_name);
+            for (name, dataset) in self.datasets.iter() {
+                if name.starts_with(&prefix) && dataset.kind != Kind::Snapshot &&
+                   self.datasets.contains_key(format!("{}{}", name, snapshot_name).as_str()) {
+                    datasets.push((name.to_owned(), name[dataset_name.len()..].to_string()));
+                }
+            }
+        }
+
+        datasets
+    }
+
+    // Names of the snapshots a stream carries for one dataset, oldest first.
+    fn stream_snapshots(&self, dataset_name: &str, send: &SendOptions) -> Vec<String> {
+
+        let snapshot_name = &send.snapshot[dataset_part(send.snapshot.as_str()).len() + 1..];
+
+        let snapshots = self.snapshots_of(dataset_name);
+        let end = snapshots.iter().position(|(name, _)| name == snapshot_name).unwrap_or(0);
+
+        let start = match (&send.base, send.intermediate) {
+            (Some(base), true) => {
+                let base_name = base.rsplit('@').next().unwrap_or("");
+                snapshots.iter().position(|(name, _)| name == base_name).map_or(end, |start| start + 1)
+            },
+            _ => end,
+        };
+
+        snapshots[start.min(end)..=end].iter().map(|(name, _)| name.to_owned()).collect()
+    }
+
+    // The name (after '@') and creation of the snapshot an incremental
+    // stream starts from, which a bookmark of the sent dataset stands in for.
+    fn incremental_source(&self, dataset_name: &str, base: &str) -> Option<(String, u64)> {
+
+        if base.contains('#') {
+            let bookmark = self.bookmarks.get(base)?;
+            return Some((bookmark.snapshot.to_owned(), bookmark.creation));
+        }
+
+        let name = base.rsplit('@').next().unwrap_or("");
+        let snapshot = self.datasets.get(format!("{}@{}", dataset_name, name).as_str())?;
+        Some((name.to_string(), snapshot.creation))
+    }
+
+    fn resumed_by(&self, token: &str) -> Option<(&String, &String)> {
+
+        self.datasets.iter()
+            .find_map(|(name, dataset)| match &dataset.resume {
+                Some((resume_token, snapshot)) if resume_token == token => Some((name, snapshot)),
+                _ => None,
+            })
+    }
+
+    fn stream_size(&self, send: &SendOptions) -> Result<u64, String> {
+
+        if let Some(token) = &send.token {
+            let snapshot = match self.resumed_by(token) {
+                Some((_, snapshot)) => snapshot,
+                None => return Err("cannot resume send: resume token is corrupt or truncated".to_string()),
+            };
+
+            return match self.datasets.get(snapshot.as_str()) {
+                Some(source) => Ok(self.dataset_properties(snapshot, source).property("referenced").parse::<u64>().unwrap_or(0) / 2),
+                None => Err(format!("cannot resume send: '{}' used in the initial send no longer exists", snapshot)),
+            };
+        }
+
+        match self.datasets.get(send.snapshot.as_str()) {
+            Some(snapshot) if snapshot.kind == Kind::Snapshot => {},
+            _ => return Err(format!("cannot open '{}': dataset does not exist", send.snapshot)),
+        }
+
+        if let Some(base) = &send.base {
+            if base.contains('#') && (send.intermediate || send.recursive) {
+                return Err(format!("cannot send '{}': -I and -R need a snapshot as incremental source, not a bookmark", send.snapshot));
+            }
+
+            let base_creation = match self.incremental_source(dataset_part(send.snapshot.as_str()), base) {
+                Some((_, creation)) => creation,
+                None => return Err(format!("cannot open '{}': dataset does not exist", base)),
+            };
+            let creation = self.datasets[send.snapshot.as_str()].creation;
+
+            if base_creation >= creation {
+                return Err(format!("cannot send '{}': incremental source ({}) is not earlier than it", send.snapshot, base));
+            }
+        }
+
+        let mut size = 0;
+        for (dataset_name, _) in self.stream_datasets(send) {
+            for snapshot_name in self.stream_snapshots(dataset_name.as_str(), send) {
+                let name = format!("{}@{}", dataset_name, snapshot_name);
+                let snapshot = &self.datasets[name.as_str()];
+                let properties = self.dataset_properties(name.as_str(), snapshot);
+
+                size += match send.base {
+                    Some(_) => properties.property("written").parse::<u64>().unwrap_or(0),
+                    None => properties.property("referenced").parse::<u64>().unwrap_or(0),
+                };
+            }
+        }
+
+        Ok(size)
+    }
+
+    // Checks that the stream can be received and, when apply is set, creates
+    // the received datasets and snapshots. Returns the stream size.
+    fn receive(&mut self, send: &SendOptions, apply: bool) -> Result<u64, String> {
+
+        let size = self.stream_size(send)?;
+
+        let target = match &send.target {
+            Target::Dataset(target) => target.to_owned(),
+            Target::File(_) => return Ok(size),
+        };
+
+        if target.contains('@') {
+            return Err(format!("cannot receive: '{}' is a snapshot; specify a filesystem or volume", target));
+        }
+
+        if let Some(token) = &send.token {
+            let snapshot = match self.resumed_by(token) {
+                Some((name, snapshot)) if *name == target => snapshot.to_owned(),
+                _ => return Err(format!("cannot receive resume stream: destination '{}' has no matching resumable receive state", target)),
+            };
+
+            if apply {
+                let (used, creation) = {
+                    let source = &self.datasets[snapshot.as_str()];
+                    (self.datasets[dataset_part(snapshot.as_str())].used, source.creation)
+                };
+
+                let dataset = self.datasets.get_mut(target.as_str()).unwrap();
+                dataset.resume = None;
+                dataset.used = used;
+
+                let snapshot_name = format!("{}{}", target, &snapshot[dataset_part(snapshot.as_str()).len()..]);
+                self.add_dataset(snapshot_name.as_str(), Kind::Snapshot, 0, creation);
+            }
+
+            return Ok(size);
+        }
+
+        for (dataset_name, relative) in self.stream_datasets(send) {
+
+            let target_name = format!("{}{}", target, relative);
+            let exists = self.datasets.contains_key(target_name.as_str());
+            let snapshots = self.stream_snapshots(dataset_name.as_str(), send);
+
+            match &send.base {
+                None => {
+                    if exists && !send.force {
+                        return Err(format!("cannot receive new filesystem stream: destination '{}' exists\nmust specify -F to overwrite it", target_name));
+                    }
+                    match parent_name(target_name.as_str()) {
+                        Some(parent) if self.datasets.contains_key(parent) => {},
+                        Some(_) if !relative.is_empty() => {},
+                        _ => return Err(format!("cannot receive new filesystem stream: parent of '{}' does not exist", target_name)),
+                    }
+                    if !self.has_pool(pool_name(target_name.as_str())) {
+                        return Err(format!("cannot receive: no such pool '{}'", pool_name(target_name.as_str())));
+                    }
+                },
+                Some(base) => {
+                    if !exists {
+                        return Err(format!("cannot receive incremental stream: destination '{}' does not exist", target_name));
+                    }
+                    let base_name = self.incremental_source(dataset_name.as_str(), base).map(|(name, _)| name);
+                    let latest = self.snapshots_of(target_name.as_str()).last().map(|(name, _)| name.to_owned());
+                    if latest != base_name && !send.force {
+                        return Err(format!("cannot receive incremental stream: most recent snapshot of '{}' does not\nmatch incremental source", target_name));
+                    }
+                },
+            }
+
+            if !apply { continue }
+
+            if send.base.is_none() {
+                if exists {
+                    let prefix = format!("{}@", target_name);
+                    self.datasets.retain(|name, _| !name.starts_with(&prefix));
+                    self.datasets.remove(target_name.as_str());
+                }
+
+                let source = &self.datasets[dataset_name.as_str()];
+                let (kind, used) = (source.kind, source.used);
+                let properties = if send.properties || send.recursive { source.properties.clone() } else { BTreeMap::new() };
+
+                self.add_dataset(target_name.as_str(), kind, used, now());
+                self.datasets.get_mut(target_name.as_str()).unwrap().properties = properties;
+            }
+
+            for snapshot_name in snapshots {
+                let source = &self.datasets[format!("{}@{}", dataset_name, snapshot_name).as_str()];
+                let (used, creation) = (source.used, source.creation);
+                self.add_dataset(format!("{}@{}", target_name, snapshot_name).as_str(), Kind::Snapshot, used, creation);
+            }
+        }
+
+        Ok(size)
+    }
+
+    fn receive_abort(&mut self, dataset_name: &str) -> Result<String, String> {
+
+        let dataset = match self.datasets.get_mut(dataset_name) {
+            Some(dataset) => dataset,
+            None => return Err(format!("cannot open '{}': dataset does not exist", dataset_name)),
+        };
+
+        if dataset.resume.take().is_none() {
+            return Err(format!("'{}' does not have any resumable receive state to abort", dataset_name));
+        }
+
+        if self.snapshots_of(dataset_name).is_empty() && self.children(dataset_name).is_empty() {
+            self.datasets.remove(dataset_name);
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_status(&self, pool_name: &str) -> Result<String, String> {
+
+        let pool = match self.pools.iter().find(|pool| pool.name == pool_name) {
+            Some(pool) => pool,
+            None => return Err(format!("cannot open '{}': no such pool", pool_name)),
+        };
+
+        let state = pool_state(pool);
+        let mut text = format!("  pool: {}\n state: {}\n", pool.name, state);
+
+        let devices = || pool.vdevs.iter().flat_map(|vdev| vdev.devices.iter().map(move |device| (vdev.class, device)));
+
+        if devices().any(|(class, device)| class != Class::Spare && !is_online(device)) {
+            text.push_str("status: One or more devices are faulted in response to persistent errors.\n");
+            text.push_str("\tSufficient replicas exist for the pool to continue functioning in a\n\tdegraded state.\n");
+            text.push_str("action: Replace the faulted device, or use 'zpool clear' to mark the device\n\trepaired.\n");
+        } else if devices().any(|(_, device)| device.read + device.write + device.cksum > 0) {
+            text.push_str("status: One or more devices has experienced an unrecoverable error.  An\n");
+            text.push_str("\tattempt was made to correct the error.  Applications are unaffected.\n");
+            text.push_str("action: Determine if the device needs to be replaced, and clear the errors\n");
+            text.push_str("\tusing 'zpool clear' or replace the device with 'zpool replace'.\n");
+            text.push_str("   see: https://openzfs.github.io/openzfs-docs/msg/ZFS-8000-9P\n");
+        }
+
+        text.push_str(format!("  scan: {}\n", self.scan_text(pool)).as_str());
+        text.push_str("config:\n\n");
+
+        let rows = config_rows(pool);
+
+        let width = rows.iter().map(|row| row.depth * 2 + row.name.len()).max().unwrap_or(4).max(4);
+        text.push_str(format!("\t{:<width$}  {:<8} {:>5} {:>5} {:>5}\n", "NAME", "STATE", "READ", "WRITE", "CKSUM", width = width).as_str());
+
+        for row in rows {
+            let name = format!("{}{}", "  ".repeat(row.depth), row.name);
+
+            if row.state.is_empty() {
+                text.push_str(format!("\t{}\n", name).as_str());
+            } else if row.state == "AVAIL" {
+                text.push_str(format!("\t{:<width$}  {:<8}\n", name, row.state, width = width).as_str());
+            } else {
+                text.push_str(format!("\t{:<width$}  {:<8} {:>5} {:>5} {:>5}  {}", name, row.state, row.read, row.write, row.cksum, row.note, width = width).trim_end());
+                text.push('\n');
+            }
+        }
+
+        text.push('\n');
+
+        if pool.error_files.is_empty() {
+            text.push_str("errors: No known data errors\n");
+        } else {
+            text.push_str("errors: Permanent errors have been detected in the following files:\n\n");
+            for file in pool.error_files.iter() {
+                text.push_str(format!("        {}\n", file).as_str());
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn scan_text(&self, pool: &Pool) -> String {
+
+        let scrub = match pool.scrub {
+            Some(scrub) => scrub,
+            None => return "none requested".to_string(),
+        };
+
+        let allocated = self.allocated(pool.name.as_str()).min(pool.size);
+        let elapsed = scrub.elapsed();
+
+        if let Some(canceled) = scrub.canceled {
+            return format!("scrub canceled on {}", column::format_time(canceled as i64));
+        }
+
+        if elapsed >= SCRUB_DURATION {
+            return format!("scrub repaired 0B in {} with 0 errors on {}",
+                format_duration(SCRUB_DURATION), column::format_time((scrub.started + SCRUB_DURATION) as i64));
+        }
+
+        let issued = allocated * elapsed / SCRUB_DURATION;
+        let rate = allocated / SCRUB_DURATION;
+        let percent = elapsed as f64 * 100.0 / SCRUB_DURATION as f64;
+
+        if let Some(paused) = scrub.paused {
+            return format!("scrub paused since {}\n\tscrub started on {}\n\t{} scanned, {} issued, {} total\n\t0B repaired, {:.2}% done",
+                column::format_time(paused as i64), column::format_time(scrub.started as i64), column::format_size(issued),
+                column::format_size(issued), column::format_size(allocated), percent);
+        }
+
+        format!("scrub in progress since {}\n\t{} scanned at {}/s, {} issued at {}/s, {} total\n\t0B repaired, {:.2}% done, {} to go",
+            column::format_time(scrub.started as i64), column::format_size(issued), column::format_size(rate),
+            column::format_size(issued), column::format_size(rate), column::format_size(allocated),
+            percent, format_duration(SCRUB_DURATION - elapsed))
+    }
+
+    fn pool_destroy(&mut self, pool_name: &str) -> Result<String, String> {
+
+        let exported = self.take_pool(pool_name)?;
+        self.exported.push(Exported { destroyed: true, ..exported });
+
+        Ok(String::new())
+    }
+
+    fn pool_export(&mut self, pool_name: &str) -> Result<String, String> {
+
+        let exported = self.take_pool(pool_name)?;
+        self.exported.push(exported);
+
+        Ok(String::new())
+    }
+
+    // Removes a pool and its datasets from the imported ones.
+    fn take_pool(&mut self, pool_name: &str) -> Result<Exported, String> {
+
+        let index = match self.pools.iter().position(|pool| pool.name == pool_name) {
+            Some(index) => index,
+            None => return Err(format!("cannot open '{}': no such pool", pool_name)),
+        };
+
+        let pool = self.pools.remove(index);
+
+        let names: Vec<String> = self.datasets.keys()
+            .filter(|name| crate::command::pool_name(name) == pool_name)
+            .cloned()
+            .collect();
+
+        let datasets = names.into_iter()
+            .filter_map(|name| self.datasets.remove(&name).map(|dataset| (name, dataset)))
+            .collect();
+
+        Ok(Exported { pool, datasets, destroyed: false })
+    }
+
+    // Exported pools are found on the devices in /dev, or with -d in the
+    // given directories; a destroyed pool is gone once one of its devices
+    // has been reused.
+    fn importable(&self, directories: &[String], destroyed: bool) -> Vec<&Exported> {
+
+        let visible = |path: &str| {
+            if path.starts_with("/dev/") {
+                directories.is_empty() || directories.iter().any(|directory| path.starts_with(directory.as_str()))
+            } else {
+                directories.iter().any(|directory| parent_name(path) == Some(directory.trim_end_matches('/')))
+            }
+        };
+
+        self.exported.iter()
+            .filter(|exported| exported.destroyed == destroyed)
+            .filter(|exported| exported.pool.vdevs.iter().all(|vdev| vdev.devices.iter().all(|device| {
+                visible(device.path.as_str()) && !self.pools.iter().any(|pool| find_device(pool, device.path.as_str()).is_some())
+            })))
+            .collect()
+    }
+
+    fn import_text(&self, directories: &[String], destroyed: bool) -> String {
+
+        let mut blocks = Vec::new();
+
+        for exported in self.importable(directories, destroyed) {
+            let pool = &exported.pool;
+            let state = pool_state(pool);
+
+            let mut text = format!("   pool: {}\n     id: {}\n", pool.name, pool.id);
+
+            if exported.destroyed {
+                text.push_str(format!("  state: {} (DESTROYED)\n", state).as_str());
+                text.push_str(" action: The pool can be imported using its name or numeric identifier.  The\n");
+                text.push_str("\tpool was destroyed, but can be imported using the '-Df' flags.\n");
+            } else {
+                text.push_str(format!("  state: {}\n", state).as_str());
+                text.push_str(" action: The pool can be imported using its name or numeric identifier.\n");
+            }
+
+            text.push_str(" config:\n\n");
+
+            let rows = config_rows(pool);
+            let width = rows.iter().map(|row| row.depth * 2 + row.name.len()).max().unwrap_or(4);
+
+            for row in rows {
+                let name = format!("{}{}", "  ".repeat(row.depth), row.name);
+                text.push_str(format!("\t{:<width$}  {}", name, row.state, width = width).trim_end());
+                text.push('\n');
+            }
+
+            blocks.push(text);
+        }
+
+        blocks.join("\n")
+    }
+
+    fn pool_import(&mut self, import: &ImportOptions) -> Result<String, String> {
+
+        let matching: Vec<u64> = self.importable(&import.directories, import.destroyed).iter()
+            .filter(|exported| exported.pool.name == import.pool || exported.pool.id.to_string() == import.pool)
+            .map(|exported| exported.pool.id)
+            .collect();
+
+        let id = match matching.as_slice() {
+            [id] => *id,
+            [] => return Err(format!("cannot import '{}': no such pool available", import.pool)),
+            _ => return Err(format!("cannot import '{}': more than one matching pool\nimport by numeric ID instead", import.pool)),
+        };
+
+        let new_name = import.imported_name().to_string();
+        let index = self.exported.iter().position(|exported| exported.pool.id == id).unwrap();
+        let old_name = self.exported[index].pool.name.to_owned();
+
+        if new_name != old_name || self.has_pool(new_name.as_str()) {
+            self.check_pool_name(new_name.as_str(), "import")?;
+        }
+
+        if self.exported[index].destroyed && !import.force {
+            return Err(format!("cannot import '{}': pool was destroyed, use '-Df' to import it", import.pool));
+        }
+
+        let mut exported = self.exported.remove(index);
+
+        exported.pool.name = new_name.to_owned();
+        if import.readonly {
+            exported.pool.properties.insert("readonly".to_string(), "on".to_string());
+        }
+        if let Some(altroot) = &import.altroot {
+            exported.pool.properties.insert("altroot".to_string(), altroot.to_owned());
+        }
+
+        for (name, dataset) in exported.datasets {
+            let name = format!("{}{}", new_name, &name[old_name.len()..]);
+            self.datasets.insert(name, dataset);
+        }
+
+        self.pools.push(exported.pool);
+        Ok(String::new())
+    }
+
+    fn pool_scrub(&mut self, pool_name: &str, control: Control) -> Result<String, String> {
+
+        let pool = match self.pools.iter_mut().find(|pool| pool.name == pool_name) {
+            Some(pool) => pool,
+            None => return Err(format!("cannot open '{}': no such pool", pool_name)),
+        };
+
+        let active = pool.scrub.as_mut().filter(|scrub| scrub.is_active(SCRUB_DURATION));
+
+        match (control, active) {
+            (Control::Start, Some(scrub)) if scrub.paused.is_some() => scrub.resume(),
+            (Control::Start, Some(_)) => {
+                return Err(format!("cannot scrub {}: currently scrubbing; use 'zpool scrub -s' to cancel current scrub", pool_name));
+            },
+            (Control::Start, None) => pool.scrub = Some(Task::start()),
+            (Control::Pause, Some(scrub)) if scrub.paused.is_some() => {
+                return Err(format!("cannot pause scrubbing {}: scrub is already paused", pool_name));
+            },
+            (Control::Pause, Some(scrub)) => scrub.paused = Some(now()),
+            (Control::Cancel, Some(scrub)) => scrub.canceled = Some(now()),
+            (_, None) => {
+                let action = if control == Control::Pause { "pause" } else { "cancel" };
+                return Err(format!("cannot {} scrubbing {}: there is no active scrub", action, pool_name));
+            },
+        }
+
+        Ok(String::new())
+    }
+
+    // `zpool trim` and `zpool initialize` act on every data and log device
+    // of the pool that is online.
+    fn pool_device_task(&mut self, pool_name: &str, operation: &str, control: Control) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+        let duration = if operation == "trim" { TRIM_DURATION } else { INITIALIZE_DURATION };
+
+        let mut devices: Vec<&mut Device> = pool.vdevs.iter_mut()
+            .filter(|vdev| vdev.class == Class::Data || vdev.class == Class::Log)
+            .flat_map(|vdev| vdev.devices.iter_mut())
+            .filter(|device| is_online(device))
+            .collect();
+
+        let tasks: Vec<(String, Option<Task>)> = devices.iter_mut().map(|device| (device.path.to_owned(), *device.task_mut(operation))).collect();
+
+        let running = tasks.iter().find(|(_, task)| task.is_some_and(|task| task.is_active(duration) && task.paused.is_none()));
+        let completed = tasks.iter().find(|(_, task)| task.is_some_and(|task| !task.is_active(duration) && task.canceled.is_none()));
+        let active = tasks.iter().any(|(_, task)| task.is_some_and(|task| task.is_active(duration)));
+
+        match control {
+            Control::Start => {
+                if let Some((path, _)) = running {
+                    let doing = if operation == "trim" { "trimming" } else { "initializing" };
+                    return Err(format!("cannot {} '{}': currently {}", operation, path, doing));
+                }
+                if let Some((path, _)) = completed.filter(|_| operation == "initialize") {
+                    return Err(format!("cannot initialize '{}': device was already initialized", path));
+                }
+                for device in devices {
+                    match device.task_mut(operation) {
+                        Some(task) if task.paused.is_some() => task.resume(),
+                        task => *task = Some(Task::start()),
+                    }
+                }
+            },
+            _ if !active => {
+                let action = if control == Control::Pause { "suspend" } else { "cancel" };
+                return Err(format!("cannot {} {} of '{}': there is no active {}", action, operation, pool_name, operation));
+            },
+            Control::Pause => {
+                for device in devices {
+                    if let Some(task) = device.task_mut(operation).as_mut().filter(|task| task.is_active(duration)) {
+                        task.paused.get_or_insert_with(now);
+                    }
+                }
+            },
+            Control::Cancel => {
+                for device in devices {
+                    let task = device.task_mut(operation);
+                    if task.is_some_and(|task| task.is_active(duration)) {
+                        *task = None;
+                    }
+                }
+            },
+        }
+
+        Ok(String::new())
+    }
+
+    // A sample of `zpool iostat -Hpvy -l -q` in the OpenZFS 2 layout. The
+    // devices get a made-up load, busier on tank and while scrubbing; the
+    // vdevs and pools add up their devices.
+    fn iostat_text(&self, pool_names: &[String], sample: u64) -> Result<String, String> {
+
+        let mut pools = Vec::new();
+
+        for pool_name in pool_names {
+            match self.pools.iter().find(|pool| pool.name == *pool_name) {
+                Some(pool) => pools.push(pool),
+                None => return Err(format!("cannot open '{}': no such pool", pool_name)),
+            }
+        }
+
+        if pool_names.is_empty() {
+            pools = self.pools.iter().collect();
+        }
+
+        let mut text = String::new();
+
+        for pool in pools {
+            let scrubbing = pool.scrub.is_some_and(|scrub| scrub.is_active(SCRUB_DURATION) && scrub.paused.is_none());
+            let load = if pool.name == "tank" { 4 } else { 1 };
+
+            let rows: Vec<status::Vdev> = config_rows(pool).into_iter()
+                .take_while(|row| row.name != "spares")
+                .collect();
+
+            // (read ops, write ops, read bytes, write bytes, wait, disk wait, sync queue, async queue)
+            let leaves: Vec<Option<[u64; 8]>> = rows.iter().map(|row| {
+                if !row.is_device() { return None }
+                if row.state != "ONLINE" { return Some([0; 8]) }
+
+                let noise = |salt: &str, range: u64| hash(format!("{}{}{}", row.name, salt, sample).as_str()) % range.max(1);
+                let is_nvme = row.name.contains("nvme");
+
+                let read_ops = load * (20 + noise("r", 60)) + if scrubbing { 400 + noise("s", 200) } else { 0 };
+                let write_ops = load * (10 + noise("w", 80));
+                let wait = if is_nvme { 30_000 + noise("l", 150_000) } else { 2_000_000 + noise("l", 9_000_000) };
+
+                Some([read_ops, write_ops, read_ops * (16 + noise("rb", 112)) * KB, write_ops * (8 + noise("wb", 120)) * KB,
+                    wait, wait * 4 / 5, noise("q", 3 * load), noise("aq", 6 * load)])
+            }).collect();
+
+            for (i, row) in rows.iter().enumerate() {
+                let name = format!("{}{}", "  ".repeat(row.depth), row.name.rsplit('/').next().unwrap_or(""));
+
+                if row.is_section() {
+                    text.push_str(format!("{}{}\n", name, "\t-".repeat(31)).as_str());
+                    continue;
+                }
+
+                // The pool adds up all its devices, a vdev the devices under it.
+                let end = if row.depth == 0 { rows.len() } else {
+                    rows.iter().skip(i + 1).position(|other| other.depth <= row.depth).map(|n| i + 1 + n).unwrap_or(rows.len())
+                };
+                let leaves: Vec<[u64; 8]> = leaves[i..end].iter().flatten().copied().collect();
+                let count = leaves.len().max(1) as u64;
+                let sum = |field: usize| leaves.iter().map(|leaf| leaf[field]).sum::<u64>();
+
+                let (allocated, free) = if row.depth == 0 {
+                    let allocated = self.allocated(pool.name.as_str()).min(pool.size);
+                    (allocated.to_string(), (pool.size - allocated).to_string())
+                } else {
+                    ("-".to_string(), "-".to_string())
+                };
+
+                let wait = sum(4) / count;
+                let disk_wait = sum(5) / count;
+                let latencies = [wait, wait * 4 / 3, disk_wait, disk_wait * 4 / 3, wait / 5, wait / 4, wait / 3, wait / 2];
+                let queues = [sum(6), sum(6) / 2, sum(6) / 2, sum(6) / 3, sum(7) / 2, sum(7) / 3, sum(7), sum(7) / 2];
+
+                let mut fields = vec![name, allocated, free];
+                fields.extend([sum(0), sum(1), sum(2), sum(3)].iter().map(|value| value.to_string()));
+                fields.extend(latencies.iter().map(|value| value.to_string()));
+                fields.extend(["-", "-", "-"].iter().map(|value| value.to_string()));
+                fields.extend(queues.iter().map(|value| value.to_string()));
+                fields.extend(["0"; 6].iter().map(|value| value.to_string()));
+
+                text.push_str(fields.join("\t").as_str());
+                text.push('\n');
+            }
+        }
+
+        Ok(text)
+    }
+
+    fn pool_mut(&mut self, pool_name: &str) -> Result<&mut Pool, String> {
+
+        match self.pools.iter_mut().find(|pool| pool.name == pool_name) {
+            Some(pool) => Ok(pool),
+            None => Err(format!("cannot open '{}': no such pool", pool_name)),
+        }
+    }
+
+    fn check_unused(&self, path: &str) -> Result<(), String> {
+
+        if !path.starts_with('/') {
+            return Err(format!("cannot open '{}': no such device in /dev", path));
+        }
+
+        if !path.starts_with("/dev/") && !self.devices.contains_key(path) {
+            return Err(format!("cannot open '{}': No such file or directory", path));
+        }
+
+        let owner = self.pools.iter()
+            .find(|pool| pool.vdevs.iter().any(|vdev| vdev.devices.iter().any(|device| device.path == path)));
+
+        if let Some(pool) = owner {
+            return Err(format!("{} is part of active pool '{}'", path, pool.name));
+        }
+
+        let exported = self.exported.iter()
+            .filter(|exported| !exported.destroyed)
+            .find(|exported| exported.pool.vdevs.iter().any(|vdev| vdev.devices.iter().any(|device| device.path == path)));
+
+        match exported {
+            Some(exported) => Err(format!("{} is part of exported pool '{}'", path, exported.pool.name)),
+            None => Ok(()),
+        }
+    }
+
+    fn pool_offline(&mut self, pool_name: &str, device: &str) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+        let (vdev_index, device_index) = find_device(pool, device).ok_or_else(|| format!("cannot offline {}: no such device in pool", device))?;
+
+        let vdev = &mut pool.vdevs[vdev_index];
+        if vdev.class == Class::Spare {
+            return Err(format!("cannot offline {}: device is reserved as a hot spare", device));
+        }
+
+        let previous = std::mem::replace(&mut vdev.devices[device_index].state, "OFFLINE".to_string());
+        if vdev.class != Class::Cache && vdev_state(vdev) == "UNAVAIL" {
+            vdev.devices[device_index].state = previous;
+            return Err(format!("cannot offline {}: no valid replicas", device));
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_online(&mut self, pool_name: &str, device: &str) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+        let (vdev_index, device_index) = find_device(pool, device).ok_or_else(|| format!("cannot online {}: no such device in pool", device))?;
+
+        let device = &mut pool.vdevs[vdev_index].devices[device_index];
+        if device.state != "AVAIL" && device.state != "INUSE" {
+            device.state = "ONLINE".to_string();
+            device.note.clear();
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_attach(&mut self, pool_name: &str, device: &str, new_device: &str) -> Result<String, String> {
+
+        self.check_unused(new_device)?;
+
+        let pool = self.pool_mut(pool_name)?;
+        let (vdev_index, _) = find_device(pool, device).ok_or_else(|| format!("cannot attach {} to {}: no such device in pool", new_device, device))?;
+
+        let vdev = &mut pool.vdevs[vdev_index];
+        if (vdev.kind != "mirror" && vdev.kind != "disk") || vdev.class == Class::Cache || vdev.class == Class::Spare {
+            return Err(format!("cannot attach {} to {}: can only attach to mirrors and top-level disks", new_device, device));
+        }
+
+        vdev.kind = "mirror".to_string();
+        vdev.devices.extend(self::vdev(vdev.class, "disk", &[new_device.to_string()]).devices);
+
+        Ok(String::new())
+    }
+
+    fn pool_detach(&mut self, pool_name: &str, device: &str) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+        let (vdev_index, device_index) = find_device(pool, device).ok_or_else(|| format!("cannot detach {}: no such device in pool", device))?;
+
+        let vdev = &mut pool.vdevs[vdev_index];
+        if vdev.kind != "mirror" {
+            return Err(format!("cannot detach {}: only applicable to mirror and replacing vdevs", device));
+        }
+
+        if !vdev.devices.iter().enumerate().any(|(i, other)| i != device_index && is_online(other)) {
+            return Err(format!("cannot detach {}: no valid replicas", device));
+        }
+
+        vdev.devices.remove(device_index);
+        if vdev.devices.len() == 1 {
+            vdev.kind = "disk".to_string();
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_replace(&mut self, pool_name: &str, device: &str, new_device: &str) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+        let spare = pool.vdevs.iter().position(|vdev| vdev.class == Class::Spare && vdev.devices.iter().any(|spare| spare.path == new_device));
+
+        if spare.is_none() {
+            self.check_unused(new_device)?;
+        }
+
+        let pool = self.pool_mut(pool_name)?;
+        let (vdev_index, device_index) = find_device(pool, device).ok_or_else(|| format!("cannot replace {} with {}: no such device in pool", device, new_device))?;
+
+        if pool.vdevs[vdev_index].class == Class::Spare {
+            return Err(format!("cannot replace {} with {}: device is reserved as a hot spare", device, new_device));
+        }
+
+        let class = pool.vdevs[vdev_index].class;
+        pool.vdevs[vdev_index].devices[device_index] = self::vdev(class, "disk", &[new_device.to_string()]).devices.remove(0);
+
+        if let Some(spare) = spare {
+            pool.vdevs.remove(spare);
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_add(&mut self, pool_name: &str, spec: &[String], dry_run: bool) -> Result<String, String> {
+
+        let vdevs = parse_vdevs(spec).map_err(|error| format!("cannot add to '{}': {}", pool_name, error))?;
+
+        let mut paths: Vec<&String> = vdevs.iter().flat_map(|vdev| vdev.devices.iter().map(|device| &device.path)).collect();
+        for path in paths.iter() {
+            self.check_unused(path)?;
+        }
+
+        let count = paths.len();
+        paths.sort();
+        paths.dedup();
+        if paths.len() < count {
+            return Err(format!("cannot add to '{}': the same device is listed more than once", pool_name));
+        }
+
+        let pool = self.pool_mut(pool_name)?;
+
+        if dry_run {
+            let mut updated = pool.clone();
+            updated.vdevs.extend(vdevs);
+
+            let text = format!("would update '{}' to the following configuration:\n\n{}", pool_name, layout_text(&updated));
+            return Ok(text);
+        }
+
+        let added: u64 = vdevs.iter().filter(|vdev| vdev.class == Class::Data).map(|vdev| self.capacity(vdev)).sum();
+
+        let pool = self.pool_mut(pool_name)?;
+        pool.size += added;
+        pool.vdevs.extend(vdevs);
+
+        Ok(String::new())
+    }
+
+    fn pool_remove(&mut self, pool_name: &str, device: &str) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+
+        let (vdev_index, device_index) = find_vdev(pool, device).ok_or_else(|| format!("cannot remove {}: no such device in pool", device))?;
+
+        let vdev = &pool.vdevs[vdev_index];
+        let top_level = device_index.is_none() || vdev.kind == "disk";
+
+        if !top_level {
+            return Err(format!("cannot remove {}: operation not supported on this type of vdev, use 'zpool detach' to remove a device from a mirror", device));
+        }
+
+        if vdev.class == Class::Data {
+            if pool.vdevs.iter().any(|vdev| vdev.class == Class::Data && vdev.kind.starts_with("raidz")) {
+                return Err(format!("cannot remove {}: invalid config; all top-level vdevs must have the same sector size and not be raidz.", device));
+            }
+            if pool.vdevs.iter().filter(|vdev| vdev.class == Class::Data).count() == 1 {
+                return Err(format!("cannot remove {}: the pool needs at least one top-level vdev", device));
+            }
+        }
+
+        let removed = pool.vdevs.remove(vdev_index);
+        if removed.class == Class::Data {
+            let capacity = self.capacity(&removed);
+            let pool = self.pool_mut(pool_name)?;
+            pool.size = pool.size.saturating_sub(capacity);
+        }
+
+        Ok(String::new())
+    }
+
+    fn free_devices(&self) -> Vec<BlockDevice> {
+
+        self.devices.iter()
+            .filter(|(path, _)| path.starts_with("/dev/disk/by-id/") && self.check_unused(path).is_ok())
+            .map(|(path, size)| BlockDevice { path: path.to_owned(), size: *size })
+            .collect()
+    }
+
+    // Usable space of a data vdev: the smallest device times the number of
+    // devices that do not hold parity.
+    fn capacity(&self, vdev: &Vdev) -> u64 {
+
+        let count = vdev.devices.len() as u64;
+        let smallest = vdev.devices.iter()
+            .map(|device| self.devices.get(&device.path).copied().unwrap_or(4 * TB))
+            .min()
+            .unwrap_or(0);
+
+        let data = match vdev.kind.as_str() {
+            "mirror" => 1,
+            "raidz1" | "draid1" => count.saturating_sub(1),
+            "raidz2" | "draid2" => count.saturating_sub(2),
+            "raidz3" | "draid3" => count.saturating_sub(3),
+            _ => count,
+        };
+
+        smallest * data
+    }
+
+    fn file_vdev(&mut self, path: &str, size: u64) -> Result<String, String> {
+
+        if !path.starts_with('/') || path.ends_with('/') {
+            return Err(format!("truncate: cannot open '{}' for writing: No such file or directory", path));
+        }
+
+        if let Err(error) = self.check_unused(path) {
+            if error.contains("active pool") { return Err(format!("truncate: {}", error)); }
+        }
+
+        self.devices.insert(path.to_string(), size);
+        Ok(String::new())
+    }
+
+    fn check_pool_name(&self, name: &str, action: &str) -> Result<(), String> {
+
+        if !name.starts_with(|c: char| c.is_ascii_alphabetic()) {
+            return Err(format!("cannot {} '{}': name must begin with a letter", action, name));
+        }
+
+        if let Some(c) = name.chars().find(|c| !c.is_ascii_alphanumeric() && !"_-.:".contains(*c)) {
+            return Err(format!("cannot {} '{}': invalid character '{}' in pool name", action, name, c));
+        }
+
+        if name == "log" || RESERVED_POOL_NAMES.iter().any(|reserved| name.starts_with(reserved)) {
+            return Err(format!("cannot {} '{}': name is reserved", action, name));
+        }
+
+        if self.has_pool(name) {
+            return Err(format!("cannot {} '{}': a pool with that name already exists", action, name));
+        }
+
+        Ok(())
+    }
+
+    fn pool_create(&mut self, create: &CreateOptions, dry_run: bool) -> Result<String, String> {
+
+        let name = create.name.as_str();
+        self.check_pool_name(name, "create")?;
+
+        for (property, value) in create.pool_properties.iter() {
+            property::validate(property, value, true).map_err(|error| format!("cannot create '{}': {}", name, error))?;
+        }
+
+        for (property, value) in create.filesystem_properties.iter() {
+            property::validate(property, value, false).map_err(|error| format!("cannot create '{}': {}", name, error))?;
+        }
+
+        let vdevs = parse_vdevs(&create.vdev_arguments()).map_err(|error| format!("cannot create '{}': {}", name, error))?;
+
+        for device in vdevs.iter().flat_map(|vdev| vdev.devices.iter()) {
+            self.check_unused(device.path.as_str())?;
+            if self.devices.get(&device.path).is_some_and(|size| *size < MIN_DEVICE_SIZE) {
+                return Err(format!("cannot create '{}': one or more devices is less than the minimum size (64M)", name));
+            }
+        }
+
+        let size = vdevs.iter().map(|vdev| self.capacity(vdev)).sum();
+
+        if dry_run {
+            let preview = Pool::new(name, size, vdevs);
+            return Ok(format!("would create '{}' with the following layout:\n\n{}", name, layout_text(&preview)));
+        }
+
+        self.add_pool(name, size, now(), vdevs);
+
+        let pool = self.pools.last_mut().unwrap();
+        if let Some(ashift) = create.ashift {
+            pool.properties.insert("ashift".to_string(), ashift.to_string());
+        }
+        for (property, value) in create.pool_properties.iter() {
+            pool.properties.insert(property.to_owned(), value.to_owned());
+        }
+
+        for (property, value) in create.filesystem_properties.iter() {
+            self.set_local(name, property, value);
+        }
+
+        Ok(String::new())
+    }
+
+    fn pool_clear(&mut self, pool_name: &str, device: Option<&str>) -> Result<String, String> {
+
+        let pool = self.pool_mut(pool_name)?;
+
+        let clear = |device: &mut Device| {
+            device.read = 0;
+            device.write = 0;
+            device.cksum = 0;
+            device.note.clear();
+            if device.state == "FAULTED" || device.state == "DEGRADED" {
+                device.state = "ONLINE".to_string();
+            }
+        };
+
+        match device {
+            Some(device) => {
+                let (vdev_index, device_index) = find_vdev(pool, device).ok_or_else(|| format!("cannot clear errors for {}: no such device in pool", device))?;
+
+                match device_index {
+                    Some(device_index) => clear(&mut pool.vdevs[vdev_index].devices[device_index]),
+                    None => pool.vdevs[vdev_index].devices.iter_mut().for_each(clear),
+                }
+            },
+            None => {
+                pool.vdevs.iter_mut().flat_map(|vdev| vdev.devices.iter_mut()).for_each(clear);
+                pool.error_files.clear();
+            },
+        }
+
+        Ok(String::new())
+    }
+}
+
+// The vdev tree of a pool as `zpool status` shows it: data vdevs under the
+// pool, then the logs, cache and spares sections.
+fn config_rows(pool: &Pool) -> Vec<status::Vdev> {
+
+    let row = |name: String, depth: usize, state: &str, device: Option<&Device>| status::Vdev {
+        name,
+        depth,
+        state: state.to_string(),
+        read: device.map(|device| device.read.to_string()).unwrap_or_else(|| "0".to_string()),
+        write: device.map(|device| device.write.to_string()).unwrap_or_else(|| "0".to_string()),
+        cksum: device.map(|device| device.cksum.to_string()).unwrap_or_else(|| "0".to_string()),
+        note: device.map(|device| {
+            let notes = [
+                Some(device.note.to_owned()).filter(|note| !note.is_empty()),
+                task_note(device.trim, "trimmed", TRIM_DURATION),
+                task_note(device.initialize, "initialized", INITIALIZE_DURATION),
+            ];
+            notes.iter().flatten().cloned().collect::<Vec<String>>().join("  ")
+        }).unwrap_or_default(),
+    };
+
+    let mut rows = vec![row(pool.name.to_owned(), 0, pool_state(pool), None)];
+
+    for class in [Class::Data, Class::Log, Class::Cache, Class::Spare].iter() {
+
+        if *class != Class::Data && pool.vdevs.iter().any(|vdev| vdev.class == *class) {
+            rows.push(row(class_section(*class).to_string(), 0, "", None));
+        }
+
+        for (index, vdev) in pool.vdevs.iter().enumerate().filter(|(_, vdev)| vdev.class == *class) {
+            let mut depth = 1;
+
+            if vdev.kind != "disk" {
+                rows.push(row(format!("{}-{}", vdev.kind, index), depth, vdev_state(vdev), None));
+                depth += 1;
+            }
+
+            for device in vdev.devices.iter() {
+                rows.push(row(device.path.to_owned(), depth, device.state.as_str(), Some(device)));
+            }
+        }
+    }
+
+    rows
+}
+
+// The vdev tree as `zpool create -n` and `zpool add -n` print it.
+fn layout_text(pool: &Pool) -> String {
+
+    let mut text = String::new();
+
+    for row in config_rows(pool) {
+        let name = row.name.rsplit('/').next().unwrap_or("");
+        text.push_str(format!("\t{}{}\n", "  ".repeat(row.depth), name).as_str());
+    }
+
+    text
+}
+
+fn class_section(class: Class) -> &'static str {
+    match class {
+        Class::Data  => "",
+        Class::Log   => "logs",
+        Class::Cache => "cache",
+        Class::Spare => "spares",
+    }
+}
+
+// Parses a vdev specification as given to `zpool add`, e.g.
+// "mirror sda sdb log mirror nvme0 nvme1 cache nvme2".
+fn parse_vdevs(spec: &[String]) -> Result<Vec<Vdev>, String> {
+
+    fn close(group: Option<(Class, String, Vec<String>)>, vdevs: &mut Vec<Vdev>) -> Result<(), String> {
+
+        let (class, kind, devices) = match group {
+            Some(group) => group,
+            None => return Ok(()),
+        };
+
+        let required = pool::minimum_devices(kind.as_str());
+
+        if devices.len() < required {
+            return Err(format!("invalid vdev specification: {} requires at least {} devices", kind, required));
+        }
+
+        if (class == Class::Cache || class == Class::Spare) && kind != "disk" {
+            return Err(format!("invalid vdev specification: {} devices must be disks or files", class_section(class)));
+        }
+
+        vdevs.push(vdev(class, kind.as_str(), &devices));
+        Ok(())
+    }
+
+    let mut vdevs = Vec::new();
+    let mut class = Class::Data;
+    let mut group: Option<(Class, String, Vec<String>)> = None;
+
+    for token in spec {
+        match token.as_str() {
+            "log" | "cache" | "spare" => {
+                close(group.take(), &mut vdevs)?;
+                class = match token.as_str() { "log" => Class::Log, "cache" => Class::Cache, _ => Class::Spare };
+            },
+            "special" | "dedup" => {
+                return Err(format!("invalid vdev specification: {} vdevs are not supported by the fake backend", token));
+            },
+            "mirror" | "raidz" | "raidz1" | "raidz2" | "raidz3" | "draid" | "draid1" | "draid2" | "draid3" => {
+                close(group.take(), &mut vdevs)?;
+                let kind = match token.as_str() { "raidz" => "raidz1", "draid" => "draid1", kind => kind };
+                group = Some((class, kind.to_string(), Vec::new()));
+            },
+            device => {
+                let path = if device.starts_with('/') { device.to_string() } else { format!("/dev/{}", device) };
+
+                match group.as_mut() {
+                    Some((_, _, devices)) => devices.push(path),
+                    None => close(Some((class, "disk".to_string(), vec![path])), &mut vdevs)?,
+                }
+            },
+        }
+    }
+
+    close(group, &mut vdevs)?;
+
+    if vdevs.is_empty() {
+        return Err("missing vdev specification".to_string());
+    }
+
+    Ok(vdevs)
+}
+
+fn find_device(pool: &Pool, path: &str) -> Option<(usize, usize)> {
+
+    pool.vdevs.iter().enumerate().find_map(|(vdev_index, vdev)| {
+        vdev.devices.iter().position(|device| device.path == path).map(|device_index| (vdev_index, device_index))
+    })
+}
+
+// A device path, or the name of a top-level vdev such as "mirror-1".
+fn find_vdev(pool: &Pool, name: &str) -> Option<(usize, Option<usize>)> {
+
+    if let Some((vdev_index, device_index)) = find_device(pool, name) {
+        return Some((vdev_index, Some(device_index)));
+    }
+
+    pool.vdevs.iter().enumerate()
+        .position(|(index, vdev)| format!("{}-{}", vdev.kind, index) == name)
+        .map(|vdev_index| (vdev_index, None))
+}
+
+fn vdev(class: Class, kind: &str, devices: &[String]) -> Vdev {
+
+    Vdev {
+        class,
+        kind: kind.to_string(),
+        devices: devices.iter().map(|path| Device {
+            path: path.to_owned(),
+            state: if class == Class::Spare { "AVAIL" } else { "ONLINE" }.to_string(),
+            read: 0,
+            write: 0,
+            cksum: 0,
+            note: String::new(),
+            trim: None,
+            initialize: None,
+        }).collect(),
+    }
+}
+
+fn disk(serial: &str) -> String {
+    format!("/dev/disk/by-id/ata-WDC_WD40EFRX-68N32N0_WD-{}-part1", serial)
+}
+
+fn nvme(serial: &str, partition: u32) -> String {
+    format!("/dev/disk/by-id/nvme-Samsung_SSD_970_EVO_Plus_500GB_{}-part{}", serial, partition)
+}
+
+fn is_online(device: &Device) -> bool {
+    device.state == "ONLINE" || device.state == "AVAIL" || device.state == "INUSE"
+}
+
+fn vdev_state(vdev: &Vdev) -> &'static str {
+
+    let online = vdev.devices.iter().filter(|device| is_online(device)).count();
+
+    let tolerated = match vdev.kind.as_str() {
+        "mirror" => vdev.devices.len().saturating_sub(1),
+        "raidz1" => 1,
+        "raidz2" => 2,
+        "raidz3" => 3,
+        _ => 0,
+    };
+
+    if online == vdev.devices.len() {
+        "ONLINE"
+    } else if vdev.devices.len() - online <= tolerated {
+        "DEGRADED"
+    } else {
+        "UNAVAIL"
+    }
+}
+
+fn pool_state(pool: &Pool) -> &'static str {
+
+    let states: Vec<&str> = pool.vdevs.iter()
+        .filter(|vdev| vdev.class == Class::Data)
+        .map(vdev_state)
+        .collect();
+
+    if states.contains(&"UNAVAIL") {
+        "UNAVAIL"
+    } else if states.contains(&"DEGRADED") || pool.vdevs.iter().any(|vdev| vdev.class == Class::Log && vdev_state(vdev) != "ONLINE") {
+        "DEGRADED"
+    } else {
+        "ONLINE"
+    }
+}
+
+fn format_duration(seconds: u64) -> String {
+    format!("{:02}:{:02}:{:02}", seconds / 3600, seconds / 60 % 60, seconds % 60)
+}
+
+fn simulated_control(operation: &str, pool_name: &str, control: Control, result: Result<String, String>) -> CommandOutput {
+
+    let arguments = control.arguments(operation, pool_name);
+    let arguments: Vec<&str> = arguments.iter().map(|argument| argument.as_str()).collect();
+    CommandOutput::simulated("zpool", &arguments, result)
+}
+
+// "(25% trimmed, started at ...)" as `zpool status -t` and `-i` show it.
+fn task_note(task: Option<Task>, done: &str, duration: u64) -> Option<String> {
+
+    let task = task?;
+    if task.canceled.is_some() { return None }
+
+    let elapsed = task.elapsed().min(duration);
+    let percent = elapsed * 100 / duration;
+
+    Some(match task.paused {
+        _ if elapsed >= duration => format!("(100% {}, completed at {})", done, column::format_time((task.started + duration) as i64)),
+        Some(paused) => format!("({}% {}, suspended at {})", percent, done, column::format_time(paused as i64)),
+        None => format!("({}% {}, started at {})", percent, done, column::format_time(task.started as i64)),
+    })
+}
+
+fn resume_token(snapshot_name: &str) -> String {
+
+    let hash = hash(snapshot_name);
+    format!("1-{:x}-d8-789c636064000310a500c4ec50360710e72765a52697303028{:016x}", hash & 0xfffffffff, hash)
+}
+
+fn hash(text: &str) -> u64 {
+    text.bytes().fold(0xcbf29ce484222325u64, |hash, b| (hash ^ b as u64).wrapping_mul(0x100000001b3))
+}
+
+fn now() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+// Stable for the life of the snapshot, which is all a guid needs here.
+fn snapshot_guid(snapshot_name: &str) -> u64 {
+    hash(snapshot_name) >> 1
+}
+
+fn bookmark_dataset(name: &str) -> &str {
+    name.split('#').next().unwrap_or(name)
+}
+
+fn dataset_part(name: &str) -> &str {
+    name.split('@').next().unwrap_or(name)
+}
+
+fn parent_name(name: &str) -> Option<&str> {
+    name.rfind('/').map(|i| &name[..i])
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::dependency;
+    use crate::hold;
+
+    fn ok<T>(result: Result<T, CommandOutput>) -> T {
+        result.unwrap_or_else(|output| panic!("{}", output.error_message()))
+    }
+
+    fn names(result: ListResult) -> Vec<String> {
+        ok(result).into_iter().map(|result| result.name).collect()
+    }
+
+    #[test]
+    fn creates_snapshots_and_destroys() {
+
+        let fake = Fake::new();
+
+        assert!(fake.zfs_create("tank/scratch").success);
+        assert!(!fake.zfs_create("tank/scratch").success);
+        assert!(!fake.zfs_create("nopool/scratch").success);
+        assert!(names(fake.zfs_dataset()).contains(&"tank/scratch".to_string()));
+
+        assert!(fake.zfs_snapshot("tank/scratch@first").success);
+        assert!(names(fake.zfs_snapshots()).contains(&"tank/scratch@first".to_string()));
+        assert!(!fake.zfs_destroy("tank/scratch").success);
+
+        assert!(fake.zfs_destroy("tank/scratch@first").success);
+        assert!(fake.zfs_destroy("tank/scratch").success);
+        assert!(!names(fake.zfs_dataset()).contains(&"tank/scratch".to_string()));
+    }
+
+    #[test]
+    fn renames_and_clones() {
+
+        let fake = Fake::new();
+
+        assert!(fake.zfs_clone("tank/home@daily-1", "tank/restored").success);
+        assert!(!fake.zfs_clone("tank/home@missing", "tank/other").success);
+
+        assert!(fake.zfs_rename("tank/restored", "tank/renamed").success);
+        let datasets = names(fake.zfs_dataset());
+        assert!(datasets.contains(&"tank/renamed".to_string()));
+        assert!(!datasets.contains(&"tank/restored".to_string()));
+    }
+
+    #[test]
+    fn holds_block_destroy_until_released() {
+
+        let fake = Fake::new();
+        let snapshot = "tank/home@daily-1".to_string();
+
+        assert!(fake.zfs_hold("backup", &snapshot).success);
+        assert!(!fake.zfs_hold("backup", &snapshot).success);
+
+        let holds = ok(fake.zfs_holds(&[snapshot.to_owned()]));
+        assert_eq!(hold::tags(&holds, &snapshot), ["backup"]);
+        assert!(!fake.zfs_destroy(&snapshot).success);
+
+        assert!(fake.zfs_release("backup", &snapshot).success);
+        assert!(ok(fake.zfs_holds(&[snapshot.to_owned()])).is_empty());
+        assert!(fake.zfs_destroy(&snapshot).success);
+    }
+
+    #[test]
+    fn promote_swaps_a_clone_with_its_origin() {
+
+        let fake = Fake::new();
+
+        assert!(!fake.zfs_promote("tank/home").success);
+        assert!(fake.zfs_clone("tank/home@daily-3", "tank/clone").success);
+
+        let datasets = ok(fake.zfs_list_properties("filesystem,volume", &["origin".to_string()]));
+        assert_eq!(dependency::origin(&datasets, "tank/clone"), Some("tank/home@daily-3"));
+        assert!(!fake.zfs_destroy("tank/home@daily-3").success);
+
+        assert!(fake.zfs_promote("tank/clone").success);
+
+        let datasets = ok(fake.zfs_list_properties("filesystem,volume", &["origin".to_string()]));
+        assert_eq!(dependency::origin(&datasets, "tank/clone"), None);
+        assert_eq!(dependency::origin(&datasets, "tank/home"), Some("tank/clone@daily-3"));
+
+        let snapshots = names(fake.zfs_snapshots());
+        assert!(snapshots.contains(&"tank/clone@daily-1".to_string()));
+        assert!(!snapshots.contains(&"tank/home@daily-3".to_string()));
+        assert!(snapshots.contains(&"tank/home@daily-4".to_string()));
+    }
+}
@@ -0,0 +1,254 @@
+
+use std::collections::HashSet;
+use std::fs;
+use std::os::unix::fs::FileTypeExt;
+
+use crate::backend::Backend;
+
+// A directory entry, with the file type written as the `zfs diff -F` markers.
+#[derive(Clone)]
+pub struct Entry {
+
+    pub name: String,
+    pub file_type: char,
+    pub size: u64,
+    pub mode: u32,
+    pub modified: i64,
+}
+
+impl Entry {
+
+    pub fn is_directory(&self) -> bool {
+        self.file_type == '/'
+    }
+
+    fn parent() -> Entry {
+        Entry { name: "..".to_string(), file_type: '/', size: 0, mode: 0, modified: 0 }
+    }
+}
+
+pub fn file_type(file_type: &fs::FileType) -> char {
+
+    if file_type.is_dir() { '/' }
+    else if file_type.is_symlink() { '@' }
+    else if file_type.is_block_device() { 'B' }
+    else if file_type.is_char_device() { 'C' }
+    else if file_type.is_fifo() { '|' }
+    else if file_type.is_socket() { '=' }
+    else { 'F' }
+}
+
+// Directories first, then by name.
+pub fn sort(entries: &mut [Entry]) {
+    entries.sort_by(|a, b| b.is_directory().cmp(&a.is_directory()).then_with(|| a.name.cmp(&b.name)));
+}
+
+// "drwxr-xr-x" as shown by ls.
+pub fn format_mode(file_type: char, mode: u32) -> String {
+
+    let kind = match file_type {
+        '/' => 'd',
+        '@' => 'l',
+        'B' => 'b',
+        'C' => 'c',
+        '|' => 'p',
+        '=' => 's',
+        _ => '-',
+    };
+
+    let permissions: String = (0..9).map(|bit| {
+        if mode & (0o400 >> bit) == 0 { '-' } else { ['r', 'w', 'x'][bit % 3] }
+    }).collect();
+
+    format!("{}{}", kind, permissions)
+}
+
+pub fn join(directory: &str, name: &str) -> String {
+    format!("{}/{}", directory.trim_end_matches('/'), name)
+}
+
+// Where the contents of a snapshot are found under the mountpoint of its
+// filesystem, even when the snapdir property hides the .zfs directory.
+pub fn snapshot_root(mountpoint: &str, snapshot_name: &str) -> String {
+
+    let name = snapshot_name.split('@').nth(1).unwrap_or(snapshot_name);
+    join(join(mountpoint, ".zfs/snapshot").as_str(), name)
+}
+
+// Arguments of `cp` copying files or directories into a directory, keeping
+// the mode, ownership and timestamps (-p) and symbolic links as they are (-P).
+pub fn restore_arguments(sources: &[String], directory: &str) -> Vec<String> {
+
+    let mut arguments: Vec<String> = vec!["-pPR".to_string(), "--".to_string()];
+    arguments.extend(sources.iter().cloned());
+    arguments.push(directory.to_string());
+    arguments
+}
+
+// One side of the restore view: a directory that cannot be left above `root`,
+// its entries and the ones tagged for copying.
+pub struct Browser {
+
+    pub root: String,
+    pub path: String,
+    pub entries: Vec<Entry>,
+    pub tagged: HashSet<String>,
+    pub position: usize,
+    pub start_from: usize,
+}
+
+impl Browser {
+
+    pub fn new(root: &str) -> Browser {
+
+        Browser {
+            root: root.to_string(),
+            path: root.to_string(),
+            entries: Vec::new(),
+            tagged: HashSet::new(),
+            position: 0,
+            start_from: 0,
+        }
+    }
+
+    // The path below the root, "" at the root itself.
+    pub fn relative(&self) -> &str {
+        &self.path[self.root.len()..]
+    }
+
+    pub fn load(&mut self, backend: &dyn Backend) -> Result<(), String> {
+
+        let mut entries = backend.list_directory(self.path.as_str()).map_err(|output| output.error_message())?;
+        if self.path != self.root {
+            entries.insert(0, Entry::parent());
+        }
+
+        self.tagged.retain(|name| entries.iter().any(|entry| entry.name == *name));
+        self.entries = entries;
+        self.position = self.position.min(self.entries.len().saturating_sub(1));
+        Ok(())
+    }
+
+    // Moves into `relative` (below the root), staying where it was when the
+    // directory cannot be read.
+    pub fn open(&mut self, relative: &str, backend: &dyn Backend) -> Result<(), String> {
+
+        let previous = self.path.to_owned();
+        let child = previous.strip_prefix(format!("{}{}", self.root, relative).as_str())
+            .and_then(|rest| rest.trim_start_matches('/').split('/').next())
+            .map(|name| name.to_string());
+
+        self.path = format!("{}{}", self.root, relative);
+        self.tagged.clear();
+
+        if let Err(error) = self.load(backend) {
+            self.path = previous;
+            self.load(backend).ok();
+            return Err(error);
+        }
+
+        // Going up keeps the cursor on the directory just left.
+        self.position = child.and_then(|child| self.entries.iter().position(|entry| entry.name == child)).unwrap_or(0);
+        self.start_from = 0;
+        Ok(())
+    }
+
+    pub fn focused(&self) -> Option<&Entry> {
+        self.entries.get(self.position)
+    }
+
+    // The relative path the focused directory leads to.
+    pub fn target(&self) -> Option<String> {
+
+        let entry = self.focused().filter(|entry| entry.is_directory())?;
+
+        if entry.name == ".." {
+            let relative = self.relative();
+            Some(relative[..relative.rfind('/').unwrap_or(0)].to_string())
+        } else {
+            Some(format!("{}/{}", self.relative(), entry.name))
+        }
+    }
+
+    pub fn toggle_tag(&mut self) {
+
+        if let Some(entry) = self.focused().filter(|entry| entry.name != "..") {
+            let name = entry.name.to_owned();
+            if !self.tagged.remove(&name) {
+                self.tagged.insert(name);
+            }
+        }
+        self.position = (self.position + 1).min(self.entries.len().saturating_sub(1));
+    }
+
+    // The tagged entries, or the focused one when nothing is tagged.
+    pub fn selected(&self) -> Vec<&Entry> {
+
+        if self.tagged.is_empty() {
+            self.focused().filter(|entry| entry.name != "..").into_iter().collect()
+        } else {
+            self.entries.iter().filter(|entry| self.tagged.contains(&entry.name)).collect()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    fn entry(name: &str, file_type: char) -> Entry {
+        Entry { name: name.to_string(), file_type, size: 0, mode: 0o755, modified: 0 }
+    }
+
+    #[test]
+    fn targets_stay_below_the_root() {
+
+        let mut browser = Browser::new("/tank/.zfs/snapshot/daily-1");
+        browser.path = "/tank/.zfs/snapshot/daily-1/home/alice".to_string();
+        browser.entries = vec![Entry::parent(), entry("documents", '/'), entry("notes.txt", 'F')];
+
+        assert_eq!(browser.relative(), "/home/alice");
+        assert_eq!(browser.target().as_deref(), Some("/home"));
+
+        browser.position = 1;
+        assert_eq!(browser.target().as_deref(), Some("/home/alice/documents"));
+
+        browser.position = 2;
+        assert_eq!(browser.target(), None);
+
+        browser.path = "/tank/.zfs/snapshot/daily-1/home".to_string();
+        browser.position = 0;
+        assert_eq!(browser.target().as_deref(), Some(""));
+    }
+
+    #[test]
+    fn selects_tagged_entries_or_the_focused_one() {
+
+        let mut browser = Browser::new("/");
+        browser.entries = vec![Entry::parent(), entry("a", 'F'), entry("b", 'F')];
+
+        assert!(browser.selected().is_empty());
+
+        browser.position = 1;
+        assert_eq!(browser.selected()[0].name, "a");
+
+        browser.toggle_tag();
+        browser.toggle_tag();
+        let names: Vec<&str> = browser.selected().iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["a", "b"]);
+    }
+
+    #[test]
+    fn formats_paths_and_modes() {
+
+        let mut entries = vec![entry("z", 'F'), entry("b", '/'), entry("a", 'F')];
+        sort(&mut entries);
+        let names: Vec<&str> = entries.iter().map(|entry| entry.name.as_str()).collect();
+        assert_eq!(names, ["b", "a", "z"]);
+
+        assert_eq!(format_mode('/', 0o755), "drwxr-xr-x");
+        assert_eq!(format_mode('@', 0o777), "lrwxrwxrwx");
+        assert_eq!(snapshot_root("/tank/home/", "tank/home@daily-1"), "/tank/home/.zfs/snapshot/daily-1");
+    }
+}
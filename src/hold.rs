@@ -0,0 +1,57 @@
+
+// A user hold on a snapshot, as listed by `zfs holds`. A snapshot cannot be
+// destroyed while it has any.
+pub struct Hold {
+
+    pub snapshot: String,
+    pub tag: String,
+    pub time: i64,
+}
+
+// Parses `zfs holds -H -p`: the snapshot, the tag and the time the hold was
+// placed, separated by tabs.
+pub fn parse_holds(output: &str) -> Vec<Hold> {
+
+    output.lines().filter_map(|line| {
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 3 { return None }
+
+        Some(Hold {
+            snapshot: fields[0].to_string(),
+            tag: fields[1].to_string(),
+            time: fields[2].trim().parse().unwrap_or(0),
+        })
+    }).collect()
+}
+
+// The snapshots zfs counts user references on (the userrefs property).
+pub fn is_held(userrefs: &str) -> bool {
+    userrefs.parse::<u64>().unwrap_or(0) > 0
+}
+
+// The tags of a snapshot, in the order they were placed.
+pub fn tags(holds: &[Hold], snapshot_name: &str) -> Vec<String> {
+
+    let mut holds: Vec<&Hold> = holds.iter().filter(|hold| hold.snapshot == snapshot_name).collect();
+    holds.sort_by_key(|hold| hold.time);
+    holds.into_iter().map(|hold| hold.tag.to_owned()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parses_holds_in_order() {
+
+        let holds = parse_holds("tank@a\tkeep\t200\ntank@a\tbackup\t100\ntank@b\tkeep\t300\nbroken\n");
+
+        assert_eq!(holds.len(), 3);
+        assert_eq!(tags(&holds, "tank@a"), ["backup", "keep"]);
+        assert!(tags(&holds, "tank@c").is_empty());
+        assert!(is_held("2"));
+        assert!(!is_held("0"));
+        assert!(!is_held("-"));
+    }
+}
@@ -0,0 +1,242 @@
+
+use std::collections::{HashMap, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+
+use crate::column;
+
+// Seconds between two samples of `zpool iostat`.
+pub const INTERVAL: u64 = 2;
+
+// Samples kept for the sparklines: five minutes.
+pub const HISTORY: usize = 150;
+
+const SPARKS: &[char] = &[' ', '.', ':', '-', '=', '+', '*', '#', '%', '@'];
+
+#[derive(Clone, Default)]
+pub struct IoStat {
+
+    pub key: String,
+    pub name: String,
+    pub depth: usize,
+    pub read_ops: Option<u64>,
+    pub write_ops: Option<u64>,
+    pub read_bytes: Option<u64>,
+    pub write_bytes: Option<u64>,
+    pub read_wait: Option<u64>,
+    pub write_wait: Option<u64>,
+    pub disk_read_wait: Option<u64>,
+    pub disk_write_wait: Option<u64>,
+    pub sync_queue: Option<u64>,
+    pub async_queue: Option<u64>,
+}
+
+impl IoStat {
+
+    pub fn bandwidth(&self) -> u64 {
+        self.read_bytes.unwrap_or(0) + self.write_bytes.unwrap_or(0)
+    }
+}
+
+#[derive(Default)]
+struct Rows {
+    order: Vec<String>,
+    rows: HashMap<String, (IoStat, VecDeque<u64>)>,
+    error: Option<String>,
+}
+
+// The latest statistics of every pool and vdev with their bandwidth history,
+// filled by the thread following `zpool iostat` and read by the screen.
+pub struct Monitor {
+    rows: Mutex<Rows>,
+    stopped: AtomicBool,
+}
+
+impl Monitor {
+
+    pub fn new() -> Arc<Monitor> {
+        Arc::new(Monitor { rows: Mutex::new(Rows::default()), stopped: AtomicBool::new(false) })
+    }
+
+    pub fn update(&self, stat: IoStat) {
+
+        let mut rows = self.rows.lock().unwrap();
+
+        if !rows.rows.contains_key(&stat.key) {
+            rows.order.push(stat.key.to_owned());
+        }
+
+        let (current, history) = rows.rows.entry(stat.key.to_owned()).or_insert_with(|| (IoStat::default(), VecDeque::new()));
+
+        history.push_back(stat.bandwidth());
+        if history.len() > HISTORY { history.pop_front(); }
+
+        *current = stat;
+    }
+
+    pub fn fail(&self, error: String) {
+        self.rows.lock().unwrap().error = Some(error);
+    }
+
+    pub fn rows(&self) -> Vec<(IoStat, Vec<u64>)> {
+
+        let rows = self.rows.lock().unwrap();
+
+        rows.order.iter()
+            .filter_map(|key| rows.rows.get(key))
+            .map(|(stat, history)| (stat.clone(), history.iter().copied().collect()))
+            .collect()
+    }
+
+    pub fn error(&self) -> Option<String> {
+        self.rows.lock().unwrap().error.clone()
+    }
+
+    pub fn stop(&self) {
+        self.stopped.store(true, Ordering::Relaxed);
+    }
+
+    pub fn is_stopped(&self) -> bool {
+        self.stopped.load(Ordering::Relaxed)
+    }
+}
+
+// Parses a line of `zpool iostat -Hpv -l -q`: the name indented two spaces per
+// level, alloc and free, the operations and bandwidth, the latencies in
+// nanoseconds and the queue depths. OpenZFS 2 added a rebuild column to both
+// the latencies and the queues, so the queues are found from the end.
+// `pool` tracks the pool the following vdevs belong to.
+pub fn parse_line(line: &str, pool: &mut String) -> Option<IoStat> {
+
+    let fields: Vec<&str> = line.split('\t').collect();
+    if fields.len() < 7 { return None }
+
+    let name = fields[0].trim_start();
+    if name.is_empty() { return None }
+
+    let depth = (fields[0].len() - name.len()) / 2;
+    if depth == 0 && !["logs", "cache", "special", "dedup"].contains(&name) {
+        *pool = name.to_string();
+    }
+
+    let value = |index: usize| fields.get(index).and_then(|field| field.parse::<f64>().ok()).map(|value| value as u64);
+    let sum = |first: usize, second: usize| match (value(first), value(second)) {
+        (None, None) => None,
+        (first, second) => Some(first.unwrap_or(0) + second.unwrap_or(0)),
+    };
+
+    let queues = if fields.len() >= 32 { 18 } else { 17 };
+
+    Some(IoStat {
+        key: if depth == 0 && name == pool.as_str() { name.to_string() } else { format!("{}/{}", pool, name) },
+        name: name.to_string(),
+        depth,
+        read_ops: value(3),
+        write_ops: value(4),
+        read_bytes: value(5),
+        write_bytes: value(6),
+        read_wait: value(7),
+        write_wait: value(8),
+        disk_read_wait: value(9),
+        disk_write_wait: value(10),
+        sync_queue: sum(queues, queues + 2),
+        async_queue: sum(queues + 4, queues + 6),
+    })
+}
+
+// "1.25ms" from nanoseconds. Devices without any I/O report 0.
+pub fn format_latency(nanoseconds: Option<u64>) -> String {
+
+    match nanoseconds {
+        None | Some(0) => "-".to_string(),
+        Some(ns) if ns < 1_000 => format!("{}ns", ns),
+        Some(ns) if ns < 1_000_000 => format!("{}us", ns / 1_000),
+        Some(ns) if ns < 1_000_000_000 => format!("{:.1}ms", ns as f64 / 1e6),
+        Some(ns) => format!("{:.1}s", ns as f64 / 1e9),
+    }
+}
+
+pub fn format_count(count: Option<u64>) -> String {
+
+    match count {
+        None => "-".to_string(),
+        Some(count) if count < 10_000 => count.to_string(),
+        Some(count) => format!("{}K", count / 1_000),
+    }
+}
+
+pub fn format_bandwidth(bytes: Option<u64>) -> String {
+
+    match bytes {
+        None => "-".to_string(),
+        Some(bytes) => column::format_size(bytes),
+    }
+}
+
+// The last `width` values, scaled to the largest of them.
+pub fn sparkline(values: &[u64], width: usize) -> String {
+
+    let values = &values[values.len().saturating_sub(width)..];
+    let max = values.iter().copied().max().unwrap_or(0).max(1);
+
+    let line: String = values.iter()
+        .map(|value| SPARKS[((*value as f64 / max as f64) * (SPARKS.len() - 1) as f64).round() as usize])
+        .collect();
+
+    format!("{:>width$}", line, width = width)
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    // A row with the 17 columns up to the latencies and 12 queue columns.
+    fn line(name: &str, first: u64) -> String {
+        let values: Vec<String> = (first..first + 28).map(|value| value.to_string()).collect();
+        format!("{}\t{}", name, values.join("\t"))
+    }
+
+    #[test]
+    fn parses_pools_and_vdevs() {
+
+        let mut pool = String::new();
+
+        let stat = parse_line(line("tank", 1).as_str(), &mut pool).unwrap();
+        assert_eq!(stat.key, "tank");
+        assert_eq!(stat.depth, 0);
+        assert_eq!(stat.read_ops, Some(3));
+        assert_eq!(stat.write_bytes, Some(6));
+        assert_eq!(stat.disk_write_wait, Some(10));
+        assert_eq!(stat.sync_queue, Some(17 + 19));
+        assert_eq!(stat.async_queue, Some(21 + 23));
+
+        let stat = parse_line(line("  mirror-0", 1).as_str(), &mut pool).unwrap();
+        assert_eq!(stat.key, "tank/mirror-0");
+        assert_eq!(stat.depth, 1);
+
+        let stat = parse_line(line("logs", 1).as_str(), &mut pool).unwrap();
+        assert_eq!(stat.key, "tank/logs");
+        assert_eq!(pool, "tank");
+    }
+
+    #[test]
+    fn reads_dashes_as_missing() {
+
+        let mut pool = String::new();
+        let stat = parse_line("tank\t1\t2\t-\t-\t-\t-", &mut pool).unwrap();
+
+        assert_eq!(stat.read_ops, None);
+        assert_eq!(stat.sync_queue, None);
+        assert!(parse_line("tank\t1", &mut pool).is_none());
+    }
+
+    #[test]
+    fn formats_values() {
+
+        assert_eq!(format_latency(Some(0)), "-");
+        assert_eq!(format_latency(Some(1_250_000)), "1.2ms");
+        assert_eq!(format_count(Some(12_345)), "12K");
+        assert_eq!(sparkline(&[0, 9], 3), "  @");
+    }
+}
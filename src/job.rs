@@ -0,0 +1,355 @@
+
+use std::collections::{HashSet, VecDeque};
+use std::panic::{self, AssertUnwindSafe};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{mpsc, Arc, Mutex};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::backend::{Backend, SharedBackend};
+use crate::command::CommandOutput;
+
+const KEEP_FINISHED: usize = 100;
+
+pub type Task = Box<dyn FnOnce(&dyn Backend) -> Vec<CommandOutput> + Send>;
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum JobState {
+    Queued,
+    Running,
+    Succeeded,
+    Failed,
+}
+
+impl JobState {
+
+    pub fn text(&self) -> &'static str {
+        match self {
+            JobState::Queued    => "queued",
+            JobState::Running   => "running",
+            JobState::Succeeded => "done",
+            JobState::Failed    => "FAILED",
+        }
+    }
+}
+
+// Byte counters updated by long running jobs (zfs send) while they run.
+pub struct Progress {
+    done: AtomicU64,
+    total: AtomicU64,
+}
+
+impl Progress {
+
+    pub fn new(total: u64) -> Arc<Progress> {
+        Arc::new(Progress { done: AtomicU64::new(0), total: AtomicU64::new(total) })
+    }
+
+    pub fn add(&self, bytes: u64) {
+        self.done.fetch_add(bytes, Ordering::Relaxed);
+    }
+
+    pub fn done(&self) -> u64 {
+        self.done.load(Ordering::Relaxed)
+    }
+
+    pub fn total(&self) -> u64 {
+        self.total.load(Ordering::Relaxed)
+    }
+
+    pub fn percent(&self) -> Option<u64> {
+        match self.total() {
+            0 => None,
+            total => Some((self.done() * 100 / total).min(100)),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct Job {
+
+    pub id: usize,
+    pub description: String,
+    pub state: JobState,
+    pub started: Option<Instant>,
+    pub finished: Option<Instant>,
+    pub outputs: Vec<Arc<CommandOutput>>,
+    pub progress: Option<Arc<Progress>>,
+    queue: String,
+}
+
+impl Job {
+
+    pub fn duration(&self) -> Duration {
+        match (self.started, self.finished) {
+            (Some(started), Some(finished)) => finished - started,
+            (Some(started), None) => started.elapsed(),
+            _ => Duration::from_secs(0),
+        }
+    }
+
+    pub fn failures(&self) -> Vec<Arc<CommandOutput>> {
+        self.outputs.iter().filter(|output| !output.success).cloned().collect()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.state == JobState::Succeeded || self.state == JobState::Failed
+    }
+}
+
+struct Inner {
+    jobs: Vec<Job>,
+    pending: VecDeque<(usize, Task)>,
+    busy: HashSet<String>,
+    next_id: usize,
+}
+
+#[derive(Clone)]
+pub struct Jobs {
+    inner: Arc<Mutex<Inner>>,
+    backend: SharedBackend,
+    notifier: mpsc::Sender<usize>,
+}
+
+impl Jobs {
+
+    pub fn new(backend: SharedBackend) -> (Jobs, mpsc::Receiver<usize>) {
+
+        let (notifier, notifications) = mpsc::channel();
+
+        let inner = Inner {
+            jobs: Vec::new(),
+            pending: VecDeque::new(),
+            busy: HashSet::new(),
+            next_id: 1,
+        };
+
+        let jobs = Jobs {
+            inner: Arc::new(Mutex::new(inner)),
+            backend,
+            notifier,
+        };
+
+        (jobs, notifications)
+    }
+
+    // Jobs sharing a queue (usually the pool name) run one after the other in
+    // submission order; jobs on different queues run concurrently.
+    pub fn submit<F>(&self, queue: String, description: String, task: F) -> usize
+    where F: FnOnce(&dyn Backend) -> Vec<CommandOutput> + Send + 'static {
+        self.submit_with_progress(queue, description, None, task)
+    }
+
+    pub fn submit_with_progress<F>(&self, queue: String, description: String, progress: Option<Arc<Progress>>, task: F) -> usize
+    where F: FnOnce(&dyn Backend) -> Vec<CommandOutput> + Send + 'static {
+
+        let id = {
+            let mut inner = self.inner.lock().unwrap();
+
+            let id = inner.next_id;
+            inner.next_id += 1;
+
+            inner.jobs.push(Job {
+                id,
+                description,
+                state: JobState::Queued,
+                started: None,
+                finished: None,
+                outputs: Vec::new(),
+                progress,
+                queue,
+            });
+
+            inner.pending.push_back((id, Box::new(task)));
+            id
+        };
+
+        self.schedule();
+        id
+    }
+
+    pub fn get(&self, id: usize) -> Option<Job> {
+        self.inner.lock().unwrap().jobs.iter().find(|job| job.id == id).cloned()
+    }
+
+    pub fn list(&self) -> Vec<Job> {
+        self.inner.lock().unwrap().jobs.clone()
+    }
+
+    pub fn active(&self) -> usize {
+        self.inner.lock().unwrap().jobs.iter().filter(|job| !job.is_finished()).count()
+    }
+
+    pub fn clear_finished(&self) {
+        self.inner.lock().unwrap().jobs.retain(|job| !job.is_finished());
+    }
+
+    fn schedule(&self) {
+
+        let mut inner = self.inner.lock().unwrap();
+
+        let mut index = 0;
+        while index < inner.pending.len() {
+
+            let id = inner.pending[index].0;
+            let queue = match inner.jobs.iter().find(|job| job.id == id) {
+                Some(job) => job.queue.to_owned(),
+                None => String::new(),
+            };
+
+            if inner.busy.contains(&queue) {
+                index += 1;
+                continue;
+            }
+
+            let (id, task) = inner.pending.remove(index).unwrap();
+            inner.busy.insert(queue.to_owned());
+
+            let mut description = String::new();
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.state = JobState::Running;
+                job.started = Some(Instant::now());
+                description = job.description.to_owned();
+            }
+
+            // A task that panics still has to free its queue, or every later
+            // job of the pool would wait forever.
+            let jobs = self.clone();
+            thread::spawn(move || {
+                let outputs = panic::catch_unwind(AssertUnwindSafe(|| task(jobs.backend.as_ref())))
+                    .unwrap_or_else(|payload| vec![panicked(description.as_str(), payload.as_ref())]);
+                jobs.finish(id, queue, outputs);
+            });
+        }
+    }
+
+    fn finish(&self, id: usize, queue: String, outputs: Vec<CommandOutput>) {
+
+        {
+            let mut inner = self.inner.lock().unwrap();
+            inner.busy.remove(&queue);
+
+            if let Some(job) = inner.jobs.iter_mut().find(|job| job.id == id) {
+                job.state = if outputs.iter().all(|output| output.success) { JobState::Succeeded } else { JobState::Failed };
+                job.finished = Some(Instant::now());
+                job.outputs = outputs.into_iter().map(Arc::new).collect();
+            }
+
+            let finished = inner.jobs.iter().filter(|job| job.is_finished()).count();
+            if finished > KEEP_FINISHED {
+                let mut excess = finished - KEEP_FINISHED;
+                inner.jobs.retain(|job| {
+                    if excess > 0 && job.is_finished() { excess -= 1; false } else { true }
+                });
+            }
+        }
+
+        let _ = self.notifier.send(id);
+        self.schedule();
+    }
+}
+
+// The failed output of a task that panicked, with the panic message.
+fn panicked(description: &str, payload: &(dyn std::any::Any + Send)) -> CommandOutput {
+
+    let message = payload.downcast_ref::<&str>().map(|message| message.to_string())
+        .or_else(|| payload.downcast_ref::<String>().cloned())
+        .unwrap_or_else(|| "unknown error".to_string());
+
+    CommandOutput {
+        command: description.to_string(),
+        success: false,
+        code: None,
+        stdout: String::new(),
+        stderr: format!("internal error: {}", message),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::fake::Fake;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn jobs() -> (Jobs, mpsc::Receiver<usize>) {
+        Jobs::new(Arc::new(Fake::new()))
+    }
+
+    #[test]
+    fn runs_jobs_of_one_queue_in_order() {
+
+        let (jobs, notifications) = jobs();
+        let (release, released) = mpsc::channel::<()>();
+
+        let first = jobs.submit("tank".to_string(), "first".to_string(), move |zfs| {
+            released.recv().unwrap();
+            vec![zfs.zfs_create("tank/first")]
+        });
+        let second = jobs.submit("tank".to_string(), "second".to_string(), |zfs| vec![zfs.zfs_create("tank/second")]);
+        let other = jobs.submit("backup".to_string(), "other".to_string(), |zfs| vec![zfs.zfs_create("backup/other")]);
+
+        // The other pool does not wait for tank.
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(other));
+        assert!(jobs.get(first).unwrap().state == JobState::Running);
+        assert!(jobs.get(second).unwrap().state == JobState::Queued);
+        assert_eq!(jobs.active(), 2);
+
+        release.send(()).unwrap();
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(first));
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(second));
+        assert_eq!(jobs.active(), 0);
+    }
+
+    #[test]
+    fn reports_failed_commands() {
+
+        let (jobs, notifications) = jobs();
+
+        let id = jobs.submit("tank".to_string(), "destroy".to_string(), |zfs| vec![zfs.zfs_destroy("tank/missing")]);
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(id));
+
+        let job = jobs.get(id).unwrap();
+        assert!(job.state == JobState::Failed);
+        assert_eq!(job.failures().len(), 1);
+
+        jobs.clear_finished();
+        assert!(jobs.list().is_empty());
+    }
+
+    #[test]
+    fn keeps_the_latest_finished_jobs() {
+
+        let (jobs, notifications) = jobs();
+
+        for i in 0..KEEP_FINISHED + 5 {
+            jobs.submit(format!("pool{}", i), format!("job {}", i), |_| Vec::new());
+        }
+        for _ in 0..KEEP_FINISHED + 5 {
+            notifications.recv_timeout(TIMEOUT).unwrap();
+        }
+
+        let list = jobs.list();
+        assert_eq!(list.len(), KEEP_FINISHED);
+        assert!(list.iter().all(|job| job.state == JobState::Succeeded));
+    }
+
+    #[test]
+    fn fails_panicking_jobs_and_frees_their_queue() {
+
+        let (jobs, notifications) = jobs();
+
+        let panicking = jobs.submit("tank".to_string(), "panic".to_string(), |_| panic!("bad index"));
+        let next = jobs.submit("tank".to_string(), "next".to_string(), |zfs| vec![zfs.zfs_create("tank/next")]);
+
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(panicking));
+        let job = jobs.get(panicking).unwrap();
+        assert!(job.state == JobState::Failed);
+        assert_eq!(job.outputs[0].command, "panic");
+        assert_eq!(job.outputs[0].stderr, "internal error: bad index");
+
+        assert_eq!(notifications.recv_timeout(TIMEOUT), Ok(next));
+        assert!(jobs.get(next).unwrap().state == JobState::Succeeded);
+    }
+}
@@ -1,14 +1,63 @@
 
-mod display;
+mod backend;
+mod column;
 mod command;
+mod dependency;
+mod diff;
+mod display;
+mod fake;
+mod files;
+mod hold;
+mod iostat;
+mod job;
+mod pattern;
+mod pool;
+mod property;
+mod replication;
+mod retention;
+mod schedule;
+mod status;
+
+use std::env;
+use std::sync::Arc;
 
 fn main() {
 
-    if command::is_zfs_installed() {
-        let mut screen = display::Screen::new();
-        screen.run()
+    let arguments: Vec<String> = env::args().skip(1).collect();
+    let has = |flag: &str| arguments.iter().any(|argument| argument == flag);
+
+    let use_fake = has("--fake");
+
+    let backend: backend::SharedBackend = if use_fake {
+        Arc::new(fake::Fake::new())
+
+    } else if command::is_zfs_installed() {
+        Arc::new(command::Cli)
 
     } else {
-        println!("ZFS is not installed")
+        println!("ZFS is not installed (run with --fake to use simulated pools)");
+        return;
+    };
+
+    // The simulated pools get a schedule of their own, so trying zc never
+    // touches the real one.
+    let schedule_path = match arguments.iter().position(|argument| argument == "--config") {
+        Some(position) => match arguments.get(position + 1) {
+            Some(path) => path.to_owned(),
+            None => {
+                println!("--config needs the path of the schedule file");
+                return;
+            },
+        },
+        None if use_fake => env::temp_dir().join("zc-schedule.conf").to_string_lossy().to_string(),
+        None => schedule::CONFIG_PATH.to_string(),
     };
+
+    if has("--scheduler") {
+        schedule::run_scheduler(backend.as_ref(), schedule_path.as_str(), has("--once"), has("--prune"));
+        return;
+    }
+
+    let mut screen = display::Screen::new(backend, schedule_path);
+    screen.run()
 }
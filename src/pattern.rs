@@ -0,0 +1,157 @@
+
+use regex::Regex;
+
+// Shell-style wildcard matching: '*' matches any sequence, '?' any single
+// character and '[...]' a character class (with '!' or '^' to negate).
+pub fn glob_match(pattern: &str, text: &str) -> bool {
+
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+
+    let mut p = 0;
+    let mut t = 0;
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+
+        if p < pattern.len() {
+            match pattern[p] {
+                '*' => {
+                    backtrack = Some((p, t));
+                    p += 1;
+                    continue;
+                },
+                '?' => {
+                    p += 1;
+                    t += 1;
+                    continue;
+                },
+                '[' => {
+                    if let Some((matched, next)) = match_class(&pattern, p, text[t]) {
+                        if matched {
+                            p = next;
+                            t += 1;
+                            continue;
+                        }
+                    } else if text[t] == '[' {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                },
+                c => {
+                    if c == text[t] {
+                        p += 1;
+                        t += 1;
+                        continue;
+                    }
+                },
+            }
+        }
+
+        match backtrack {
+            Some((star, position)) => {
+                p = star + 1;
+                t = position + 1;
+                backtrack = Some((star, position + 1));
+            },
+            None => return false,
+        }
+    }
+
+    pattern[p..].iter().all(|c| *c == '*')
+}
+
+fn match_class(pattern: &[char], start: usize, c: char) -> Option<(bool, usize)> {
+
+    let mut i = start + 1;
+    let negate = i < pattern.len() && (pattern[i] == '!' || pattern[i] == '^');
+    if negate { i += 1; }
+
+    let mut matched = false;
+    let mut first = true;
+
+    while i < pattern.len() && (first || pattern[i] != ']') {
+        first = false;
+
+        if i + 2 < pattern.len() && pattern[i + 1] == '-' && pattern[i + 2] != ']' {
+            if pattern[i] <= c && c <= pattern[i + 2] { matched = true; }
+            i += 3;
+        } else {
+            if pattern[i] == c { matched = true; }
+            i += 1;
+        }
+    }
+
+    if i >= pattern.len() {
+        return None;
+    }
+
+    Some((matched != negate, i + 1))
+}
+
+pub enum Filter {
+    Substring(String),
+    Glob(String),
+    Regex(Regex),
+}
+
+impl Filter {
+
+    // "/expr/" is a regular expression, text with '*', '?' or '[' a wildcard
+    // pattern, anything else a case-insensitive substring.
+    pub fn parse(expression: &str) -> Result<Filter, String> {
+
+        if expression.len() >= 2 && expression.starts_with('/') && expression.ends_with('/') {
+            let expression = &expression[1..expression.len() - 1];
+            return match Regex::new(expression) {
+                Ok(regex) => Ok(Filter::Regex(regex)),
+                Err(error) => Err(error.to_string()),
+            };
+        }
+
+        if expression.contains(['*', '?', '[']) {
+            Ok(Filter::Glob(expression.to_string()))
+        } else {
+            Ok(Filter::Substring(expression.to_lowercase()))
+        }
+    }
+
+    pub fn matches(&self, text: &str) -> bool {
+        match self {
+            Filter::Substring(substring) => text.to_lowercase().contains(substring.as_str()),
+            Filter::Glob(pattern) => glob_match(pattern, text),
+            Filter::Regex(regex) => regex.is_match(text),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn matches_wildcards() {
+
+        assert!(glob_match("tank/*", "tank/home/alice"));
+        assert!(glob_match("*@daily-?", "tank/home@daily-7"));
+        assert!(!glob_match("*@daily-?", "tank/home@daily-10"));
+        assert!(glob_match("zstd-[1-9]", "zstd-3"));
+        assert!(!glob_match("zstd-[!1-9]", "zstd-3"));
+        assert!(glob_match("a[^b]c", "axc"));
+        assert!(glob_match("[abc", "[abc"));
+        assert!(glob_match("**", ""));
+        assert!(!glob_match("a*b", "acbd"));
+    }
+
+    #[test]
+    fn parses_filters() {
+
+        assert!(Filter::parse("HOME").unwrap().matches("tank/home"));
+        assert!(Filter::parse("tank/*/alice").unwrap().matches("tank/home/alice"));
+        assert!(Filter::parse("/^tank/h/").unwrap().matches("tank/home"));
+        assert!(!Filter::parse("/^tank/h/").unwrap().matches("backup/tank/home"));
+        assert!(Filter::parse("/(/").is_err());
+    }
+}
@@ -0,0 +1,244 @@
+
+pub const TOPOLOGIES: &[&str] = &["stripe", "mirror", "raidz1", "raidz2", "raidz3", "draid1", "draid2", "draid3"];
+
+// Properties offered when creating a pool: (property, value, pool property).
+pub const CREATE_PROPERTIES: &[(&str, &str, bool)] = &[
+    ("compression", "lz4", false),
+    ("atime", "off", false),
+    ("xattr", "sa", false),
+    ("acltype", "posixacl", false),
+    ("autotrim", "on", true),
+    ("autoexpand", "on", true),
+];
+
+// Starting a paused scrub again resumes it, and the same goes for a
+// suspended trim or initialize.
+#[derive(Clone, Copy, PartialEq)]
+pub enum Control {
+    Start,
+    Pause,
+    Cancel,
+}
+
+impl Control {
+
+    // `zpool scrub` pauses with -p and cancels with -s, while `zpool trim`
+    // and `zpool initialize` suspend with -s and cancel with -c.
+    pub fn arguments(self, operation: &str, pool_name: &str) -> Vec<String> {
+
+        let flag = match (self, operation) {
+            (Control::Start, _) => None,
+            (Control::Pause, "scrub") => Some("-p"),
+            (Control::Pause, _) => Some("-s"),
+            (Control::Cancel, "scrub") => Some("-s"),
+            (Control::Cancel, _) => Some("-c"),
+        };
+
+        let mut arguments = vec![operation.to_string()];
+        arguments.extend(flag.map(|flag| flag.to_string()));
+        arguments.push(pool_name.to_string());
+        arguments
+    }
+
+    pub fn description(self, operation: &str, pool_name: &str) -> String {
+
+        match self {
+            Control::Start => format!("{} {}", operation, pool_name),
+            Control::Pause if operation == "scrub" => format!("pause scrub of {}", pool_name),
+            Control::Pause => format!("suspend {} of {}", operation, pool_name),
+            Control::Cancel => format!("cancel {} of {}", operation, pool_name),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct BlockDevice {
+
+    pub path: String,
+    pub size: u64,
+}
+
+#[derive(Clone)]
+pub struct CreateOptions {
+
+    pub name: String,
+    pub topology: String,
+    pub groups: usize,
+    pub devices: Vec<String>,
+    pub ashift: Option<u32>,
+    pub pool_properties: Vec<(String, String)>,
+    pub filesystem_properties: Vec<(String, String)>,
+}
+
+impl CreateOptions {
+
+    pub fn new(name: &str) -> CreateOptions {
+
+        CreateOptions {
+            name: name.to_string(),
+            topology: "stripe".to_string(),
+            groups: 1,
+            devices: Vec::new(),
+            ashift: None,
+            pool_properties: Vec::new(),
+            filesystem_properties: Vec::new(),
+        }
+    }
+
+    // The vdev specification: the devices split in `groups` vdevs of the
+    // chosen type, or listed on their own for a stripe.
+    pub fn vdev_arguments(&self) -> Vec<String> {
+
+        if self.topology == "stripe" {
+            return self.devices.clone();
+        }
+
+        let width = (self.devices.len() / self.groups.max(1)).max(1);
+        let mut arguments = Vec::new();
+
+        for group in self.devices.chunks(width) {
+            arguments.push(self.topology.to_owned());
+            arguments.extend(group.iter().cloned());
+        }
+
+        arguments
+    }
+
+    pub fn arguments(&self, dry_run: bool) -> Vec<String> {
+
+        let mut arguments = vec!["create".to_string()];
+
+        if dry_run { arguments.push("-n".to_string()); }
+
+        if let Some(ashift) = self.ashift {
+            arguments.push("-o".to_string());
+            arguments.push(format!("ashift={}", ashift));
+        }
+
+        for (property, value) in self.pool_properties.iter() {
+            arguments.push("-o".to_string());
+            arguments.push(format!("{}={}", property, value));
+        }
+
+        for (property, value) in self.filesystem_properties.iter() {
+            arguments.push("-O".to_string());
+            arguments.push(format!("{}={}", property, value));
+        }
+
+        arguments.push(self.name.to_owned());
+        arguments.extend(self.vdev_arguments());
+        arguments
+    }
+
+    pub fn description(&self) -> String {
+
+        let layout = if self.groups > 1 { format!("{} x {}", self.groups, self.topology) } else { self.topology.to_owned() };
+        format!("create pool {} ({}, {} devices)", self.name, layout, self.devices.len())
+    }
+}
+
+// The fewest devices a single vdev of the topology accepts.
+pub fn minimum_devices(topology: &str) -> usize {
+
+    match topology {
+        "mirror" | "raidz1" => 2,
+        "raidz2" => 3,
+        "raidz3" => 4,
+        "draid1" => 3,
+        "draid2" => 4,
+        "draid3" => 5,
+        _ => 1,
+    }
+}
+
+// The ways `count` devices can be split in equal vdevs of the topology, as
+// the number of vdevs. dRAID spreads all devices over a single vdev.
+pub fn group_counts(topology: &str, count: usize) -> Vec<usize> {
+
+    if topology == "stripe" || topology.starts_with("draid") {
+        return if count >= minimum_devices(topology) { vec![1] } else { Vec::new() };
+    }
+
+    (1..=count)
+        .filter(|groups| count.is_multiple_of(*groups) && count / groups >= minimum_devices(topology))
+        .collect()
+}
+
+#[derive(Clone)]
+pub struct ImportOptions {
+
+    pub pool: String,
+    pub new_name: Option<String>,
+    pub directories: Vec<String>,
+    pub destroyed: bool,
+    pub readonly: bool,
+    pub altroot: Option<String>,
+    pub force: bool,
+}
+
+impl ImportOptions {
+
+    // `pool` is the name or the numeric id shown by `zpool import`.
+    pub fn new(pool: &str) -> ImportOptions {
+
+        ImportOptions {
+            pool: pool.to_string(),
+            new_name: None,
+            directories: Vec::new(),
+            destroyed: false,
+            readonly: false,
+            altroot: None,
+            force: false,
+        }
+    }
+
+    pub fn search_arguments(directories: &[String], destroyed: bool) -> Vec<String> {
+
+        let mut arguments = vec!["import".to_string()];
+
+        for directory in directories {
+            arguments.push("-d".to_string());
+            arguments.push(directory.to_owned());
+        }
+
+        if destroyed { arguments.push("-D".to_string()); }
+        arguments
+    }
+
+    pub fn arguments(&self) -> Vec<String> {
+
+        let mut arguments = ImportOptions::search_arguments(&self.directories, self.destroyed);
+
+        if self.force { arguments.push("-f".to_string()); }
+
+        if self.readonly {
+            arguments.push("-o".to_string());
+            arguments.push("readonly=on".to_string());
+        }
+
+        if let Some(altroot) = &self.altroot {
+            arguments.push("-R".to_string());
+            arguments.push(altroot.to_owned());
+        }
+
+        arguments.push(self.pool.to_owned());
+
+        if let Some(new_name) = &self.new_name {
+            arguments.push(new_name.to_owned());
+        }
+
+        arguments
+    }
+
+    pub fn imported_name(&self) -> &str {
+        self.new_name.as_deref().unwrap_or(self.pool.as_str())
+    }
+
+    pub fn description(&self) -> String {
+
+        match &self.new_name {
+            Some(new_name) => format!("import {} as {}", self.pool, new_name),
+            None => format!("import {}", self.pool),
+        }
+    }
+}
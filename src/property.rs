@@ -0,0 +1,207 @@
+
+use crate::column::{self, Format};
+use crate::pattern::glob_match;
+
+#[derive(Clone)]
+pub struct Property {
+
+    pub name: String,
+    pub value: String,
+    pub source: String,
+}
+
+impl Property {
+
+    pub fn new(name: &str, value: &str, source: &str) -> Property {
+
+        Property {
+            name: name.to_string(),
+            value: value.to_string(),
+            source: source.to_string(),
+        }
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.source == "-"
+    }
+
+    pub fn is_local(&self) -> bool {
+        self.source == "local"
+    }
+
+    pub fn display_value(&self) -> String {
+
+        let property = self.name.as_str();
+
+        if property == "creation" {
+            column::format_value(self.value.as_str(), Format::Time)
+        } else if (SIZE_PROPERTIES.contains(&property) || BYTE_PROPERTIES.contains(&property)) && self.value != "0" {
+            column::format_value(self.value.as_str(), Format::Bytes)
+        } else {
+            self.value.to_owned()
+        }
+    }
+}
+
+const ON_OFF: &[&str] = &["on", "off"];
+
+const COMPRESSION: &[&str] = &[
+    "on", "off", "lzjb", "gzip", "gzip-[1-9]", "zle", "lz4", "zstd", "zstd-[1-9]", "zstd-1[0-9]",
+    "zstd-fast", "zstd-fast-[1-9]", "zstd-fast-[1-9]0", "zstd-fast-[1-9]00", "zstd-fast-1000",
+];
+
+const CHECKSUM: &[&str] = &["on", "off", "fletcher2", "fletcher4", "sha256", "sha512", "skein", "edonr", "blake3", "noparity"];
+
+const DATASET_VALUES: &[(&str, &[&str])] = &[
+    ("aclinherit",        &["discard", "noallow", "restricted", "passthrough", "passthrough-x"]),
+    ("acltype",           &["off", "nfsv4", "posix", "noacl", "posixacl"]),
+    ("atime",             ON_OFF),
+    ("canmount",          &["on", "off", "noauto"]),
+    ("checksum",          CHECKSUM),
+    ("compression",       COMPRESSION),
+    ("copies",            &["1", "2", "3"]),
+    ("dedup",             &["on", "off", "verify", "sha256", "sha256,verify", "sha512", "sha512,verify", "skein", "skein,verify", "edonr,verify", "blake3", "blake3,verify"]),
+    ("devices",           ON_OFF),
+    ("dnodesize",         &["legacy", "auto", "1k", "2k", "4k", "8k", "16k"]),
+    ("exec",              ON_OFF),
+    ("logbias",           &["latency", "throughput"]),
+    ("overlay",           ON_OFF),
+    ("primarycache",      &["all", "none", "metadata"]),
+    ("readonly",          ON_OFF),
+    ("redundant_metadata", &["all", "most", "some", "none"]),
+    ("relatime",          ON_OFF),
+    ("secondarycache",    &["all", "none", "metadata"]),
+    ("setuid",            ON_OFF),
+    ("snapdev",           &["hidden", "visible"]),
+    ("snapdir",           &["hidden", "visible"]),
+    ("sync",              &["standard", "always", "disabled"]),
+    ("volmode",           &["default", "full", "geom", "dev", "none"]),
+    ("vscan",             ON_OFF),
+    ("xattr",             &["on", "off", "sa", "dir"]),
+    ("zoned",             ON_OFF),
+];
+
+const POOL_VALUES: &[(&str, &[&str])] = &[
+    ("autoexpand",    ON_OFF),
+    ("autoreplace",   ON_OFF),
+    ("autotrim",      ON_OFF),
+    ("delegation",    ON_OFF),
+    ("failmode",      &["wait", "continue", "panic"]),
+    ("listsnapshots", ON_OFF),
+    ("multihost",     ON_OFF),
+];
+
+const SIZE_PROPERTIES: &[&str] = &[
+    "quota", "refquota", "reservation", "refreservation", "recordsize", "volsize", "special_small_blocks",
+];
+
+const BYTE_PROPERTIES: &[&str] = &[
+    "allocated", "available", "free", "freeing", "logicalreferenced", "logicalused", "referenced", "size", "used",
+    "usedbychildren", "usedbydataset", "usedbyrefreservation", "usedbysnapshots", "volblocksize", "written",
+];
+
+// Checks the value of a known property before running zfs/zpool set, so a
+// typo shows the accepted values instead of a failed job.
+pub fn validate(property: &str, value: &str, is_pool: bool) -> Result<(), String> {
+
+    if value.is_empty() {
+        return Err(format!("'{}' cannot be empty", property));
+    }
+
+    let values = if is_pool { POOL_VALUES } else { DATASET_VALUES };
+
+    if let Some((_, allowed)) = values.iter().find(|(name, _)| *name == property) {
+        if allowed.iter().any(|pattern| glob_match(pattern, value)) {
+            return Ok(());
+        }
+        return Err(format!("'{}' must be one of '{}'", property, allowed.join(" | ")));
+    }
+
+    if !is_pool && SIZE_PROPERTIES.contains(&property) && parse_size(value).is_none() && value != "none" && value != "auto" {
+        return Err(format!("bad numeric value '{}' for '{}'", value, property));
+    }
+
+    Ok(())
+}
+
+// Accepts plain byte counts and the zfs suffixes (10G, 1.5T, 128K, ...).
+pub fn parse_size(value: &str) -> Option<u64> {
+
+    const SUFFIXES: &str = "BKMGTPE";
+
+    let value = value.trim_end_matches(['b', 'B']);
+    let (number, exponent) = match value.chars().last() {
+        Some(c) if c.is_ascii_alphabetic() => match SUFFIXES.find(c.to_ascii_uppercase()) {
+            Some(exponent) => (&value[..value.len() - 1], exponent as i32),
+            None => return None,
+        },
+        Some(_) => (value, 0),
+        None => return Some(0),
+    };
+
+    match number.parse::<f64>() {
+        Ok(number) if number >= 0.0 => Some((number * 1024f64.powi(exponent)) as u64),
+        _ => None,
+    }
+}
+
+// Parses the output of `zfs get all -H -p` / `zpool get all -H -p`, whose
+// lines are "name<TAB>property<TAB>value<TAB>source".
+pub fn parse_properties(output: &str) -> Vec<Property> {
+
+    let mut properties = Vec::new();
+
+    for line in output.lines() {
+
+        let fields: Vec<&str> = line.split('\t').collect();
+        if fields.len() < 4 { continue }
+
+        properties.push(Property::new(fields[1], fields[2], fields[3]));
+    }
+
+    properties
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    #[test]
+    fn parses_sizes() {
+
+        assert_eq!(parse_size("4096"), Some(4096));
+        assert_eq!(parse_size("128K"), Some(128 * 1024));
+        assert_eq!(parse_size("1.5g"), Some(3 * 512 * 1024 * 1024));
+        assert_eq!(parse_size("10GB"), Some(10 * 1024 * 1024 * 1024));
+        assert_eq!(parse_size(""), Some(0));
+        assert_eq!(parse_size("10X"), None);
+        assert_eq!(parse_size("-1"), None);
+    }
+
+    #[test]
+    fn validates_known_values() {
+
+        assert!(validate("compression", "zstd-19", false).is_ok());
+        assert!(validate("compression", "zstd-fast-500", false).is_ok());
+        assert!(validate("compression", "brotli", false).is_err());
+        assert!(validate("atime", "", false).is_err());
+        assert!(validate("quota", "10G", false).is_ok());
+        assert!(validate("quota", "none", false).is_ok());
+        assert!(validate("quota", "lots", false).is_err());
+        assert!(validate("failmode", "panic", true).is_ok());
+        assert!(validate("autotrim", "maybe", true).is_err());
+        assert!(validate("com.example:note", "anything", false).is_ok());
+    }
+
+    #[test]
+    fn parses_get_output() {
+
+        let properties = parse_properties("tank\tused\t1024\t-\ntank\tatime\toff\tlocal\nshort\tline\n");
+
+        assert_eq!(properties.len(), 2);
+        assert!(properties[0].is_readonly());
+        assert!(properties[1].is_local());
+        assert_eq!(properties[1].display_value(), "off");
+    }
+}
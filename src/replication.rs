@@ -0,0 +1,210 @@
+
+#[derive(Clone)]
+pub enum Target {
+    Dataset(String),
+    File(String),
+}
+
+impl Target {
+
+    // The target the wizard asked for: a stream file, or a dataset to receive
+    // into, which has to be on one of the imported pools.
+    pub fn parse(target: &str, file: bool, pools: &[String]) -> Result<Target, String> {
+
+        if file {
+            return Ok(Target::File(target.to_string()));
+        }
+
+        let pool = target.split('/').next().unwrap_or(target);
+        if !pools.iter().any(|name| name == pool) {
+            return Err(format!("There is no pool '{}' to receive into.", pool));
+        }
+
+        Ok(Target::Dataset(target.to_string()))
+    }
+
+    pub fn name(&self) -> &str {
+        match self {
+            Target::Dataset(name) => name.as_str(),
+            Target::File(path) => path.as_str(),
+        }
+    }
+}
+
+#[derive(Clone)]
+pub struct SendOptions {
+
+    pub snapshot: String,
+    pub token: Option<String>,
+    pub base: Option<String>,
+    pub intermediate: bool,
+    pub target: Target,
+    pub raw: bool,
+    pub compressed: bool,
+    pub properties: bool,
+    pub recursive: bool,
+    pub resumable: bool,
+    pub force: bool,
+}
+
+impl SendOptions {
+
+    pub fn new(snapshot: &str, target: Target) -> SendOptions {
+
+        SendOptions {
+            snapshot: snapshot.to_string(),
+            token: None,
+            base: None,
+            intermediate: false,
+            target,
+            raw: false,
+            compressed: false,
+            properties: false,
+            recursive: false,
+            resumable: false,
+            force: false,
+        }
+    }
+
+    // Continues an interrupted "receive -s" into the dataset holding the
+    // receive_resume_token.
+    pub fn resume(token: &str, dataset_name: &str) -> SendOptions {
+
+        let mut send = SendOptions::new("", Target::Dataset(dataset_name.to_string()));
+        send.token = Some(token.to_string());
+        send.resumable = true;
+        send
+    }
+
+    pub fn send_arguments(&self) -> Vec<String> {
+
+        let mut arguments = vec!["send".to_string()];
+
+        if let Some(token) = &self.token {
+            arguments.push("-t".to_string());
+            arguments.push(token.to_owned());
+            return arguments;
+        }
+
+        if self.raw        { arguments.push("-w".to_string()); }
+        if self.compressed { arguments.push("-c".to_string()); }
+        if self.properties { arguments.push("-p".to_string()); }
+        if self.recursive  { arguments.push("-R".to_string()); }
+
+        if let Some(base) = &self.base {
+            arguments.push(if self.intermediate { "-I" } else { "-i" }.to_string());
+            arguments.push(base.to_owned());
+        }
+
+        arguments.push(self.snapshot.to_owned());
+        arguments
+    }
+
+    pub fn estimate_arguments(&self) -> Vec<String> {
+
+        let mut arguments = self.send_arguments();
+        arguments.insert(1, "-nvP".to_string());
+        arguments
+    }
+
+    pub fn receive_arguments(&self) -> Vec<String> {
+
+        let mut arguments = vec!["receive".to_string()];
+
+        if self.resumable { arguments.push("-s".to_string()); }
+        if self.force     { arguments.push("-F".to_string()); }
+
+        arguments.push(self.target.name().to_string());
+        arguments
+    }
+
+    pub fn description(&self) -> String {
+
+        if self.token.is_some() {
+            return format!("resume receive into {}", self.target.name());
+        }
+
+        let source = match &self.base {
+            Some(base) => format!("{}..{}", base, self.snapshot),
+            None => self.snapshot.to_owned(),
+        };
+
+        format!("send {} to {}", source, self.target.name())
+    }
+}
+
+// `zfs send -nvP` prints one line per stream and a final "size<TAB>bytes".
+pub fn parse_estimate(output: &str) -> Option<u64> {
+
+    output.lines()
+        .rev()
+        .filter_map(|line| line.strip_prefix("size\t"))
+        .find_map(|size| size.trim().parse::<u64>().ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_targets() {
+
+        let pools = vec!["tank".to_string(), "backup".to_string()];
+
+        assert!(matches!(Target::parse("backup/home", false, &pools), Ok(Target::Dataset(name)) if name == "backup/home"));
+        assert!(matches!(Target::parse("home.zfs", true, &pools), Ok(Target::File(path)) if path == "home.zfs"));
+        assert!(matches!(Target::parse("backup", true, &pools), Ok(Target::File(_))));
+        assert!(Target::parse("home.zfs", false, &pools).is_err());
+        assert!(Target::parse("/backup/home", false, &pools).is_err());
+    }
+
+    #[test]
+    fn builds_send_and_receive_arguments() {
+
+        let mut send = SendOptions::new("tank/home@daily-3", Target::Dataset("backup/home".to_string()));
+        assert_eq!(send.send_arguments(), ["send", "tank/home@daily-3"]);
+        assert_eq!(send.receive_arguments(), ["receive", "backup/home"]);
+
+        send.base = Some("tank/home@daily-1".to_string());
+        send.raw = true;
+        send.recursive = true;
+        assert_eq!(send.send_arguments(), ["send", "-w", "-R", "-i", "tank/home@daily-1", "tank/home@daily-3"]);
+
+        send.intermediate = true;
+        assert_eq!(send.send_arguments(), ["send", "-w", "-R", "-I", "tank/home@daily-1", "tank/home@daily-3"]);
+        assert_eq!(send.estimate_arguments(), ["send", "-nvP", "-w", "-R", "-I", "tank/home@daily-1", "tank/home@daily-3"]);
+
+        let mut send = SendOptions::new("tank/home@daily-3", Target::File("/backup/home.zfs".to_string()));
+        send.base = Some("tank/home#daily-1".to_string());
+        send.compressed = true;
+        assert_eq!(send.send_arguments(), ["send", "-c", "-i", "tank/home#daily-1", "tank/home@daily-3"]);
+        assert_eq!(send.description(), "send tank/home#daily-1..tank/home@daily-3 to /backup/home.zfs");
+
+        send.resumable = true;
+        send.force = true;
+        assert_eq!(send.receive_arguments(), ["receive", "-s", "-F", "/backup/home.zfs"]);
+    }
+
+    #[test]
+    fn resumes_with_the_token() {
+
+        let send = SendOptions::resume("1-abc-def", "backup/home");
+
+        assert_eq!(send.send_arguments(), ["send", "-t", "1-abc-def"]);
+        assert_eq!(send.receive_arguments(), ["receive", "-s", "backup/home"]);
+        assert_eq!(send.description(), "resume receive into backup/home");
+    }
+
+    #[test]
+    fn parses_send_estimates() {
+
+        let output = "incremental\tdaily-1\ttank/home@daily-3\t1048576\nsize\t1048576\n";
+        assert_eq!(parse_estimate(output), Some(1048576));
+
+        let output = "full\ttank/home@daily-1\t4096\nfull\ttank/home/docs@daily-1\t8192\nsize\t12288\n";
+        assert_eq!(parse_estimate(output), Some(12288));
+
+        assert_eq!(parse_estimate("full\ttank/home@daily-1\t4096\n"), None);
+        assert_eq!(parse_estimate(""), None);
+    }
+}
@@ -0,0 +1,192 @@
+
+use std::collections::HashSet;
+
+use crate::command::CommandResult;
+use crate::dependency;
+use crate::hold;
+use crate::schedule::Policy;
+
+// Snapshot properties the plan needs, listed with `zfs list -t snapshot`.
+pub fn properties() -> Vec<String> {
+    ["creation", "used", "userrefs", "clones"].iter().map(|property| property.to_string()).collect()
+}
+
+// What pruning the snapshots of a policy would do: the snapshots to destroy,
+// and the ones past the retention that zfs would refuse to destroy.
+#[derive(Default)]
+pub struct Plan {
+    pub destroy: Vec<String>,
+    pub held: Vec<String>,
+    pub cloned: Vec<(String, String)>,
+}
+
+impl Plan {
+
+    // The snapshots to destroy grouped by dataset, as `zfs destroy` takes
+    // several snapshots of one dataset at once.
+    pub fn by_dataset(&self) -> Vec<(String, Vec<String>)> {
+
+        let mut groups: Vec<(String, Vec<String>)> = Vec::new();
+
+        for name in self.destroy.iter() {
+            let dataset = name.split('@').next().unwrap_or("");
+            match groups.iter_mut().find(|(group, _)| group == dataset) {
+                Some((_, names)) => names.push(name.to_owned()),
+                None => groups.push((dataset.to_string(), vec![name.to_owned()])),
+            }
+        }
+
+        groups
+    }
+}
+
+// Keeps, for every rule such as "24 hourly", the newest snapshot of each of
+// the last 24 hours that have one, like `borg prune`. A rule looks at the
+// snapshots the policy took for its period, or at all of them when the
+// template has no %P. Snapshots no rule looks at are kept.
+pub fn plan(policy: &Policy, datasets: &[String], snapshots: &[CommandResult]) -> Plan {
+
+    let mut plan = Plan::default();
+
+    let creation = |snapshot: &CommandResult| snapshot.property("creation").parse::<i64>().unwrap_or(0);
+
+    for dataset in datasets {
+        let mut own: Vec<(&CommandResult, &str)> = snapshots.iter()
+            .filter_map(|snapshot| {
+                let mut parts = snapshot.name.splitn(2, '@');
+                if parts.next() != Some(dataset.as_str()) { return None }
+                parts.next().map(|name| (snapshot, name))
+            })
+            .collect();
+
+        own.sort_by_key(|(snapshot, _)| -creation(snapshot));
+
+        let mut ruled: HashSet<&str> = HashSet::new();
+        let mut kept: HashSet<&str> = HashSet::new();
+
+        for (period, count) in policy.keep.iter() {
+            let mut periods: Vec<i64> = Vec::new();
+
+            for (snapshot, _) in own.iter().filter(|(_, name)| policy.is_period(name, *period)) {
+                ruled.insert(snapshot.name.as_str());
+
+                let start = period.start(creation(snapshot));
+                if periods.contains(&start) || periods.len() == *count { continue }
+
+                periods.push(start);
+                kept.insert(snapshot.name.as_str());
+            }
+        }
+
+        let expired = own.into_iter().rev()
+            .map(|(snapshot, _)| snapshot)
+            .filter(|snapshot| ruled.contains(snapshot.name.as_str()) && !kept.contains(snapshot.name.as_str()));
+
+        for snapshot in expired {
+            let clones = snapshot.property("clones");
+
+            if hold::is_held(snapshot.property("userrefs")) {
+                plan.held.push(snapshot.name.to_owned());
+            } else if dependency::has_clones(clones) {
+                plan.cloned.push((snapshot.name.to_owned(), clones.to_string()));
+            } else {
+                plan.destroy.push(snapshot.name.to_owned());
+            }
+        }
+    }
+
+    plan
+}
+
+// Arguments of a dry run destroying snapshots of one dataset, written as
+// "dataset@first,second" the way `zfs destroy` takes them.
+pub fn estimate_arguments(snapshot_names: &[String]) -> Vec<String> {
+
+    let dataset = snapshot_names.first().and_then(|name| name.split('@').next()).unwrap_or("");
+    let names: Vec<&str> = snapshot_names.iter().filter_map(|name| name.split('@').nth(1)).collect();
+
+    vec!["destroy".to_string(), "-nvp".to_string(), format!("{}@{}", dataset, names.join(","))]
+}
+
+// The space `zfs destroy -nvp` reports it would reclaim.
+pub fn parse_reclaim(output: &str) -> Option<u64> {
+
+    output.lines()
+        .filter_map(|line| line.strip_prefix("reclaim"))
+        .find_map(|value| value.trim().parse().ok())
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::schedule::Period;
+
+    const START: i64 = 1_760_000_400;
+
+    fn snapshot(name: &str, creation: i64, userrefs: &str, clones: &str) -> CommandResult {
+
+        let mut snapshot = CommandResult::new(name.to_string());
+        snapshot.properties.insert("creation".to_string(), creation.to_string());
+        snapshot.properties.insert("userrefs".to_string(), userrefs.to_string());
+        snapshot.properties.insert("clones".to_string(), clones.to_string());
+        snapshot
+    }
+
+    fn policy() -> Policy {
+
+        let mut policy = Policy::new("tank");
+        policy.periods = vec![Period::Frequent];
+        policy.template = "%P-%M".to_string();
+        policy.keep = vec![(Period::Frequent, 2)];
+        policy
+    }
+
+    #[test]
+    fn keeps_the_newest_snapshot_of_each_period() {
+
+        let snapshots = vec![
+            snapshot("tank@frequent-00", START, "0", ""),
+            snapshot("tank@frequent-01", START + 60, "0", ""),
+            snapshot("tank@frequent-15", START + 900, "0", "-"),
+            snapshot("tank@frequent-30", START + 1800, "0", ""),
+            snapshot("tank@frequent-31", START + 1860, "0", ""),
+            snapshot("tank@frequent-45", START + 2700, "0", ""),
+            snapshot("tank@manual", START, "0", ""),
+            snapshot("other@frequent-00", START, "0", ""),
+        ];
+
+        let plan = plan(&policy(), &["tank".to_string()], &snapshots);
+
+        assert_eq!(plan.destroy, ["tank@frequent-00", "tank@frequent-01", "tank@frequent-15", "tank@frequent-30"]);
+        assert!(plan.held.is_empty());
+        assert!(plan.cloned.is_empty());
+        assert_eq!(plan.by_dataset(), [("tank".to_string(), plan.destroy.clone())]);
+    }
+
+    #[test]
+    fn reports_held_and_cloned_snapshots() {
+
+        let snapshots = vec![
+            snapshot("tank@frequent-00", START, "1", ""),
+            snapshot("tank@frequent-15", START + 900, "0", "tank/clone"),
+            snapshot("tank@frequent-30", START + 1800, "0", ""),
+            snapshot("tank@frequent-45", START + 2700, "0", ""),
+        ];
+
+        let plan = plan(&policy(), &["tank".to_string()], &snapshots);
+
+        assert!(plan.destroy.is_empty());
+        assert_eq!(plan.held, ["tank@frequent-00"]);
+        assert_eq!(plan.cloned, [("tank@frequent-15".to_string(), "tank/clone".to_string())]);
+    }
+
+    #[test]
+    fn estimates_and_parses_reclaim() {
+
+        let names = vec!["tank/home@a".to_string(), "tank/home@b".to_string()];
+        assert_eq!(estimate_arguments(&names), ["destroy", "-nvp", "tank/home@a,b"]);
+        assert_eq!(parse_reclaim("destroy\ttank/home@a\nreclaim\t4096\n"), Some(4096));
+        assert_eq!(parse_reclaim(""), None);
+    }
+}
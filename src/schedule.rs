@@ -0,0 +1,662 @@
+
+use std::fs;
+use std::mem;
+use std::path::Path;
+use std::thread;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+use regex::Regex;
+
+use crate::backend::Backend;
+use crate::column;
+use crate::command::CommandResult;
+use crate::retention;
+
+pub const CONFIG_PATH: &str = "/etc/zc/schedule.conf";
+
+pub const DEFAULT_TEMPLATE: &str = "zc-%P-%Y-%m-%d-%H%M";
+
+// The user property zfs-auto-snapshot uses, also read per period as
+// "com.sun:auto-snapshot:daily".
+pub const PROPERTY: &str = "com.sun:auto-snapshot";
+
+#[derive(Clone, Copy, PartialEq)]
+pub enum Period {
+    Frequent,
+    Hourly,
+    Daily,
+    Weekly,
+    Monthly,
+}
+
+impl Period {
+
+    pub const ALL: [Period; 5] = [Period::Frequent, Period::Hourly, Period::Daily, Period::Weekly, Period::Monthly];
+
+    pub fn label(self) -> &'static str {
+
+        match self {
+            Period::Frequent => "frequent",
+            Period::Hourly => "hourly",
+            Period::Daily => "daily",
+            Period::Weekly => "weekly",
+            Period::Monthly => "monthly",
+        }
+    }
+
+    pub fn description(self) -> &'static str {
+
+        match self {
+            Period::Frequent => "frequent (every 15 minutes)",
+            Period::Hourly => "hourly",
+            Period::Daily => "daily (at midnight)",
+            Period::Weekly => "weekly (on Monday)",
+            Period::Monthly => "monthly (on the 1st)",
+        }
+    }
+
+    fn parse(label: &str) -> Option<Period> {
+        Period::ALL.iter().copied().find(|period| period.label() == label)
+    }
+
+    // The local time the current period started at: a snapshot taken since
+    // then means the period is done.
+    pub fn start(self, now: i64) -> i64 {
+
+        if self == Period::Frequent {
+            return now - now.rem_euclid(15 * 60);
+        }
+
+        let time = now as libc::time_t;
+        let mut tm: libc::tm = unsafe { mem::zeroed() };
+
+        if unsafe { libc::localtime_r(&time, &mut tm) }.is_null() {
+            return now;
+        }
+
+        tm.tm_sec = 0;
+        tm.tm_min = 0;
+        tm.tm_isdst = -1;
+
+        match self {
+            Period::Daily => { tm.tm_hour = 0; },
+            Period::Weekly => { tm.tm_hour = 0; tm.tm_mday -= (tm.tm_wday + 6) % 7; },
+            Period::Monthly => { tm.tm_hour = 0; tm.tm_mday = 1; },
+            _ => {},
+        }
+
+        unsafe { libc::mktime(&mut tm) as i64 }
+    }
+}
+
+#[derive(Clone)]
+pub struct Policy {
+
+    pub dataset: String,
+    pub periods: Vec<Period>,
+    pub recursive: bool,
+    // Only datasets with com.sun:auto-snapshot=true instead of all but the
+    // ones set to false.
+    pub marked_only: bool,
+    pub template: String,
+    // Retention rules such as 24 hourly; none keeps every snapshot.
+    pub keep: Vec<(Period, usize)>,
+}
+
+impl Policy {
+
+    pub fn new(dataset: &str) -> Policy {
+
+        Policy {
+            dataset: dataset.to_string(),
+            periods: vec![Period::Hourly, Period::Daily],
+            recursive: false,
+            marked_only: false,
+            template: DEFAULT_TEMPLATE.to_string(),
+            keep: Vec::new(),
+        }
+    }
+
+    pub fn periods_label(&self) -> String {
+        self.periods.iter().map(|period| period.label()).collect::<Vec<&str>>().join(",")
+    }
+
+    // "hourly=24,daily=30", or "-" when every snapshot is kept.
+    pub fn keep_label(&self) -> String {
+
+        if self.keep.is_empty() { return "-".to_string() }
+        self.keep.iter().map(|(period, count)| format!("{}={}", period.label(), count)).collect::<Vec<String>>().join(",")
+    }
+
+    pub fn parse_keep(text: &str) -> Result<Vec<(Period, usize)>, String> {
+
+        if text.is_empty() || text == "-" { return Ok(Vec::new()) }
+
+        text.split(',').map(|rule| {
+            let (label, count) = rule.split_once('=').ok_or(format!("'{}' is not period=count", rule))?;
+            let period = Period::parse(label.trim()).ok_or(format!("unknown period '{}'", label.trim()))?;
+            match count.trim().parse::<usize>() {
+                Ok(count) if count > 0 => Ok((period, count)),
+                _ => Err(format!("'{}' is not a number of snapshots to keep", count.trim())),
+            }
+        }).collect()
+    }
+
+    // Whether a snapshot name (the part after '@') was made by this policy
+    // for the period; without %P in the template, for any period.
+    pub fn is_period(&self, name: &str, period: Period) -> bool {
+        self.pattern(period).is_match(name)
+    }
+
+    pub fn validate(&self) -> Result<(), String> {
+
+        if self.dataset.is_empty() || self.dataset.contains(['@', '#']) || self.dataset.contains(char::is_whitespace) {
+            return Err(format!("'{}' is not a dataset name", self.dataset));
+        }
+        if self.periods.is_empty() {
+            return Err("choose at least one period".to_string());
+        }
+        if self.template.is_empty() || self.template.contains(['@', '/', '#']) || self.template.contains(char::is_whitespace) {
+            return Err(format!("'{}' is not a valid snapshot name template", self.template));
+        }
+        if self.periods.len() > 1 && !self.template.contains("%P") {
+            return Err("the template needs %P to tell the periods apart".to_string());
+        }
+
+        Ok(())
+    }
+
+    // The name of the snapshot taken at `time`, from the template with
+    // %Y %m %d %H %M %S expanded in local time and %P as the period.
+    pub fn snapshot_name(&self, period: Period, time: i64) -> String {
+
+        let time = time as libc::time_t;
+        let mut tm: libc::tm = unsafe { mem::zeroed() };
+        unsafe { libc::localtime_r(&time, &mut tm) };
+
+        self.expand(period, |field| match field {
+            'Y' => format!("{:04}", tm.tm_year + 1900),
+            'm' => format!("{:02}", tm.tm_mon + 1),
+            'd' => format!("{:02}", tm.tm_mday),
+            'H' => format!("{:02}", tm.tm_hour),
+            'M' => format!("{:02}", tm.tm_min),
+            _ => format!("{:02}", tm.tm_sec),
+        }, |text| text.to_string())
+    }
+
+    // Matches the snapshots this policy took for a period, whatever their time.
+    fn pattern(&self, period: Period) -> Regex {
+
+        let pattern = self.expand(period, |field| if field == 'Y' { r"\d{4}".to_string() } else { r"\d{2}".to_string() }, regex::escape);
+        Regex::new(format!("^{}$", pattern).as_str()).unwrap()
+    }
+
+    fn expand<F, G>(&self, period: Period, field: F, text: G) -> String
+    where F: Fn(char) -> String, G: Fn(&str) -> String {
+
+        let mut result = String::new();
+        let mut chars = self.template.chars();
+
+        while let Some(c) = chars.next() {
+            if c != '%' {
+                result.push_str(text(c.to_string().as_str()).as_str());
+                continue;
+            }
+
+            match chars.next() {
+                Some('P') => result.push_str(text(period.label()).as_str()),
+                Some(c @ ('Y' | 'm' | 'd' | 'H' | 'M' | 'S')) => result.push_str(field(c).as_str()),
+                Some(c) => result.push_str(text(c.to_string().as_str()).as_str()),
+                None => result.push_str(text("%").as_str()),
+            }
+        }
+
+        result
+    }
+
+    // The datasets and volumes the policy snapshots for a period, given their
+    // com.sun:auto-snapshot properties.
+    pub fn datasets(&self, datasets: &[CommandResult], period: Period) -> Vec<String> {
+
+        let prefix = format!("{}/", self.dataset);
+        let period_property = format!("{}:{}", PROPERTY, period.label());
+
+        datasets.iter()
+            .filter(|dataset| dataset.name == self.dataset || (self.recursive && dataset.name.starts_with(&prefix)))
+            .filter(|dataset| {
+                let value = match dataset.property(period_property.as_str()) {
+                    "" | "-" => dataset.property(PROPERTY),
+                    value => value,
+                };
+                if self.marked_only { value == "true" } else { value != "false" }
+            })
+            .map(|dataset| dataset.name.to_owned())
+            .collect()
+    }
+
+    // The datasets whose snapshots the retention rules apply to: those the
+    // policy covers, even when they no longer take snapshots.
+    pub fn covered(&self, datasets: &[CommandResult]) -> Vec<String> {
+
+        let prefix = format!("{}/", self.dataset);
+
+        datasets.iter()
+            .filter(|dataset| dataset.name == self.dataset || (self.recursive && dataset.name.starts_with(&prefix)))
+            .map(|dataset| dataset.name.to_owned())
+            .collect()
+    }
+}
+
+pub struct Schedule {
+    pub policies: Vec<Policy>,
+}
+
+impl Schedule {
+
+    // A missing file is an empty schedule.
+    pub fn load(path: &str) -> Result<Schedule, String> {
+
+        let text = match fs::read_to_string(path) {
+            Ok(text) => text,
+            Err(error) if error.kind() == std::io::ErrorKind::NotFound => String::new(),
+            Err(error) => return Err(format!("{}: {}", path, error)),
+        };
+
+        let mut policies = Vec::new();
+
+        for (number, line) in text.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() || line.starts_with('#') { continue }
+
+            let policy = Schedule::parse_policy(line).map_err(|error| format!("{} line {}: {}", path, number + 1, error))?;
+            policies.push(policy);
+        }
+
+        Ok(Schedule { policies })
+    }
+
+    // "dataset periods recursive|single all|marked template [keep]", the
+    // periods and retention rules separated by commas.
+    fn parse_policy(line: &str) -> Result<Policy, String> {
+
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() != 5 && fields.len() != 6 {
+            return Err("expected dataset, periods, recursive or single, all or marked, template and retention".to_string());
+        }
+
+        let periods = fields[1].split(',')
+            .map(|label| Period::parse(label).ok_or(format!("unknown period '{}'", label)))
+            .collect::<Result<Vec<Period>, String>>()?;
+
+        let recursive = match fields[2] {
+            "recursive" => true,
+            "single" => false,
+            other => return Err(format!("'{}' is neither recursive nor single", other)),
+        };
+
+        let marked_only = match fields[3] {
+            "marked" => true,
+            "all" => false,
+            other => return Err(format!("'{}' is neither all nor marked", other)),
+        };
+
+        let keep = Policy::parse_keep(fields.get(5).copied().unwrap_or("-"))?;
+
+        let policy = Policy { dataset: fields[0].to_string(), periods, recursive, marked_only, template: fields[4].to_string(), keep };
+        policy.validate()?;
+        Ok(policy)
+    }
+
+    pub fn save(&self, path: &str) -> Result<(), String> {
+
+        let mut text = String::from("# zc snapshot policies, see `zc --scheduler`\n");
+        text.push_str("# dataset\tperiods\trecursive|single\tall|marked\ttemplate\tkeep\n");
+
+        for policy in self.policies.iter() {
+            text.push_str(format!("{}\t{}\t{}\t{}\t{}\t{}\n", policy.dataset, policy.periods_label(),
+                if policy.recursive { "recursive" } else { "single" },
+                if policy.marked_only { "marked" } else { "all" },
+                policy.template, policy.keep_label()).as_str());
+        }
+
+        if let Some(directory) = Path::new(path).parent() {
+            fs::create_dir_all(directory).map_err(|error| format!("{}: {}", directory.display(), error))?;
+        }
+
+        fs::write(path, text).map_err(|error| format!("{}: {}", path, error))
+    }
+
+    pub fn properties() -> Vec<String> {
+
+        let mut properties = vec![PROPERTY.to_string()];
+        properties.extend(Period::ALL.iter().map(|period| format!("{}:{}", PROPERTY, period.label())));
+        properties
+    }
+
+    // Takes the snapshots that are due: for every policy and period, one
+    // atomic `zfs snapshot` of the datasets without a snapshot of that period
+    // since it started. Then prunes the snapshots past the retention rules,
+    // or only lists them unless `prune` is set. Returns what was done, one
+    // line each.
+    pub fn run(&self, backend: &dyn Backend, now: i64, prune: bool) -> Vec<String> {
+
+        let datasets = match backend.zfs_list_properties("filesystem,volume", &Schedule::properties()) {
+            Ok(datasets) => datasets,
+            Err(output) => return vec![output.error_message()],
+        };
+
+        let snapshots = match backend.zfs_list_properties("snapshot", &retention::properties()) {
+            Ok(snapshots) => snapshots,
+            Err(output) => return vec![output.error_message()],
+        };
+
+        let mut log = Vec::new();
+
+        for policy in self.policies.iter() {
+            if !datasets.iter().any(|dataset| dataset.name == policy.dataset) {
+                log.push(format!("{}: dataset does not exist", policy.dataset));
+                continue;
+            }
+
+            for period in policy.periods.iter().copied() {
+                let start = period.start(now);
+                let pattern = policy.pattern(period);
+
+                let due: Vec<String> = policy.datasets(&datasets, period).into_iter()
+                    .filter(|dataset| !snapshots.iter().any(|snapshot| {
+                        let mut parts = snapshot.name.splitn(2, '@');
+                        parts.next() == Some(dataset.as_str()) &&
+                            parts.next().is_some_and(|name| pattern.is_match(name)) &&
+                            snapshot.property("creation").parse::<i64>().unwrap_or(0) >= start
+                    }))
+                    .collect();
+
+                if due.is_empty() { continue }
+
+                let name = policy.snapshot_name(period, now);
+                let names: Vec<String> = due.iter().map(|dataset| format!("{}@{}", dataset, name)).collect();
+
+                let output = backend.zfs_snapshot_all(&names);
+                if output.success {
+                    log.push(format!("{}: ok", output.command));
+                } else {
+                    log.push(format!("{}: {}", output.command, output.error_message()));
+                }
+            }
+        }
+
+        if self.policies.iter().all(|policy| policy.keep.is_empty()) { return log }
+
+        // The snapshots just taken count towards the retention.
+        let snapshots = match backend.zfs_list_properties("snapshot", &retention::properties()) {
+            Ok(snapshots) => snapshots,
+            Err(output) => { log.push(output.error_message()); return log },
+        };
+
+        for policy in self.policies.iter().filter(|policy| datasets.iter().any(|dataset| dataset.name == policy.dataset)) {
+            let plan = retention::plan(policy, &policy.covered(&datasets), &snapshots);
+
+            for name in plan.destroy.iter() {
+                if !prune {
+                    log.push(format!("{}: past the retention rules, run with --prune to destroy it", name));
+                    continue;
+                }
+                let output = backend.zfs_destroy(name);
+                if output.success {
+                    log.push(format!("{}: ok", output.command));
+                } else {
+                    log.push(format!("{}: {}", output.command, output.error_message()));
+                }
+            }
+            for name in plan.held.iter() {
+                log.push(format!("{}: kept, it has user holds", name));
+            }
+            for (name, clones) in plan.cloned.iter() {
+                log.push(format!("{}: kept, it has clones: {}", name, clones));
+            }
+        }
+
+        log
+    }
+}
+
+// The daemon mode: checks the schedule every minute, reading the file each
+// time so changes made from the TUI apply without a restart. Snapshots are
+// only destroyed with `prune`, otherwise the ones due are logged.
+pub fn run_scheduler(backend: &dyn Backend, path: &str, once: bool, prune: bool) {
+
+    loop {
+        let now = SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+
+        match Schedule::load(path) {
+            Ok(schedule) => {
+                for line in schedule.run(backend, now as i64, prune) {
+                    println!("{} {}", column::format_time(now as i64), line);
+                }
+            },
+            Err(error) => eprintln!("{} {}", column::format_time(now as i64), error),
+        }
+
+        if once { return }
+
+        thread::sleep(Duration::from_secs(60 - now % 60));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+    use crate::fake::Fake;
+
+    // The time of a local date, so the tests hold in any time zone.
+    fn local(year: i32, month: i32, day: i32, hour: i32, minute: i32, second: i32) -> i64 {
+
+        let mut tm: libc::tm = unsafe { mem::zeroed() };
+        tm.tm_year = year - 1900;
+        tm.tm_mon = month - 1;
+        tm.tm_mday = day;
+        tm.tm_hour = hour;
+        tm.tm_min = minute;
+        tm.tm_sec = second;
+        tm.tm_isdst = -1;
+
+        unsafe { libc::mktime(&mut tm) as i64 }
+    }
+
+    fn dataset(name: &str, properties: &[(&str, &str)]) -> CommandResult {
+
+        let mut dataset = CommandResult::new(name.to_string());
+        for (property, value) in properties {
+            dataset.properties.insert(property.to_string(), value.to_string());
+        }
+        dataset
+    }
+
+    fn now() -> i64 {
+        SystemTime::now().duration_since(UNIX_EPOCH).unwrap().as_secs() as i64
+    }
+
+    #[test]
+    fn parses_policies() {
+
+        let policy = Schedule::parse_policy("tank/home  hourly,daily  recursive  marked  auto-%P-%Y%m%d").unwrap();
+
+        assert_eq!(policy.dataset, "tank/home");
+        assert!(policy.periods == [Period::Hourly, Period::Daily]);
+        assert!(policy.recursive);
+        assert!(policy.marked_only);
+        assert_eq!(policy.template, "auto-%P-%Y%m%d");
+
+        assert!(Schedule::parse_policy("tank/home hourly recursive all").is_err());
+        assert!(Schedule::parse_policy("tank/home yearly single all x-%Y").is_err());
+        assert!(Schedule::parse_policy("tank/home daily both all x-%Y").is_err());
+        assert!(Schedule::parse_policy("tank/home daily single some x-%Y").is_err());
+        assert!(Schedule::parse_policy("tank/home hourly,daily single all x-%Y").is_err());
+        assert!(Schedule::parse_policy("tank@snap daily single all x-%Y").is_err());
+    }
+
+    #[test]
+    fn saves_and_loads_the_schedule() {
+
+        let path = std::env::temp_dir().join(format!("zc-schedule-{}/schedule.conf", std::process::id()));
+        let path = path.to_str().unwrap();
+
+        let mut weekly = Policy::new("tank/vm");
+        weekly.periods = vec![Period::Weekly];
+        weekly.marked_only = true;
+        weekly.template = "weekly-%Y%m%d".to_string();
+
+        let schedule = Schedule { policies: vec![Policy::new("tank/home"), weekly] };
+        schedule.save(path).unwrap();
+
+        let loaded = Schedule::load(path).unwrap();
+        fs::remove_dir_all(Path::new(path).parent().unwrap()).unwrap();
+
+        assert_eq!(loaded.policies.len(), 2);
+        for (loaded, saved) in loaded.policies.iter().zip(schedule.policies.iter()) {
+            assert_eq!(loaded.dataset, saved.dataset);
+            assert!(loaded.periods == saved.periods);
+            assert_eq!(loaded.recursive, saved.recursive);
+            assert_eq!(loaded.marked_only, saved.marked_only);
+            assert_eq!(loaded.template, saved.template);
+        }
+
+        assert!(Schedule::load("/nonexistent/zc/schedule.conf").unwrap().policies.is_empty());
+    }
+
+    #[test]
+    fn names_snapshots_from_the_template() {
+
+        let policy = Policy::new("tank");
+        let time = local(2026, 3, 31, 23, 45, 7);
+
+        assert_eq!(policy.snapshot_name(Period::Daily, time), "zc-daily-2026-03-31-2345");
+
+        let pattern = policy.pattern(Period::Daily);
+        assert!(pattern.is_match(policy.snapshot_name(Period::Daily, time).as_str()));
+        assert!(!pattern.is_match(policy.snapshot_name(Period::Hourly, time).as_str()));
+        assert!(!pattern.is_match("zc-daily-2026-3-31-2345"));
+        assert!(!pattern.is_match("old-zc-daily-2026-03-31-2345"));
+    }
+
+    #[test]
+    fn starts_periods_at_their_boundaries() {
+
+        let wednesday = local(2026, 3, 4, 13, 59, 59);
+
+        assert_eq!(Period::Frequent.start(wednesday), local(2026, 3, 4, 13, 45, 0));
+        assert_eq!(Period::Hourly.start(wednesday), local(2026, 3, 4, 13, 0, 0));
+        assert_eq!(Period::Daily.start(wednesday), local(2026, 3, 4, 0, 0, 0));
+        assert_eq!(Period::Weekly.start(wednesday), local(2026, 3, 2, 0, 0, 0));
+        assert_eq!(Period::Monthly.start(wednesday), local(2026, 3, 1, 0, 0, 0));
+
+        // Weeks start on Monday, across month and year ends.
+        assert_eq!(Period::Weekly.start(local(2026, 3, 1, 12, 0, 0)), local(2026, 2, 23, 0, 0, 0));
+        assert_eq!(Period::Weekly.start(local(2026, 1, 1, 12, 0, 0)), local(2025, 12, 29, 0, 0, 0));
+        assert_eq!(Period::Weekly.start(local(2026, 3, 2, 0, 0, 0)), local(2026, 3, 2, 0, 0, 0));
+
+        assert_eq!(Period::Daily.start(local(2026, 3, 31, 23, 59, 59)), local(2026, 3, 31, 0, 0, 0));
+        assert_eq!(Period::Monthly.start(local(2026, 3, 31, 23, 59, 59)), local(2026, 3, 1, 0, 0, 0));
+        assert_eq!(Period::Monthly.start(local(2026, 1, 1, 0, 0, 0)), local(2026, 1, 1, 0, 0, 0));
+    }
+
+    #[test]
+    fn period_properties_override_the_general_one() {
+
+        let daily = format!("{}:daily", PROPERTY);
+        let hourly = format!("{}:hourly", PROPERTY);
+
+        let datasets = vec![
+            dataset("tank", &[]),
+            dataset("tank/off", &[(PROPERTY, "false")]),
+            dataset("tank/daily", &[(PROPERTY, "false"), (daily.as_str(), "true")]),
+            dataset("tank/on", &[(PROPERTY, "true"), (hourly.as_str(), "false")]),
+            dataset("tankers", &[]),
+        ];
+
+        let mut policy = Policy::new("tank");
+        policy.recursive = true;
+
+        assert_eq!(policy.datasets(&datasets, Period::Daily), ["tank", "tank/daily", "tank/on"]);
+        assert_eq!(policy.datasets(&datasets, Period::Hourly), ["tank"]);
+
+        policy.marked_only = true;
+        assert_eq!(policy.datasets(&datasets, Period::Daily), ["tank/daily", "tank/on"]);
+
+        policy.recursive = false;
+        assert!(policy.datasets(&datasets, Period::Daily).is_empty());
+    }
+
+    #[test]
+    fn runs_each_period_once() {
+
+        let fake = Fake::new();
+
+        let mut policy = Policy::new("tank/home");
+        policy.periods = vec![Period::Daily];
+        policy.template = "zc-%P-%Y-%m-%d".to_string();
+
+        let schedule = Schedule { policies: vec![policy, Policy::new("tank/missing")] };
+        let now = now();
+
+        let log = schedule.run(&fake, now, false);
+        assert_eq!(log.len(), 2);
+        assert!(log[0].starts_with("zfs snapshot tank/home@zc-daily-") && log[0].ends_with(": ok"));
+        assert_eq!(log[1], "tank/missing: dataset does not exist");
+
+        // Taken already this period.
+        assert_eq!(schedule.run(&fake, now, false), ["tank/missing: dataset does not exist"]);
+
+        // Due again the next day.
+        let log = schedule.run(&fake, now + 2 * 24 * 3600, false);
+        assert_eq!(log.len(), 2);
+        assert!(log[0].ends_with(": ok"));
+    }
+
+    #[test]
+    fn prunes_only_when_enabled() {
+
+        let fake = Fake::new();
+
+        let mut policy = Policy::new("tank/home");
+        policy.periods = vec![Period::Daily];
+        policy.template = "zc-%P-%Y-%m-%d".to_string();
+        policy.keep = vec![(Period::Daily, 1)];
+
+        let schedule = Schedule { policies: vec![policy] };
+        let now = now();
+        let snapshots = |fake: &Fake| fake.zfs_snapshots().unwrap_or_default().into_iter().map(|snapshot| snapshot.name).collect::<Vec<String>>();
+
+        // Two daily snapshots, one more than the rule keeps.
+        assert_eq!(schedule.run(&fake, now, false).len(), 1);
+        let log = schedule.run(&fake, now + 2 * 24 * 3600, false);
+        assert_eq!(log.len(), 2);
+
+        let (stale, message) = log[1].split_once(": ").unwrap();
+        assert!(stale.starts_with("tank/home@zc-daily-"));
+        assert_eq!(message, "past the retention rules, run with --prune to destroy it");
+        assert!(snapshots(&fake).contains(&stale.to_string()));
+
+        assert_eq!(schedule.run(&fake, now, true), [format!("zfs destroy {}: ok", stale)]);
+        assert!(!snapshots(&fake).contains(&stale.to_string()));
+    }
+
+    #[test]
+    fn parses_retention_rules() {
+
+        let keep = Policy::parse_keep("hourly=24,daily=30").unwrap();
+        assert!(keep == [(Period::Hourly, 24), (Period::Daily, 30)]);
+        assert!(Policy::parse_keep("-").unwrap().is_empty());
+        assert!(Policy::parse_keep("daily").is_err());
+        assert!(Policy::parse_keep("yearly=1").is_err());
+        assert!(Policy::parse_keep("daily=many").is_err());
+
+        let policy = Schedule::parse_policy("tank hourly,daily single all zc-%P-%Y-%m-%d-%H%M hourly=24,daily=30").unwrap();
+        assert_eq!(policy.keep_label(), "hourly=24,daily=30");
+        assert_eq!(Policy::new("tank").keep_label(), "-");
+
+        assert!(policy.is_period("zc-hourly-2026-03-31-2300", Period::Hourly));
+        assert!(!policy.is_period("zc-hourly-2026-03-31-2300", Period::Daily));
+    }
+}
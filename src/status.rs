@@ -0,0 +1,397 @@
+
+use std::sync::OnceLock;
+
+use regex::Regex;
+
+const FIELDS: &[&str] = &["pool", "id", "state", "status", "action", "see", "scan", "remove", "checkpoint", "config", "errors"];
+
+const SECTIONS: &[&str] = &["logs", "cache", "spares", "special", "dedup"];
+
+static SCAN_PERCENT: OnceLock<Regex> = OnceLock::new();
+static SCAN_RATE: OnceLock<Regex> = OnceLock::new();
+static SCAN_ETA: OnceLock<Regex> = OnceLock::new();
+static TRIM_NOTE: OnceLock<Regex> = OnceLock::new();
+static INITIALIZE_NOTE: OnceLock<Regex> = OnceLock::new();
+
+// The expression in `cell`, compiled on first use.
+fn regex(cell: &'static OnceLock<Regex>, pattern: &str) -> &'static Regex {
+    cell.get_or_init(|| Regex::new(pattern).unwrap())
+}
+
+#[derive(Clone)]
+pub struct Vdev {
+
+    pub name: String,
+    pub depth: usize,
+    pub state: String,
+    pub read: String,
+    pub write: String,
+    pub cksum: String,
+    pub note: String,
+}
+
+impl Vdev {
+
+    // Section headers (logs, cache, spares, ...) group the vdevs below them
+    // and carry no state of their own.
+    pub fn is_section(&self) -> bool {
+        self.depth == 0 && self.state.is_empty() && SECTIONS.contains(&self.name.as_str())
+    }
+
+    pub fn is_device(&self) -> bool {
+        self.name.starts_with('/')
+    }
+
+    pub fn has_errors(&self) -> bool {
+        [&self.read, &self.write, &self.cksum].iter().any(|count| !count.is_empty() && *count != "0")
+    }
+
+    pub fn is_healthy(&self) -> bool {
+        self.state.is_empty() || self.state == "ONLINE" || self.state == "AVAIL" || self.state == "INUSE"
+    }
+}
+
+pub struct ScanProgress {
+
+    pub operation: String,
+    pub paused: bool,
+    pub percent: f64,
+    pub rate: Option<String>,
+    pub eta: Option<String>,
+}
+
+impl ScanProgress {
+
+    // "scrub 25.7% 73.8M/s 03:02:10" or "trim paused 40.0%", short enough
+    // for the Pools panel.
+    pub fn summary(&self) -> String {
+
+        if self.paused {
+            return format!("{} paused {:.1}%", self.operation, self.percent);
+        }
+
+        let mut summary = format!("{} {:.1}%", self.operation, self.percent);
+        if let Some(rate) = &self.rate { summary.push_str(format!(" {}/s", rate.trim_end_matches("/s")).as_str()); }
+        if let Some(eta) = &self.eta { summary.push_str(format!(" {}", eta).as_str()); }
+
+        summary
+    }
+}
+
+#[derive(Default)]
+pub struct PoolStatus {
+
+    pub name: String,
+    pub id: String,
+    pub state: String,
+    pub status: Option<String>,
+    pub action: Option<String>,
+    pub scan: Option<String>,
+    pub config: Vec<Vdev>,
+    pub errors: String,
+    pub error_files: Vec<String>,
+}
+
+impl PoolStatus {
+
+    // Pools listed by `zpool import -D` show "ONLINE (DESTROYED)".
+    pub fn is_destroyed(&self) -> bool {
+        self.state.contains("DESTROYED")
+    }
+
+    // "25.71% done, 03:02:10 to go" from the scan line of a running or
+    // paused scrub or resilver.
+    pub fn scan_progress(&self) -> Option<ScanProgress> {
+
+        let scan = self.scan.as_ref()?;
+
+        let percent = regex(&SCAN_PERCENT, r"([0-9.]+)% done");
+        let rate = regex(&SCAN_RATE, r"issued at ([^ ,]+)");
+        let eta = regex(&SCAN_ETA, r"([^ ,]+) to go");
+
+        let percent = percent.captures(scan)?[1].parse::<f64>().ok()?;
+
+        Some(ScanProgress {
+            operation: scan.split_whitespace().next().unwrap_or("scan").to_string(),
+            paused: scan.contains(" paused since "),
+            percent,
+            rate: rate.captures(scan).map(|captures| captures[1].to_string()),
+            eta: eta.captures(scan).map(|captures| captures[1].to_string()),
+        })
+    }
+
+    // The progress of a trim or initialize over the devices still at it, from
+    // the notes `zpool status -t -i` adds to them, such as
+    // "(25% trimmed, started at ...)" or "(60% initialized, suspended at ...)".
+    pub fn vdev_progress(&self, operation: &str) -> Option<ScanProgress> {
+
+        let note = if operation == "trim" {
+            regex(&TRIM_NOTE, r"\((\d+)% trimmed, (started|suspended) at")
+        } else {
+            regex(&INITIALIZE_NOTE, r"\((\d+)% initialized, (started|suspended) at")
+        };
+
+        let devices: Vec<(f64, bool)> = self.config.iter()
+            .filter_map(|vdev| note.captures(vdev.note.as_str()))
+            .map(|captures| (captures[1].parse::<f64>().unwrap_or(0.0), &captures[2] == "suspended"))
+            .collect();
+
+        if devices.is_empty() { return None }
+
+        Some(ScanProgress {
+            operation: operation.to_string(),
+            paused: devices.iter().all(|(_, suspended)| *suspended),
+            percent: devices.iter().map(|(percent, _)| percent).sum::<f64>() / devices.len() as f64,
+            rate: None,
+            eta: None,
+        })
+    }
+
+    // Everything running (or paused) on the pool: a scrub or resilver, a
+    // trim and an initialize.
+    pub fn activities(&self) -> Vec<ScanProgress> {
+
+        let mut activities: Vec<ScanProgress> = self.scan_progress().into_iter().collect();
+        activities.extend(self.vdev_progress("trim"));
+        activities.extend(self.vdev_progress("initialize"));
+        activities
+    }
+
+    // The top row a vdev belongs to: the pool itself or a section header.
+    pub fn section(&self, index: usize) -> &str {
+
+        self.config[..=index].iter()
+            .rev()
+            .find(|vdev| vdev.depth == 0)
+            .map(|vdev| vdev.name.as_str())
+            .unwrap_or("")
+    }
+
+    pub fn parent(&self, index: usize) -> Option<&Vdev> {
+
+        let depth = self.config.get(index)?.depth;
+        self.config[..index].iter().rev().find(|vdev| vdev.depth < depth)
+    }
+
+    pub fn spares(&self) -> Vec<&Vdev> {
+
+        self.config.iter().enumerate()
+            .filter(|(i, vdev)| vdev.is_device() && vdev.state == "AVAIL" && self.section(*i) == "spares")
+            .map(|(_, vdev)| vdev)
+            .collect()
+    }
+}
+
+// Parses the output of `zpool status -P <pool>`. Every field is a "key: value"
+// block whose continuation lines are indented with a tab; the config block
+// is the vdev table, indented two spaces per level.
+pub fn parse_status(output: &str) -> PoolStatus {
+
+    let mut status = PoolStatus::default();
+    let mut field = String::new();
+
+    for line in output.lines() {
+
+        let trimmed = line.trim();
+
+        if let Some((key, value)) = field_line(line) {
+            field = key.to_string();
+            let value = value.trim().to_string();
+
+            match key {
+                "pool"   => status.name = value,
+                "id"     => status.id = value,
+                "state"  => status.state = value,
+                "status" => status.status = Some(value),
+                "action" => status.action = Some(value),
+                "scan"   => status.scan = Some(value),
+                "errors" => status.errors = value,
+                _ => {},
+            }
+            continue;
+        }
+
+        if trimmed.is_empty() { continue }
+
+        match field.as_str() {
+            "status" => append(&mut status.status, trimmed, " "),
+            "action" => append(&mut status.action, trimmed, " "),
+            "scan"   => append(&mut status.scan, trimmed, "\n"),
+            "errors" => status.error_files.push(trimmed.to_string()),
+            "config" => {
+                if let Some(vdev) = parse_vdev(line) {
+                    if vdev.name != "NAME" { status.config.push(vdev); }
+                }
+            },
+            _ => {},
+        }
+    }
+
+    status
+}
+
+// `zpool import` and `zpool status` without a pool name list every pool as a
+// block starting with "pool:", laid out like the output for a single pool.
+pub fn parse_pools(output: &str) -> Vec<PoolStatus> {
+
+    let mut blocks: Vec<String> = Vec::new();
+
+    for line in output.lines() {
+        if field_line(line).is_some_and(|(key, _)| key == "pool") {
+            blocks.push(String::new());
+        }
+        if let Some(block) = blocks.last_mut() {
+            block.push_str(line);
+            block.push('\n');
+        }
+    }
+
+    blocks.iter().map(|block| parse_status(block)).collect()
+}
+
+fn field_line(line: &str) -> Option<(&str, &str)> {
+
+    if line.starts_with('\t') { return None }
+
+    let (key, value) = line.split_once(':')?;
+    let key = key.trim_start_matches(' ');
+
+    if !FIELDS.contains(&key) { return None }
+
+    Some((key, value))
+}
+
+fn append(field: &mut Option<String>, text: &str, separator: &str) {
+
+    match field {
+        Some(value) if !value.is_empty() => { value.push_str(separator); value.push_str(text); },
+        _ => *field = Some(text.to_string()),
+    }
+}
+
+fn parse_vdev(line: &str) -> Option<Vdev> {
+
+    let line = line.strip_prefix('\t').unwrap_or(line);
+    let indent = line.len() - line.trim_start_matches(' ').len();
+
+    let mut fields = line.split_whitespace();
+    let name = fields.next()?.to_string();
+
+    let state = fields.next().unwrap_or("").to_string();
+    let read = fields.next().unwrap_or("").to_string();
+    let write = fields.next().unwrap_or("").to_string();
+    let cksum = fields.next().unwrap_or("").to_string();
+    let note = fields.collect::<Vec<&str>>().join(" ");
+
+    Some(Vdev { name, depth: indent / 2, state, read, write, cksum, note })
+}
+
+#[cfg(test)]
+mod tests {
+
+    use super::*;
+
+    const STATUS: &str = "  pool: tank
+ state: DEGRADED
+status: One or more devices could not be used because the label is missing or
+\tinvalid.
+action: Replace the device using 'zpool replace'.
+  scan: scrub in progress since Sun Oct 18 08:00:00 2026
+\t1.20T scanned at 150M/s, 514G issued at 73.8M/s, 2.00T total
+\t0B repaired, 25.71% done, 03:02:10 to go
+config:
+
+\tNAME              STATE     READ WRITE CKSUM
+\ttank              DEGRADED     0     0     0
+\t  mirror-0        DEGRADED     0     0     0
+\t    /dev/sda      ONLINE       0     0     0
+\t    /dev/sdb      UNAVAIL      0     0     3  was /dev/sdb1
+\tlogs
+\t  /dev/nvme0n1    ONLINE       0     0     0  (25% trimmed, started at Sun Oct 18 08:00:00 2026)
+\tspares
+\t  /dev/sdc        AVAIL
+
+errors: No known data errors
+";
+
+    #[test]
+    fn parses_fields_and_continuation_lines() {
+
+        let status = parse_status(STATUS);
+
+        assert_eq!(status.name, "tank");
+        assert_eq!(status.state, "DEGRADED");
+        assert_eq!(status.status.as_deref(), Some("One or more devices could not be used because the label is missing or invalid."));
+        assert_eq!(status.errors, "No known data errors");
+        assert!(status.error_files.is_empty());
+    }
+
+    #[test]
+    fn parses_the_vdev_tree() {
+
+        let status = parse_status(STATUS);
+        let names: Vec<&str> = status.config.iter().map(|vdev| vdev.name.as_str()).collect();
+
+        assert_eq!(names, ["tank", "mirror-0", "/dev/sda", "/dev/sdb", "logs", "/dev/nvme0n1", "spares", "/dev/sdc"]);
+        assert_eq!(status.config[2].depth, 2);
+        assert_eq!(status.config[3].note, "was /dev/sdb1");
+        assert!(status.config[3].has_errors());
+        assert!(!status.config[3].is_healthy());
+        assert!(status.config[4].is_section());
+        assert!(status.config[7].is_healthy());
+    }
+
+    #[test]
+    fn reads_scan_progress() {
+
+        let scan = parse_status(STATUS).scan_progress().unwrap();
+
+        assert_eq!(scan.percent, 25.71);
+        assert_eq!(scan.rate.as_deref(), Some("73.8M/s"));
+        assert_eq!(scan.eta.as_deref(), Some("03:02:10"));
+        assert!(parse_status("  pool: tank\n  scan: none requested\n").scan_progress().is_none());
+    }
+
+    #[test]
+    fn finds_sections_parents_and_spares() {
+
+        let status = parse_status(STATUS);
+
+        assert!(status.config[2].is_device());
+        assert!(!status.config[1].is_device());
+        assert_eq!(status.section(3), "tank");
+        assert_eq!(status.section(5), "logs");
+        assert_eq!(status.parent(3).map(|vdev| vdev.name.as_str()), Some("mirror-0"));
+        assert!(status.parent(0).is_none());
+        assert_eq!(status.spares().len(), 1);
+    }
+
+    #[test]
+    fn splits_several_pools() {
+
+        let output = format!("{}\n   pool: backup\n     id: 1234\n  state: ONLINE (DESTROYED)\n", STATUS);
+        let pools = parse_pools(output.as_str());
+
+        assert_eq!(pools.len(), 2);
+        assert_eq!(pools[1].id, "1234");
+        assert!(pools[1].is_destroyed());
+        assert!(!pools[0].is_destroyed());
+    }
+
+    #[test]
+    fn reads_scan_and_device_activities() {
+
+        let status = parse_status(STATUS);
+        let scan = status.scan_progress().unwrap();
+
+        assert_eq!(scan.operation, "scrub");
+        assert!(!scan.paused);
+        assert_eq!(scan.summary(), "scrub 25.7% 73.8M/s 03:02:10");
+
+        let trim = status.vdev_progress("trim").unwrap();
+        assert_eq!(trim.percent, 25.0);
+        assert!(!trim.paused);
+        assert!(status.vdev_progress("initialize").is_none());
+        assert_eq!(status.activities().len(), 2);
+    }
+}